@@ -1,11 +1,12 @@
-/// Split paths with a line or line segment.
+//! Split paths with a line, a line segment or a grid.
 
 use crate::math::*;
-use crate::geom::{Line, LineSegment};
+use crate::geom::{Line, LineSegment, BezierSegment, QuadraticBezierSegment, CubicBezierSegment};
 use crate::advanced_path::*;
 use crate::path::*;
 use crate::path::iterator::PathIterator;
 use crate::path::builder::PathBuilder;
+use crate::hit_test::hit_test_path;
 use std::cmp::PartialOrd;
 use std::mem;
 
@@ -18,6 +19,68 @@ struct IntersectingEdge {
     positive: bool,
 }
 
+/// A regular grid of cells, used to split paths with `Splitter::split_with_grid`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Grid {
+    /// Position of the top-left corner of the first cell.
+    pub origin: Point,
+    /// Size of each cell.
+    pub cell_size: Size,
+    /// Number of cells along the x axis.
+    pub columns: u32,
+    /// Number of cells along the y axis.
+    pub rows: u32,
+}
+
+impl Grid {
+    /// The rectangle covered by a cell.
+    ///
+    /// The sides of adjacent cells are computed the same way so that they match exactly.
+    pub fn cell_rect(&self, column: u32, row: u32) -> Rect {
+        let min = point(self.column_x(column as i32), self.row_y(row as i32));
+        let max = point(self.column_x(column as i32 + 1), self.row_y(row as i32 + 1));
+        Rect::new(min, (max - min).to_size())
+    }
+
+    fn column_x(&self, column: i32) -> f32 {
+        self.origin.x + column as f32 * self.cell_size.width
+    }
+
+    fn row_y(&self, row: i32) -> f32 {
+        self.origin.y + row as f32 * self.cell_size.height
+    }
+}
+
+// A piece of a path that is contained in a single cell of the grid.
+#[derive(Copy, Clone, Debug)]
+struct GridPiece {
+    segment: BezierSegment<f32>,
+    column: i32,
+    row: i32,
+}
+
+// The range of pieces of a sub-path, and the range of cells it touches.
+#[derive(Clone, Debug)]
+struct GridSubPath {
+    pieces: std::ops::Range<usize>,
+    columns: (i32, i32),
+    rows: (i32, i32),
+}
+
+// A position where a segment crosses the lines of the grid.
+#[derive(Copy, Clone, Debug)]
+struct GridCut {
+    t: f32,
+    x: Option<f32>,
+    y: Option<f32>,
+}
+
+#[derive(Copy, Clone, Debug)]
+enum CellOp {
+    Line(Point),
+    Curve(BezierSegment<f32>),
+}
+
 /// A context object that can split paths.
 ///
 /// When splitting several paths, reusing the `Splitter` object can improve performance
@@ -26,6 +89,10 @@ pub struct Splitter {
     intersecting_edges: Vec<IntersectingEdge>,
     point_buffer: Vec<Point>,
     flattening_tolerance: f32,
+    grid_pieces: Vec<GridPiece>,
+    grid_sub_paths: Vec<GridSubPath>,
+    grid_cuts: Vec<GridCut>,
+    cell_ops: Vec<CellOp>,
 }
 
 impl Splitter {
@@ -35,6 +102,10 @@ impl Splitter {
             intersecting_edges: Vec::new(),
             point_buffer: Vec::new(),
             flattening_tolerance: 0.1,
+            grid_pieces: Vec::new(),
+            grid_sub_paths: Vec::new(),
+            grid_cuts: Vec::new(),
+            cell_ops: Vec::new(),
         }
     }
 
@@ -171,6 +242,285 @@ impl Splitter {
         self.split(line, &mut path)
     }
 
+    /// Splits a path into the cells of a grid.
+    ///
+    /// Returns one `Path` per cell, in row-major order (the path of the cell at a given
+    /// column and row is at index `row * grid.columns + column`).
+    ///
+    /// The path is considered to be filled: open sub-paths are implicitly closed. The parts
+    /// of the path that are outside of a cell are projected onto its sides, so every point
+    /// of the cell has the same winding number as in the original path, regardless of the
+    /// fill rule. Edges are cut exactly on the lines of the grid so that the paths of
+    /// adjacent cells line up.
+    ///
+    /// Unlike `split_with_line`, curves are not flattened. Curves that cross the lines of the
+    /// grid are split into several curves.
+    pub fn split_with_grid<'l, P>(&mut self, path: P, grid: &Grid) -> Vec<Path>
+    where P: Into<PathSlice<'l>> {
+        self.split_with_grid_impl(path.into(), grid)
+    }
+
+    fn split_with_grid_impl(&mut self, path: PathSlice, grid: &Grid) -> Vec<Path> {
+        self.grid_pieces.clear();
+        self.grid_sub_paths.clear();
+
+        let mut first = point(0.0, 0.0);
+        let mut current = first;
+        let mut sub_path_start = 0;
+        for evt in path.iter() {
+            match evt {
+                PathEvent::MoveTo(to) => {
+                    self.close_grid_sub_path(current, first, sub_path_start, grid);
+                    sub_path_start = self.grid_pieces.len();
+                    first = to;
+                    current = to;
+                }
+                PathEvent::Line(segment) => {
+                    self.add_grid_segment(segment.into(), grid);
+                    current = segment.to;
+                }
+                PathEvent::Quadratic(segment) => {
+                    self.add_grid_segment(segment.into(), grid);
+                    current = segment.to;
+                }
+                PathEvent::Cubic(segment) => {
+                    self.add_grid_segment(segment.into(), grid);
+                    current = segment.to;
+                }
+                PathEvent::Close(segment) => {
+                    self.add_grid_segment(segment.into(), grid);
+                    current = first;
+                }
+            }
+        }
+        self.close_grid_sub_path(current, first, sub_path_start, grid);
+
+        let mut cells = Vec::with_capacity((grid.columns * grid.rows) as usize);
+        for row in 0..grid.rows {
+            for column in 0..grid.columns {
+                cells.push(self.build_cell(grid, column, row));
+            }
+        }
+
+        cells
+    }
+
+    fn close_grid_sub_path(&mut self, current: Point, first: Point, start: usize, grid: &Grid) {
+        if current != first {
+            self.add_grid_segment(LineSegment { from: current, to: first }.into(), grid);
+        }
+
+        let pieces = start..self.grid_pieces.len();
+        if start == self.grid_pieces.len() {
+            return;
+        }
+
+        let mut columns = (std::i32::MAX, std::i32::MIN);
+        let mut rows = (std::i32::MAX, std::i32::MIN);
+        for piece in &self.grid_pieces[pieces.clone()] {
+            columns = (columns.0.min(piece.column), columns.1.max(piece.column));
+            rows = (rows.0.min(piece.row), rows.1.max(piece.row));
+        }
+
+        self.grid_sub_paths.push(GridSubPath { pieces, columns, rows });
+    }
+
+    // Split a segment where it crosses the lines of the grid.
+    fn add_grid_segment(&mut self, segment: BezierSegment<f32>, grid: &Grid) {
+        if segment.from() == segment.to() {
+            if let BezierSegment::Linear(..) = segment {
+                return;
+            }
+        }
+
+        self.grid_cuts.clear();
+        let bounds = bounding_rect(&segment);
+        let columns = grid_lines(bounds.min_x(), bounds.max_x(), grid.origin.x, grid.cell_size.width, grid.columns);
+        for column in columns {
+            let x = grid.column_x(column);
+            let cuts = &mut self.grid_cuts;
+            solve_t(&segment, x, true, |t| cuts.push(GridCut { t, x: Some(x), y: None }));
+        }
+        let rows = grid_lines(bounds.min_y(), bounds.max_y(), grid.origin.y, grid.cell_size.height, grid.rows);
+        for row in rows {
+            let y = grid.row_y(row);
+            let cuts = &mut self.grid_cuts;
+            solve_t(&segment, y, false, |t| cuts.push(GridCut { t, x: None, y: Some(y) }));
+        }
+
+        // solve_t only reports parameters in ]0, 1[, so there is no NaN here.
+        self.grid_cuts.sort_by(|a, b| a.t.partial_cmp(&b.t).unwrap());
+
+        let mut remaining = segment;
+        let mut t0 = 0.0;
+        let mut i = 0;
+        while i < self.grid_cuts.len() {
+            // Merge the cuts at the same position, typically where the segment goes
+            // through the corner of a cell.
+            let mut cut = self.grid_cuts[i];
+            i += 1;
+            while i < self.grid_cuts.len() && self.grid_cuts[i].t - cut.t < 0.00001 {
+                cut.x = cut.x.or(self.grid_cuts[i].x);
+                cut.y = cut.y.or(self.grid_cuts[i].y);
+                i += 1;
+            }
+
+            let t = (cut.t - t0) / (1.0 - t0);
+            let (before, after) = remaining.split(t);
+            let p = before.to();
+            let p = point(cut.x.unwrap_or(p.x), cut.y.unwrap_or(p.y));
+            self.add_grid_piece(set_to(before, p), grid);
+            remaining = set_from(after, p);
+            t0 = cut.t;
+        }
+
+        self.add_grid_piece(remaining, grid);
+    }
+
+    fn add_grid_piece(&mut self, segment: BezierSegment<f32>, grid: &Grid) {
+        let p = segment.sample(0.5);
+        self.grid_pieces.push(GridPiece {
+            segment,
+            column: ((p.x - grid.origin.x) / grid.cell_size.width).floor() as i32,
+            row: ((p.y - grid.origin.y) / grid.cell_size.height).floor() as i32,
+        });
+    }
+
+    fn build_cell(&mut self, grid: &Grid, column: u32, row: u32) -> Path {
+        let rect = grid.cell_rect(column, row);
+        let min = rect.min();
+        let max = rect.max();
+        let clamp = |p: Point| point(p.x.max(min.x).min(max.x), p.y.max(min.y).min(max.y));
+        let (column, row) = (column as i32, row as i32);
+
+        let mut builder = Path::builder();
+        for sub_path in &self.grid_sub_paths {
+            if column < sub_path.columns.0 || column > sub_path.columns.1
+                || row < sub_path.rows.0 || row > sub_path.rows.1 {
+                continue;
+            }
+
+            let pieces = &self.grid_pieces[sub_path.pieces.clone()];
+            let start = clamp(pieces[0].segment.from());
+            let mut has_curves = false;
+            self.cell_ops.clear();
+            for piece in pieces {
+                if piece.column == column && piece.row == row {
+                    let from = piece.segment.from();
+                    if cell_ops_end(&self.cell_ops, start) != from {
+                        self.cell_ops.push(CellOp::Line(from));
+                    }
+                    self.cell_ops.push(CellOp::Curve(piece.segment));
+                    has_curves = true;
+                } else {
+                    // Outside of the cell, project the piece onto the closest side.
+                    add_cell_line(&mut self.cell_ops, start, clamp(piece.segment.to()));
+                }
+            }
+
+            // Drop the sub-paths that are reduced to nothing.
+            if !has_curves {
+                let mut area = 0.0;
+                let mut prev = start;
+                for op in &self.cell_ops {
+                    if let CellOp::Line(p) = *op {
+                        area += prev.to_vector().cross(p.to_vector());
+                        prev = p;
+                    }
+                }
+                area += prev.to_vector().cross(start.to_vector());
+                if area.abs() * 0.5 <= rect.size.area() * 0.000001 {
+                    continue;
+                }
+            }
+
+            builder.move_to(start);
+            for op in &self.cell_ops {
+                match *op {
+                    CellOp::Line(to) => { builder.line_to(to); }
+                    CellOp::Curve(BezierSegment::Linear(segment)) => { builder.line_to(segment.to); }
+                    CellOp::Curve(BezierSegment::Quadratic(segment)) => {
+                        builder.quadratic_bezier_to(segment.ctrl, segment.to);
+                    }
+                    CellOp::Curve(BezierSegment::Cubic(segment)) => {
+                        builder.cubic_bezier_to(segment.ctrl1, segment.ctrl2, segment.to);
+                    }
+                }
+            }
+            builder.close();
+        }
+
+        builder.build()
+    }
+
+    /// Splits a path along another path, for example to implement a knife tool.
+    ///
+    /// The sub-paths of `path` are considered closed while `knife` is typically an open path.
+    /// Returns the pieces of `path` as closed `Path` objects. The parts of the knife that are
+    /// outside of `path`, or that end inside of it without cutting it, are ignored.
+    ///
+    /// `path` is expected not to self-intersect, and the sub-paths that represent holes to
+    /// have the opposite orientation of the sub-paths that contain them.
+    ///
+    /// Unlike `split_with_line`, curves are not flattened: the intersections are computed
+    /// between the curves of the two paths, which are split at these positions.
+    pub fn split_with_path<'l, 'k, P, K>(&mut self, path: P, knife: K) -> Vec<Path>
+    where P: Into<PathSlice<'l>>, K: Into<PathSlice<'k>> {
+        self.split_with_path_impl(path.into(), knife.into())
+    }
+
+    fn split_with_path_impl(&mut self, path: PathSlice, knife: PathSlice) -> Vec<Path> {
+        let mut segments = Vec::new();
+        collect_segments(path, true, &mut segments);
+        let num_path_segments = segments.len();
+        collect_segments(knife, false, &mut segments);
+
+        // Find where the knife intersects the path and itself.
+        let mut cuts = Vec::new();
+        for k in num_path_segments..segments.len() {
+            for s in 0..k {
+                let (a, b) = (&segments[s], &segments[k]);
+                segment_intersections(a, b, &mut |ta, tb| {
+                    let p = intersection_point(a, ta, b, tb);
+                    cuts.push((s, ta, p));
+                    cuts.push((k, tb, p));
+                });
+            }
+        }
+        cuts.sort_by(|a, b| (a.0, a.1).partial_cmp(&(b.0, b.1)).unwrap());
+
+        // Split the segments at the intersections.
+        let mut graph = KnifeGraph::new();
+        let mut c = 0;
+        for (idx, segment) in segments.iter().enumerate() {
+            let is_knife = idx >= num_path_segments;
+            let mut remaining = *segment;
+            let mut t0 = 0.0;
+            while c < cuts.len() && cuts[c].0 == idx {
+                let (_, t, p) = cuts[c];
+                c += 1;
+                if t - t0 < 0.00001 || t > 0.99999 {
+                    continue;
+                }
+                let (before, after) = remaining.split((t - t0) / (1.0 - t0));
+                graph.add_piece(set_to(before, p), is_knife);
+                remaining = set_from(after, p);
+                t0 = t;
+            }
+            graph.add_piece(remaining, is_knife);
+        }
+
+        // Only keep the parts of the knife that separate pieces of the path.
+        let tolerance = self.flattening_tolerance;
+        graph.pieces.retain(|piece| {
+            !piece.is_knife
+                || hit_test_path(&piece.segment.sample(0.5), path.iter(), FillRule::NonZero, tolerance)
+        });
+        graph.remove_dangling_pieces();
+
+        graph.build_paths()
+    }
+
     fn split(&mut self, line: &Line<f32>, path: &mut AdvancedPath) -> (Path, Path) {
         // Sort the intersecting edges along the segment.
         self.intersecting_edges.sort_by(|a, b| { a.d.partial_cmp(&b.d).unwrap() });
@@ -377,6 +727,433 @@ fn from_advanced_path(adv: &AdvancedPath, line: &Line<f32>)-> (Path, Path) {
     (p1.build(), p2.build())
 }
 
+// The range of grid lines between two coordinates.
+fn grid_lines(min: f32, max: f32, origin: f32, cell_size: f32, count: u32) -> std::ops::Range<i32> {
+    let first = ((min - origin) / cell_size).ceil().max(0.0) as i32;
+    let last = ((max - origin) / cell_size).floor().min(count as f32) as i32;
+
+    first..(last + 1).max(first)
+}
+
+fn bounding_rect(segment: &BezierSegment<f32>) -> Rect {
+    match *segment {
+        BezierSegment::Linear(segment) => segment.bounding_rect(),
+        BezierSegment::Quadratic(segment) => segment.fast_bounding_rect(),
+        BezierSegment::Cubic(segment) => segment.fast_bounding_rect(),
+    }
+}
+
+// Call `cb` with the parameters at which a segment crosses a vertical or horizontal
+// line, excluding its endpoints.
+fn solve_t<F: FnMut(f32)>(segment: &BezierSegment<f32>, value: f32, vertical: bool, mut cb: F) {
+    // Also filters out the NaN parameters of degenerate curves.
+    let mut cb = |t: f32| if t > 0.0 && t < 1.0 { cb(t) };
+    match *segment {
+        BezierSegment::Linear(segment) => {
+            let (from, to) = if vertical { (segment.from.x, segment.to.x) } else { (segment.from.y, segment.to.y) };
+            if from != to {
+                cb((value - from) / (to - from));
+            }
+        }
+        BezierSegment::Quadratic(segment) => {
+            let cubic = segment.to_cubic();
+            let ts = if vertical { cubic.solve_t_for_x(value) } else { cubic.solve_t_for_y(value) };
+            ts.into_iter().for_each(cb);
+        }
+        BezierSegment::Cubic(segment) => {
+            let ts = if vertical { segment.solve_t_for_x(value) } else { segment.solve_t_for_y(value) };
+            ts.into_iter().for_each(cb);
+        }
+    }
+}
+
+fn set_from(segment: BezierSegment<f32>, from: Point) -> BezierSegment<f32> {
+    match segment {
+        BezierSegment::Linear(s) => BezierSegment::Linear(LineSegment { from, ..s }),
+        BezierSegment::Quadratic(s) => BezierSegment::Quadratic(QuadraticBezierSegment { from, ..s }),
+        BezierSegment::Cubic(s) => BezierSegment::Cubic(CubicBezierSegment { from, ..s }),
+    }
+}
+
+fn set_to(segment: BezierSegment<f32>, to: Point) -> BezierSegment<f32> {
+    match segment {
+        BezierSegment::Linear(s) => BezierSegment::Linear(LineSegment { to, ..s }),
+        BezierSegment::Quadratic(s) => BezierSegment::Quadratic(QuadraticBezierSegment { to, ..s }),
+        BezierSegment::Cubic(s) => BezierSegment::Cubic(CubicBezierSegment { to, ..s }),
+    }
+}
+
+fn cell_ops_end(ops: &[CellOp], start: Point) -> Point {
+    match ops.last() {
+        Some(CellOp::Line(p)) => *p,
+        Some(CellOp::Curve(segment)) => segment.to(),
+        None => start,
+    }
+}
+
+// Add a line along the sides of a cell, merging it with the previous one when they
+// are on the same side.
+fn add_cell_line(ops: &mut Vec<CellOp>, start: Point, to: Point) {
+    if cell_ops_end(ops, start) == to {
+        return;
+    }
+
+    if let Some(&CellOp::Line(last)) = ops.last() {
+        let prev = cell_ops_end(&ops[..ops.len() - 1], start);
+        if (prev.x == last.x && last.x == to.x) || (prev.y == last.y && last.y == to.y) {
+            ops.pop();
+            if cell_ops_end(ops, start) == to {
+                return;
+            }
+        }
+    }
+
+    ops.push(CellOp::Line(to));
+}
+
+// Push the segments of a path, closing its sub-paths if `close` is true.
+fn collect_segments(path: PathSlice, close: bool, segments: &mut Vec<BezierSegment<f32>>) {
+    let mut first = point(0.0, 0.0);
+    let mut current = first;
+    let close_sub_path = |current: Point, first: Point, segments: &mut Vec<BezierSegment<f32>>| {
+        if close && current != first {
+            segments.push(LineSegment { from: current, to: first }.into());
+        }
+    };
+    for evt in path.iter() {
+        match evt {
+            PathEvent::MoveTo(to) => {
+                close_sub_path(current, first, segments);
+                first = to;
+                current = to;
+            }
+            PathEvent::Line(segment) | PathEvent::Close(segment) => {
+                if segment.from != segment.to {
+                    segments.push(segment.into());
+                }
+                current = segment.to;
+            }
+            PathEvent::Quadratic(segment) => {
+                segments.push(segment.into());
+                current = segment.to;
+            }
+            PathEvent::Cubic(segment) => {
+                segments.push(segment.into());
+                current = segment.to;
+            }
+        }
+    }
+    close_sub_path(current, first, segments);
+}
+
+fn segment_intersections(a: &BezierSegment<f32>, b: &BezierSegment<f32>, cb: &mut dyn FnMut(f32, f32)) {
+    // Degenerate curves can produce NaN parameters, which would break the sorting
+    // of the cuts.
+    let cb = &mut |ta: f32, tb: f32| if ta.is_finite() && tb.is_finite() { cb(ta, tb) };
+    match (a, b) {
+        (BezierSegment::Linear(a), BezierSegment::Linear(b)) => {
+            if let Some((ta, tb)) = a.intersection_t(b) {
+                cb(ta, tb);
+            }
+        }
+        (BezierSegment::Linear(a), _) => {
+            for (tb, ta) in to_cubic(b).line_segment_intersections_t(a) {
+                cb(ta, tb);
+            }
+        }
+        (_, BezierSegment::Linear(b)) => {
+            for (ta, tb) in to_cubic(a).line_segment_intersections_t(b) {
+                cb(ta, tb);
+            }
+        }
+        _ => {
+            for (ta, tb) in to_cubic(a).cubic_intersections_t(&to_cubic(b)) {
+                cb(ta, tb);
+            }
+        }
+    }
+}
+
+// The intersection point of two segments, snapped to their endpoints when the
+// intersection is close enough to them.
+fn intersection_point(a: &BezierSegment<f32>, ta: f32, b: &BezierSegment<f32>, tb: f32) -> Point {
+    const EPSILON: f32 = 0.00001;
+    if ta < EPSILON {
+        a.from()
+    } else if ta > 1.0 - EPSILON {
+        a.to()
+    } else if tb < EPSILON {
+        b.from()
+    } else if tb > 1.0 - EPSILON {
+        b.to()
+    } else {
+        a.sample(ta)
+    }
+}
+
+fn to_cubic(segment: &BezierSegment<f32>) -> CubicBezierSegment<f32> {
+    match *segment {
+        BezierSegment::Linear(s) => CubicBezierSegment {
+            from: s.from,
+            ctrl1: s.sample(1.0 / 3.0),
+            ctrl2: s.sample(2.0 / 3.0),
+            to: s.to,
+        },
+        BezierSegment::Quadratic(s) => s.to_cubic(),
+        BezierSegment::Cubic(s) => s,
+    }
+}
+
+fn flip(segment: &BezierSegment<f32>) -> BezierSegment<f32> {
+    match *segment {
+        BezierSegment::Linear(s) => BezierSegment::Linear(s.flip()),
+        BezierSegment::Quadratic(s) => BezierSegment::Quadratic(s.flip()),
+        BezierSegment::Cubic(s) => BezierSegment::Cubic(s.flip()),
+    }
+}
+
+fn start_tangent(segment: &BezierSegment<f32>) -> Vector {
+    let (from, ctrl1, ctrl2, to) = match *segment {
+        BezierSegment::Linear(s) => (s.from, s.to, s.to, s.to),
+        BezierSegment::Quadratic(s) => (s.from, s.ctrl, s.ctrl, s.to),
+        BezierSegment::Cubic(s) => (s.from, s.ctrl1, s.ctrl2, s.to),
+    };
+
+    if ctrl1 != from {
+        ctrl1 - from
+    } else if ctrl2 != from {
+        ctrl2 - from
+    } else {
+        to - from
+    }
+}
+
+fn end_tangent(segment: &BezierSegment<f32>) -> Vector {
+    -start_tangent(&flip(segment))
+}
+
+// The signed area between the segment and the origin, approximated with a few samples.
+fn segment_area(segment: &BezierSegment<f32>) -> f32 {
+    let n = match *segment {
+        BezierSegment::Linear(..) => 1,
+        _ => 16,
+    };
+    let mut area = 0.0;
+    let mut prev = segment.from();
+    for i in 1..=n {
+        let p = segment.sample(i as f32 / n as f32);
+        area += prev.to_vector().cross(p.to_vector()) * 0.5;
+        prev = p;
+    }
+
+    area
+}
+
+struct KnifePiece {
+    segment: BezierSegment<f32>,
+    from: usize,
+    to: usize,
+    is_knife: bool,
+}
+
+struct KnifeHalfEdge {
+    segment: BezierSegment<f32>,
+    to: usize,
+    twin: Option<usize>,
+}
+
+// The pieces of the path and of the knife, connected at their endpoints.
+struct KnifeGraph {
+    pieces: Vec<KnifePiece>,
+    nodes: std::collections::HashMap<(u32, u32), usize>,
+    positions: Vec<Point>,
+}
+
+impl KnifeGraph {
+    fn new() -> Self {
+        KnifeGraph {
+            pieces: Vec::new(),
+            nodes: std::collections::HashMap::new(),
+            positions: Vec::new(),
+        }
+    }
+
+    fn node(&mut self, p: Point) -> usize {
+        let positions = &mut self.positions;
+        *self.nodes.entry((p.x.to_bits(), p.y.to_bits())).or_insert_with(|| {
+            positions.push(p);
+            positions.len() - 1
+        })
+    }
+
+    fn add_piece(&mut self, segment: BezierSegment<f32>, is_knife: bool) {
+        let from = self.node(segment.from());
+        let to = self.node(segment.to());
+        if from == to {
+            return;
+        }
+
+        self.pieces.push(KnifePiece { segment, from, to, is_knife });
+    }
+
+    // Remove the parts of the knife that end without reaching the path or another part
+    // of the knife.
+    fn remove_dangling_pieces(&mut self) {
+        let mut degrees = vec![0; self.positions.len()];
+        for piece in &self.pieces {
+            degrees[piece.from] += 1;
+            degrees[piece.to] += 1;
+        }
+
+        loop {
+            let mut removed = false;
+            let pieces = mem::replace(&mut self.pieces, Vec::new());
+            for piece in pieces {
+                if piece.is_knife && (degrees[piece.from] == 1 || degrees[piece.to] == 1) {
+                    degrees[piece.from] -= 1;
+                    degrees[piece.to] -= 1;
+                    removed = true;
+                } else {
+                    self.pieces.push(piece);
+                }
+            }
+
+            if !removed {
+                return;
+            }
+        }
+    }
+
+    fn build_paths(&self) -> Vec<Path> {
+        // The sub-paths of the path go around its inside in this direction.
+        let mut interior_sign = 0.0;
+        for piece in &self.pieces {
+            if !piece.is_knife {
+                interior_sign += segment_area(&piece.segment);
+            }
+        }
+        let interior_sign = if interior_sign >= 0.0 { 1.0 } else { -1.0 };
+
+        // The pieces of the path are only followed in their direction, while the
+        // knife is followed in both directions.
+        let mut half_edges = Vec::new();
+        let mut outgoing = vec![Vec::new(); self.positions.len()];
+        for piece in &self.pieces {
+            let id = half_edges.len();
+            outgoing[piece.from].push(id);
+            half_edges.push(KnifeHalfEdge { segment: piece.segment, to: piece.to, twin: None });
+            if piece.is_knife {
+                outgoing[piece.to].push(id + 1);
+                half_edges[id].twin = Some(id + 1);
+                half_edges.push(KnifeHalfEdge { segment: flip(&piece.segment), to: piece.from, twin: Some(id) });
+            }
+        }
+
+        // Follow the edges, always turning as much as possible towards the inside, so
+        // that each loop goes around a piece.
+        let mut loops = Vec::new();
+        let mut used = vec![false; half_edges.len()];
+        for start in 0..half_edges.len() {
+            if used[start] {
+                continue;
+            }
+
+            let mut edges = Vec::new();
+            let mut area = 0.0;
+            let mut current = start;
+            let closed = loop {
+                used[current] = true;
+                edges.push(current);
+                let edge = &half_edges[current];
+                area += segment_area(&edge.segment);
+
+                let d_in = end_tangent(&edge.segment);
+                let mut best = None;
+                let mut best_angle = std::f32::MIN;
+                for &next in &outgoing[edge.to] {
+                    if Some(next) == edge.twin {
+                        continue;
+                    }
+                    let d_out = start_tangent(&half_edges[next].segment);
+                    let angle = d_in.cross(d_out).atan2(d_in.dot(d_out)) * interior_sign;
+                    if angle > best_angle {
+                        best = Some(next);
+                        best_angle = angle;
+                    }
+                }
+
+                match best {
+                    Some(next) if next == start => { break true; }
+                    Some(next) if !used[next] => { current = next; }
+                    _ => { break false; }
+                }
+            };
+
+            if closed {
+                loops.push((edges, area));
+            }
+        }
+
+        let loop_paths: Vec<Path> = loops.iter().map(|(edges, _)| {
+            let mut builder = Path::builder();
+            builder.move_to(half_edges[edges[0]].segment.from());
+            for (i, &edge) in edges.iter().enumerate() {
+                match half_edges[edge].segment {
+                    BezierSegment::Linear(s) => {
+                        // The last line is implied by the close event.
+                        if i + 1 < edges.len() {
+                            builder.line_to(s.to);
+                        }
+                    }
+                    BezierSegment::Quadratic(s) => { builder.quadratic_bezier_to(s.ctrl, s.to); }
+                    BezierSegment::Cubic(s) => { builder.cubic_bezier_to(s.ctrl1, s.ctrl2, s.to); }
+                }
+            }
+            builder.close();
+            builder.build()
+        }).collect();
+
+        // Loops that go around the inside in the opposite direction are holes that
+        // the knife didn't cut. Put them in the smallest piece that contains them.
+        let is_hole = |i: usize| loops[i].1 * interior_sign < 0.0;
+        let mut holes = vec![Vec::new(); loops.len()];
+        for hole in (0..loops.len()).filter(|&i| is_hole(i)) {
+            let position = half_edges[loops[hole].0[0]].segment.from();
+            let mut container = None;
+            let mut container_area = std::f32::MAX;
+            for i in (0..loops.len()).filter(|&i| !is_hole(i)) {
+                let area = loops[i].1.abs();
+                if area < container_area
+                    && hit_test_path(&position, loop_paths[i].iter(), FillRule::NonZero, 0.01) {
+                    container = Some(i);
+                    container_area = area;
+                }
+            }
+            if let Some(i) = container {
+                holes[i].push(hole);
+            }
+        }
+
+        let mut result = Vec::new();
+        for i in (0..loops.len()).filter(|&i| !is_hole(i)) {
+            if holes[i].is_empty() {
+                result.push(loop_paths[i].clone());
+                continue;
+            }
+
+            let mut builder = Path::builder();
+            for &j in Some(&i).into_iter().chain(holes[i].iter()) {
+                for evt in loop_paths[j].iter() {
+                    builder.path_event(evt);
+                }
+            }
+            result.push(builder.build());
+        }
+
+        result
+    }
+}
+
 fn signed_pseudo_distance(line: &Line<f32>, p: &Point) -> f32 {
     let v1 = line.point.to_vector();
     let v2 = v1 + line.vector;
@@ -733,3 +1510,223 @@ fn split_with_segment_6() {
         PathEvent::Close(LineSegment { from: point(2.0, 2.0), to: point(2.0, 1.0) }),
     ]);
 }
+
+#[cfg(test)]
+fn path_area(path: &Path) -> f32 {
+    let mut area = 0.0;
+    for evt in path.iter().flattened(0.001) {
+        match evt {
+            FlattenedEvent::Line(segment) | FlattenedEvent::Close(segment) => {
+                area += segment.from.to_vector().cross(segment.to.to_vector()) * 0.5;
+            }
+            FlattenedEvent::MoveTo(..) => {}
+        }
+    }
+
+    area
+}
+
+#[test]
+fn split_with_grid_1() {
+    let mut path = Path::builder();
+    path.polygon(&[
+        point(0.5, 0.5),
+        point(2.5, 0.5),
+        point(2.5, 2.5),
+        point(0.5, 2.5),
+    ]);
+    let path = path.build();
+
+    let grid = Grid {
+        origin: point(0.0, 0.0),
+        cell_size: size(1.0, 1.0),
+        columns: 3,
+        rows: 3,
+    };
+
+    let mut splitter = Splitter::new();
+    let cells = splitter.split_with_grid(&path, &grid);
+    assert_eq!(cells.len(), 9);
+
+    for row in 0..3 {
+        for column in 0..3 {
+            let cell = &cells[(row * 3 + column) as usize];
+            let rect = grid.cell_rect(column, row);
+            for evt in cell.iter() {
+                if let PathEvent::Line(segment) = evt {
+                    assert!(rect.min_x() <= segment.to.x && segment.to.x <= rect.max_x());
+                    assert!(rect.min_y() <= segment.to.y && segment.to.y <= rect.max_y());
+                }
+            }
+            let expected = match (column, row) {
+                (1, 1) => 1.0,
+                (1, _) | (_, 1) => 0.5,
+                _ => 0.25,
+            };
+            assert!((path_area(cell) - expected).abs() < 0.0001);
+        }
+    }
+}
+
+#[test]
+fn split_with_grid_2() {
+    // A sub-path that contains a cell without crossing it, and a curve.
+    let mut path = Path::builder();
+    path.move_to(point(-1.0, -1.0));
+    path.line_to(point(4.0, -1.0));
+    path.quadratic_bezier_to(point(5.0, 1.5), point(4.0, 4.0));
+    path.line_to(point(-1.0, 4.0));
+    path.close();
+    let path = path.build();
+
+    let grid = Grid {
+        origin: point(-2.0, -2.0),
+        cell_size: size(2.5, 2.5),
+        columns: 3,
+        rows: 3,
+    };
+
+    let mut splitter = Splitter::new();
+    let cells = splitter.split_with_grid(&path, &grid);
+
+    assert!((path_area(&cells[4]) - 6.25).abs() < 0.0001);
+
+    let mut total = 0.0;
+    for (i, cell) in cells.iter().enumerate() {
+        total += path_area(cell);
+
+        // The curve is split between the cells of the last column.
+        let has_curves = cell.iter().any(|evt| match evt { PathEvent::Quadratic(..) => true, _ => false });
+        assert_eq!(has_curves, i % 3 == 2);
+    }
+
+    assert!((total - path_area(&path)).abs() < 0.001);
+}
+
+#[test]
+fn split_with_path_1() {
+    let mut path = Path::builder();
+    path.polygon(&[
+        point(0.0, 0.0),
+        point(2.0, 0.0),
+        point(2.0, 2.0),
+        point(0.0, 2.0),
+    ]);
+    let path = path.build();
+
+    let mut knife = Path::builder();
+    knife.move_to(point(1.0, -1.0));
+    knife.line_to(point(1.0, 3.0));
+    let knife = knife.build();
+
+    let mut splitter = Splitter::new();
+    let pieces = splitter.split_with_path(&path, &knife);
+
+    assert_eq!(pieces.len(), 2);
+    for piece in &pieces {
+        assert!((path_area(piece).abs() - 2.0).abs() < 0.0001);
+    }
+}
+
+#[test]
+fn split_with_path_2() {
+    // A shape made of curves, cut with a curve.
+    let mut path = Path::builder();
+    path.move_to(point(0.0, 0.0));
+    path.quadratic_bezier_to(point(2.0, -1.0), point(4.0, 0.0));
+    path.cubic_bezier_to(point(5.0, 1.0), point(5.0, 3.0), point(4.0, 4.0));
+    path.line_to(point(0.0, 4.0));
+    path.close();
+    let path = path.build();
+
+    let mut knife = Path::builder();
+    knife.move_to(point(-1.0, 1.0));
+    knife.quadratic_bezier_to(point(2.0, 4.0), point(6.0, 2.0));
+    let knife = knife.build();
+
+    let mut splitter = Splitter::new();
+    let pieces = splitter.split_with_path(&path, &knife);
+
+    assert_eq!(pieces.len(), 2);
+    let mut total = 0.0;
+    for piece in &pieces {
+        total += path_area(piece);
+        // Both the knife and the curves of the path are preserved.
+        assert!(piece.iter().any(|evt| match evt { PathEvent::Quadratic(..) => true, _ => false }));
+        assert!(piece.iter().any(|evt| match evt { PathEvent::Cubic(..) => true, _ => false }));
+    }
+    assert!((total - path_area(&path)).abs() < 0.001);
+}
+
+#[test]
+fn split_with_degenerate_knife() {
+    let mut path = Path::builder();
+    path.polygon(&[
+        point(0.0, 0.0),
+        point(10.0, 0.0),
+        point(10.0, 10.0),
+        point(0.0, 10.0),
+    ]);
+    let path = path.build();
+
+    // The knife contains curves that collapse into a point, on the edge of the path.
+    let mut knife = Path::builder();
+    knife.move_to(point(5.0, -1.0));
+    knife.line_to(point(5.0, 0.0));
+    knife.cubic_bezier_to(point(5.0, 0.0), point(5.0, 0.0), point(5.0, 0.0));
+    knife.quadratic_bezier_to(point(5.0, 0.0), point(5.0, 0.0));
+    knife.line_to(point(5.0, 11.0));
+    let knife = knife.build();
+
+    let mut splitter = Splitter::new();
+    let pieces = splitter.split_with_path(&path, &knife);
+
+    assert_eq!(pieces.len(), 2);
+    for piece in &pieces {
+        assert!((path_area(piece).abs() - 50.0).abs() < 0.001);
+    }
+}
+
+#[test]
+fn split_with_path_3() {
+    //  ______________
+    // |  |    ____   |
+    // |  |   |    |  |
+    // |  |   |____|  |
+    // |__|___________|
+    //    |
+
+    let mut path = Path::builder();
+    path.polygon(&[
+        point(0.0, 0.0),
+        point(10.0, 0.0),
+        point(10.0, 10.0),
+        point(0.0, 10.0),
+    ]);
+    path.polygon(&[
+        point(4.0, 4.0),
+        point(4.0, 6.0),
+        point(6.0, 6.0),
+        point(6.0, 4.0),
+    ]);
+    let path = path.build();
+
+    // The knife ends inside of the shape.
+    let mut knife = Path::builder();
+    knife.move_to(point(2.0, -1.0));
+    knife.line_to(point(2.0, 11.0));
+    knife.line_to(point(8.0, 5.0));
+    let knife = knife.build();
+
+    let mut splitter = Splitter::new();
+    let mut areas: Vec<f32> = splitter.split_with_path(&path, &knife)
+        .iter()
+        .map(|piece| path_area(piece).abs())
+        .collect();
+    areas.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    // The hole is in the second piece.
+    assert_eq!(areas.len(), 2);
+    assert!((areas[0] - 20.0).abs() < 0.0001);
+    assert!((areas[1] - 76.0).abs() < 0.0001);
+}
@@ -506,6 +506,7 @@ fn get_line_join(matches: &ArgMatches) -> LineJoin {
             "MiterClip" => LineJoin::MiterClip,
             "Round" => LineJoin::Round,
             "Bevel" => LineJoin::Bevel,
+            "Arcs" => LineJoin::Arcs,
             _ => LineJoin::Miter,
         }
     }
@@ -291,6 +291,24 @@ impl<S: Scalar> Arc<S> {
         <Self as FlattenedForEach>::for_each_flattened(self, tolerance, call_back);
     }
 
+    /// Iterates through the curve invoking a callback at each point.
+    ///
+    /// Similar to `for_each_flattened`, with the parameter `t` of each point.
+    pub fn for_each_flattened_with_t<F: FnMut(Point<S>, S)>(&self, tolerance: S, call_back: &mut F) {
+        let mut iter = *self;
+        let mut t0 = S::ZERO;
+        loop {
+            let t = iter.flattening_step(tolerance);
+            if t >= S::ONE {
+                call_back(iter.to(), S::ONE);
+                break;
+            }
+            iter = iter.after_split(t);
+            t0 = t0 + t * (S::ONE - t0);
+            call_back(iter.from(), t0);
+        }
+    }
+
     /// Finds the interval of the beginning of the curve that can be approximated with a
     /// line segment.
     pub fn flattening_step(&self, tolerance: S) -> S {
@@ -2,7 +2,7 @@ pub use crate::flatten_cubic::Flattened;
 use crate::{Line, LineSegment, LineEquation, QuadraticBezierSegment};
 use crate::scalar::Scalar;
 use crate::generic_math::{Point, Vector, Rect, rect, Transform2D};
-use crate::flatten_cubic::{flatten_cubic_bezier, find_cubic_bezier_inflection_points};
+use crate::flatten_cubic::{flatten_cubic_bezier, flatten_cubic_bezier_with_t, find_cubic_bezier_inflection_points};
 use crate::cubic_to_quadratic::*;
 use crate::cubic_bezier_intersections::cubic_bezier_intersections_t;
 use crate::monotonic::Monotonic;
@@ -395,6 +395,12 @@ impl<S: Scalar> CubicBezierSegment<S> {
         flatten_cubic_bezier(*self, tolerance, call_back);
     }
 
+    /// Iterates through the curve invoking a callback at each point, also passing
+    /// the parameter `t` of the point on the curve.
+    pub fn for_each_flattened_with_t<F: FnMut(Point<S>, S)>(&self, tolerance: S, call_back: &mut F) {
+        flatten_cubic_bezier_with_t(*self, tolerance, call_back);
+    }
+
     /// Compute the length of the segment using a flattened approximation.
     pub fn approximate_length(&self, tolerance: S) -> S {
         approximate_length_from_flattening(self, tolerance)
@@ -1216,3 +1222,32 @@ fn test_cubic_intersection_deduping() {
     assert!(f64::abs(intersections[0].x) < epsilon);
     assert!(f64::abs(intersections[0].y) < epsilon);
 }
+
+#[test]
+fn test_flattening_with_t() {
+    use crate::math::point;
+
+    // This curve has two inflection points.
+    let c = CubicBezierSegment {
+        from: point(0.0, 0.0),
+        ctrl1: point(100.0, 100.0),
+        ctrl2: point(0.0, 100.0),
+        to: point(100.0, 0.0),
+    };
+
+    let mut points = Vec::new();
+    c.for_each_flattened(0.01, &mut |p| { points.push(p); });
+
+    let mut i = 0;
+    let mut prev_t = 0.0;
+    c.for_each_flattened_with_t(0.01, &mut |p, t| {
+        assert_eq!(p, points[i]);
+        assert!(t > prev_t);
+        assert!((c.sample(t) - p).length() < 0.01);
+        prev_t = t;
+        i += 1;
+    });
+
+    assert_eq!(i, points.len());
+    assert_eq!(prev_t, 1.0);
+}
@@ -112,6 +112,16 @@ impl<S: Scalar> Iterator for Flattened<S> {
 }
 
 pub fn flatten_cubic_bezier<S: Scalar, F: FnMut(Point<S>)>(
+    bezier: CubicBezierSegment<S>,
+    tolerance: S,
+    call_back: &mut F,
+) {
+    flatten_cubic_bezier_with_t(bezier, tolerance, &mut |point, _| { call_back(point); });
+}
+
+/// Same as `flatten_cubic_bezier`, also passing to the callback the parameter `t`
+/// of each point on the original curve.
+pub fn flatten_cubic_bezier_with_t<S: Scalar, F: FnMut(Point<S>, S)>(
     mut bezier: CubicBezierSegment<S>,
     tolerance: S,
     call_back: &mut F,
@@ -119,35 +129,47 @@ pub fn flatten_cubic_bezier<S: Scalar, F: FnMut(Point<S>)>(
     let mut inflections: ArrayVec<[S; 2]> = ArrayVec::new();
     find_cubic_bezier_inflection_points(&bezier, &mut|t| { inflections.push(t); });
 
+    // The remaining part of the curve always ends at t = 1, t0 is where it starts.
+    let mut t0 = S::ZERO;
     if let Some(&t1) = inflections.get(0) {
-        bezier = flatten_including_inflection(&bezier, t1, tolerance, call_back);
+        let (remaining, remaining_t0) = flatten_including_inflection(&bezier, t0, t1, tolerance, call_back);
+        bezier = remaining;
+        t0 = remaining_t0;
         if let Some(&t2) = inflections.get(1) {
             // Adjust the second inflection since we removed the part before the
             // first inflection from the bezier curve.
             let t2 = (t2 - t1) / (S::ONE - t1);
-            bezier = flatten_including_inflection(&bezier, t2, tolerance, call_back);
+            let (remaining, remaining_t0) = flatten_including_inflection(&bezier, t0, t2, tolerance, call_back);
+            bezier = remaining;
+            t0 = remaining_t0;
         }
     }
 
-    flatten_cubic_no_inflection(bezier, tolerance, call_back);
+    flatten_cubic_no_inflection(bezier, t0, S::ONE, tolerance, call_back);
 }
 
 // Flatten the curve up to the the inflection point and its approximation range included.
-fn flatten_including_inflection<S: Scalar, F: FnMut(Point<S>)>(
+//
+// `t0` is the parameter on the original curve of the start of `bezier`, which ends
+// at t = 1. Returns the remaining part of the curve and the parameter of its start.
+fn flatten_including_inflection<S: Scalar, F: FnMut(Point<S>, S)>(
     bezier: &CubicBezierSegment<S>,
+    t0: S,
     up_to_t: S,
     tolerance: S,
     call_back: &mut F,
-) -> CubicBezierSegment<S> {
+) -> (CubicBezierSegment<S>, S) {
     let (before, mut after) = bezier.split(up_to_t);
-    flatten_cubic_no_inflection(before, tolerance, call_back);
+    let mut split_t = t0 + up_to_t * (S::ONE - t0);
+    flatten_cubic_no_inflection(before, t0, split_t, tolerance, call_back);
 
     if let Some(tf) = inflection_approximation_range(&after, tolerance) {
         after = after.after_split(tf);
-        call_back(after.from);
+        split_t = split_t + tf * (S::ONE - split_t);
+        call_back(after.from, split_t);
     }
 
-    after
+    (after, split_t)
 }
 
 
@@ -158,14 +180,19 @@ fn flatten_including_inflection<S: Scalar, F: FnMut(Point<S>)>(
 // equation of a cubic bezier curve is insignificantly small. This can
 // then be approximated by a quadratic equation for which the maximum
 // difference from a linear approximation can be much more easily determined.
-fn flatten_cubic_no_inflection<S: Scalar, F: FnMut(Point<S>)>(
+//
+// `t0` and `t1` are the parameters on the original curve of the start and end of `bezier`.
+fn flatten_cubic_no_inflection<S: Scalar, F: FnMut(Point<S>, S)>(
     mut bezier: CubicBezierSegment<S>,
+    t0: S,
+    t1: S,
     tolerance: S,
     call_back: &mut F,
 ) {
     let end = bezier.to;
 
     let mut t = S::ZERO;
+    let mut current_t = t0;
     while t < S::ONE {
         t = no_inflection_flattening_step(&bezier, tolerance);
 
@@ -173,10 +200,11 @@ fn flatten_cubic_no_inflection<S: Scalar, F: FnMut(Point<S>)>(
             break;
         }
         bezier = bezier.after_split(t);
-        call_back(bezier.from);
+        current_t = current_t + t * (t1 - current_t);
+        call_back(bezier.from, current_t);
     }
 
-    call_back(end);
+    call_back(end, t1);
 }
 
 fn no_inflection_flattening_step<S: Scalar>(bezier: &CubicBezierSegment<S>, tolerance: S) -> S {
@@ -242,6 +242,9 @@ pub mod math {
     /// Alias for ```euclid::default::Point2D<f32>```.
     pub type Vector = euclid::default::Vector2D<f32>;
 
+    /// Alias for ```euclid::default::Vector2D<f64>```.
+    pub type F64Vector = euclid::default::Vector2D<f64>;
+
     /// Alias for ```euclid::default::Size2D<f32>```.
     pub type Size = euclid::default::Size2D<f32>;
 
@@ -282,6 +282,23 @@ impl<S: Scalar> QuadraticBezierSegment<S> {
         <Self as FlattenedForEach>::for_each_flattened(self, tolerance, call_back);
     }
 
+    /// Iterates through the curve invoking a callback at each point, also passing
+    /// the parameter `t` of the point on the curve.
+    pub fn for_each_flattened_with_t<F: FnMut(Point<S>, S)>(&self, tolerance: S, call_back: &mut F) {
+        let mut iter = *self;
+        let mut t0 = S::ZERO;
+        loop {
+            let t = iter.flattening_step(tolerance);
+            if t >= S::ONE {
+                call_back(iter.to, S::ONE);
+                break;
+            }
+            iter = iter.after_split(t);
+            t0 = t0 + t * (S::ONE - t0);
+            call_back(iter.from, t0);
+        }
+    }
+
     /// Returns the flattened representation of the curve as an iterator, starting *after* the
     /// current point.
     pub fn flattened(&self, tolerance: S) -> Flattened<S> {
@@ -796,3 +813,30 @@ fn test_flattening() {
     check_tolerance(&c3, 0.001);
     check_tolerance(&c3, 0.0001);
 }
+
+#[test]
+fn test_flattening_with_t() {
+    use crate::math::point;
+
+    let c = QuadraticBezierSegment {
+        from: point(0.0, 0.0),
+        ctrl: point(10.0, 20.0),
+        to: point(20.0, 0.0),
+    };
+
+    let mut points = Vec::new();
+    c.for_each_flattened(0.01, &mut |p| { points.push(p); });
+
+    let mut i = 0;
+    let mut prev_t = 0.0;
+    c.for_each_flattened_with_t(0.01, &mut |p, t| {
+        assert_eq!(p, points[i]);
+        assert!(t > prev_t);
+        assert!((c.sample(t) - p).length() < 0.001);
+        prev_t = t;
+        i += 1;
+    });
+
+    assert_eq!(i, points.len());
+    assert_eq!(prev_t, 1.0);
+}
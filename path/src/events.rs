@@ -1,5 +1,5 @@
 use crate::geom::{LineSegment, QuadraticBezierSegment, CubicBezierSegment};
-use crate::math::{Point, Vector, Angle, Transform2D, Transform};
+use crate::math::{Point, F64Point, Vector, Angle, Transform2D, Transform};
 use crate::ArcFlags;
 
 /// Path event enum that can represent all of SVG's path description syntax.
@@ -46,6 +46,21 @@ pub enum PathEvent {
     Close(LineSegment<f32>),
 }
 
+/// Path event enum with double precision coordinates.
+///
+/// This is the event type of `F64Path`. Since most of lyon works with `f32`
+/// coordinates, these events are typically converted with `to_f32_relative` after
+/// moving the coordinates close to the origin.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub enum F64PathEvent {
+    MoveTo(F64Point),
+    Line(LineSegment<f64>),
+    Quadratic(QuadraticBezierSegment<f64>),
+    Cubic(CubicBezierSegment<f64>),
+    Close(LineSegment<f64>),
+}
+
 /// Path event enum that can only present quadratic bézier curves and line segments.
 ///
 /// Useful for algorithms that approximate all curves with quadratic béziers.
@@ -115,6 +130,39 @@ impl QuadraticEvent {
     }
 }
 
+impl F64PathEvent {
+    /// Converts to a single precision event, mapping each point `p` to
+    /// `(p - origin) * scale`.
+    ///
+    /// The subtraction is done in double precision so that large coordinates
+    /// don't lose precision as long as they are close to `origin`.
+    pub fn to_f32_relative(self, origin: F64Point, scale: f64) -> PathEvent {
+        let p = |p: F64Point| ((p - origin) * scale).to_point().cast::<f32>();
+        match self {
+            F64PathEvent::MoveTo(to) => PathEvent::MoveTo(p(to)),
+            F64PathEvent::Line(segment) => PathEvent::Line(LineSegment {
+                from: p(segment.from),
+                to: p(segment.to),
+            }),
+            F64PathEvent::Quadratic(segment) => PathEvent::Quadratic(QuadraticBezierSegment {
+                from: p(segment.from),
+                ctrl: p(segment.ctrl),
+                to: p(segment.to),
+            }),
+            F64PathEvent::Cubic(segment) => PathEvent::Cubic(CubicBezierSegment {
+                from: p(segment.from),
+                ctrl1: p(segment.ctrl1),
+                ctrl2: p(segment.ctrl2),
+                to: p(segment.to),
+            }),
+            F64PathEvent::Close(segment) => PathEvent::Close(LineSegment {
+                from: p(segment.from),
+                to: p(segment.to),
+            }),
+        }
+    }
+}
+
 impl Transform for FlattenedEvent {
     fn transform(&self, mat: &Transform2D) -> Self {
         match self {
@@ -0,0 +1,325 @@
+//! A path data structure with double precision coordinates.
+
+use crate::path::Verb;
+use crate::{Path, PathEvent, F64PathEvent};
+use crate::math::{F64Point, point};
+use crate::geom::{LineSegment, QuadraticBezierSegment, CubicBezierSegment};
+
+use std::iter::IntoIterator;
+
+/// A path with `f64` coordinates.
+///
+/// This is useful for data that has large coordinates (for example CAD or GIS data)
+/// where storing points in `f32` loses too much precision.
+///
+/// `F64Path` does not support custom attributes and only implements the
+/// subset of `Path`'s functionality that doesn't depend on `f32` coordinates.
+/// The tessellators accept it directly (see `FillTessellator::tessellate_f64_path`
+/// and `StrokeTessellator::tessellate_f64_path`).
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct F64Path {
+    points: Box<[F64Point]>,
+    verbs: Box<[Verb]>,
+}
+
+impl F64Path {
+    /// Creates a [F64Builder](struct.F64Builder.html) to create a path.
+    pub fn builder() -> F64Builder { F64Builder::new() }
+
+    /// Creates an empty path.
+    pub fn new() -> F64Path {
+        F64Path {
+            points: Box::new([]),
+            verbs: Box::new([]),
+        }
+    }
+
+    /// Creates a double precision copy of a path.
+    pub fn from_path(path: &Path) -> Self {
+        let mut builder = F64Builder::with_capacity(path.points().len());
+        let p = |p: crate::math::Point| p.cast::<f64>();
+        for evt in path.iter() {
+            match evt {
+                PathEvent::MoveTo(to) => builder.move_to(p(to)),
+                PathEvent::Line(segment) => builder.line_to(p(segment.to)),
+                PathEvent::Quadratic(segment) => builder.quadratic_bezier_to(
+                    p(segment.ctrl),
+                    p(segment.to),
+                ),
+                PathEvent::Cubic(segment) => builder.cubic_bezier_to(
+                    p(segment.ctrl1),
+                    p(segment.ctrl2),
+                    p(segment.to),
+                ),
+                PathEvent::Close(..) => builder.close(),
+            }
+        }
+
+        builder.build()
+    }
+
+    /// Iterates over the path.
+    pub fn iter(&self) -> F64Iter<'_> { F64Iter::new(&self.points[..], &self.verbs[..]) }
+
+    /// Returns the endpoints and control points of the path.
+    pub fn points(&self) -> &[F64Point] { &self.points[..] }
+
+    /// Returns the endpoints and control points of the path.
+    pub fn mut_points(&mut self) -> &mut [F64Point] { &mut self.points[..] }
+
+    /// Returns the minimum and maximum coordinates of the endpoints and control points,
+    /// or `None` if the path is empty.
+    ///
+    /// Since control points are included, the result contains the path but is not
+    /// necessarily tight.
+    pub fn bounds(&self) -> Option<(F64Point, F64Point)> {
+        let first = *self.points.first()?;
+        let mut min = first;
+        let mut max = first;
+        for p in &self.points[1..] {
+            min = min.min(*p);
+            max = max.max(*p);
+        }
+
+        Some((min, max))
+    }
+
+    /// Iterates over the path, converting the events to single precision relative to
+    /// an origin.
+    ///
+    /// See `F64PathEvent::to_f32_relative`.
+    pub fn iter_f32_relative(&self, origin: F64Point, scale: f64) -> impl Iterator<Item = PathEvent> + '_ {
+        self.iter().map(move |evt| evt.to_f32_relative(origin, scale))
+    }
+}
+
+impl<'l> IntoIterator for &'l F64Path {
+    type Item = F64PathEvent;
+    type IntoIter = F64Iter<'l>;
+
+    fn into_iter(self) -> F64Iter<'l> { self.iter() }
+}
+
+/// Builds an `F64Path`.
+pub struct F64Builder {
+    points: Vec<F64Point>,
+    verbs: Vec<Verb>,
+    current_position: F64Point,
+    first_position: F64Point,
+    need_moveto: bool,
+}
+
+impl F64Builder {
+    pub fn new() -> Self { F64Builder::with_capacity(128) }
+
+    pub fn with_capacity(cap: usize) -> Self {
+        F64Builder {
+            points: Vec::with_capacity(cap),
+            verbs: Vec::with_capacity(cap),
+            current_position: point(0.0, 0.0),
+            first_position: point(0.0, 0.0),
+            need_moveto: true,
+        }
+    }
+
+    pub fn move_to(&mut self, to: F64Point) {
+        nan_check(to);
+        self.need_moveto = false;
+        self.first_position = to;
+        self.current_position = to;
+        self.points.push(to);
+        self.verbs.push(Verb::MoveTo);
+    }
+
+    pub fn line_to(&mut self, to: F64Point) {
+        nan_check(to);
+        self.move_to_if_needed();
+        self.points.push(to);
+        self.verbs.push(Verb::LineTo);
+        self.current_position = to;
+    }
+
+    pub fn close(&mut self) {
+        // See Builder::close.
+        if let Some(p) = self.points.last_mut() {
+            let d = (*p - self.first_position).abs();
+            if d.x + d.y < 0.0001 {
+                *p = self.first_position;
+            }
+        }
+
+        self.verbs.push(Verb::Close);
+        self.current_position = self.first_position;
+        self.need_moveto = true;
+    }
+
+    pub fn quadratic_bezier_to(&mut self, ctrl: F64Point, to: F64Point) {
+        nan_check(ctrl);
+        nan_check(to);
+        self.move_to_if_needed();
+        self.points.push(ctrl);
+        self.points.push(to);
+        self.verbs.push(Verb::QuadraticTo);
+        self.current_position = to;
+    }
+
+    pub fn cubic_bezier_to(&mut self, ctrl1: F64Point, ctrl2: F64Point, to: F64Point) {
+        nan_check(ctrl1);
+        nan_check(ctrl2);
+        nan_check(to);
+        self.move_to_if_needed();
+        self.points.push(ctrl1);
+        self.points.push(ctrl2);
+        self.points.push(to);
+        self.verbs.push(Verb::CubicTo);
+        self.current_position = to;
+    }
+
+    /// Add a closed polygon.
+    pub fn polygon(&mut self, points: &[F64Point]) {
+        if points.is_empty() {
+            return;
+        }
+
+        self.move_to(points[0]);
+        for p in &points[1..] {
+            self.line_to(*p);
+        }
+        self.close();
+    }
+
+    pub fn current_position(&self) -> F64Point { self.current_position }
+
+    pub fn build(self) -> F64Path {
+        F64Path {
+            points: self.points.into_boxed_slice(),
+            verbs: self.verbs.into_boxed_slice(),
+        }
+    }
+
+    fn move_to_if_needed(&mut self) {
+        if self.need_moveto {
+            let first = self.first_position;
+            self.move_to(first);
+        }
+    }
+}
+
+impl Default for F64Builder {
+    fn default() -> Self { F64Builder::new() }
+}
+
+fn nan_check(p: F64Point) {
+    debug_assert!(p.x.is_finite());
+    debug_assert!(p.y.is_finite());
+}
+
+/// An iterator for `F64Path`.
+#[derive(Clone, Debug)]
+pub struct F64Iter<'l> {
+    points: ::std::slice::Iter<'l, F64Point>,
+    verbs: ::std::slice::Iter<'l, Verb>,
+    current: F64Point,
+    first: F64Point,
+}
+
+impl<'l> F64Iter<'l> {
+    fn new(points: &'l [F64Point], verbs: &'l [Verb]) -> Self {
+        F64Iter {
+            points: points.iter(),
+            verbs: verbs.iter(),
+            current: point(0.0, 0.0),
+            first: point(0.0, 0.0),
+        }
+    }
+}
+
+impl<'l> Iterator for F64Iter<'l> {
+    type Item = F64PathEvent;
+    fn next(&mut self) -> Option<F64PathEvent> {
+        match self.verbs.next() {
+            Some(&Verb::MoveTo) => {
+                self.current = *self.points.next().unwrap();
+                self.first = self.current;
+                Some(F64PathEvent::MoveTo(self.current))
+            }
+            Some(&Verb::LineTo) => {
+                let from = self.current;
+                self.current = *self.points.next().unwrap();
+                Some(F64PathEvent::Line(LineSegment {
+                    from, to: self.current
+                }))
+            }
+            Some(&Verb::QuadraticTo) => {
+                let from = self.current;
+                let ctrl = *self.points.next().unwrap();
+                self.current = *self.points.next().unwrap();
+                Some(F64PathEvent::Quadratic(QuadraticBezierSegment {
+                    from, ctrl, to: self.current
+                }))
+            }
+            Some(&Verb::CubicTo) => {
+                let from = self.current;
+                let ctrl1 = *self.points.next().unwrap();
+                let ctrl2 = *self.points.next().unwrap();
+                self.current = *self.points.next().unwrap();
+                Some(F64PathEvent::Cubic(CubicBezierSegment {
+                    from, ctrl1, ctrl2, to: self.current
+                }))
+            }
+            Some(&Verb::Close) => {
+                let from = self.current;
+                self.current = self.first;
+                Some(F64PathEvent::Close(LineSegment {
+                    from,
+                    to: self.first,
+                }))
+            }
+            None => None,
+        }
+    }
+}
+
+#[test]
+fn test_f64_path() {
+    let mut builder = F64Path::builder();
+    builder.move_to(point(1000000.0, 2000000.0));
+    builder.line_to(point(1000000.5, 2000000.0));
+    builder.quadratic_bezier_to(point(1000000.5, 2000000.5), point(1000000.0, 2000000.5));
+    builder.close();
+    let path = builder.build();
+
+    assert_eq!(path.bounds(), Some((point(1000000.0, 2000000.0), point(1000000.5, 2000000.5))));
+
+    let mut it = path.iter_f32_relative(point(1000000.0, 2000000.0), 2.0);
+    assert_eq!(it.next(), Some(PathEvent::MoveTo(point(0.0, 0.0))));
+    assert_eq!(it.next(), Some(PathEvent::Line(LineSegment {
+        from: point(0.0, 0.0),
+        to: point(1.0, 0.0),
+    })));
+    assert_eq!(it.next(), Some(PathEvent::Quadratic(QuadraticBezierSegment {
+        from: point(1.0, 0.0),
+        ctrl: point(1.0, 1.0),
+        to: point(0.0, 1.0),
+    })));
+    assert_eq!(it.next(), Some(PathEvent::Close(LineSegment {
+        from: point(0.0, 1.0),
+        to: point(0.0, 0.0),
+    })));
+    assert_eq!(it.next(), None);
+}
+
+#[test]
+fn test_f64_path_from_path() {
+    let mut builder = Path::builder();
+    builder.move_to(point(1.0, 2.0));
+    builder.cubic_bezier_to(point(3.0, 4.0), point(5.0, 6.0), point(7.0, 8.0));
+    builder.line_to(point(9.0, 10.0));
+    let path = builder.build();
+
+    let f64_path = F64Path::from_path(&path);
+    let events: Vec<PathEvent> = f64_path.iter_f32_relative(point(0.0, 0.0), 1.0).collect();
+    let expected: Vec<PathEvent> = path.iter().collect();
+    assert_eq!(events, expected);
+}
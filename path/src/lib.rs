@@ -47,10 +47,12 @@ pub extern crate serde;
 mod events;
 mod path_state;
 mod path;
+mod f64_path;
 pub mod iterator;
 pub mod builder;
 
 pub use crate::path::*;
+pub use crate::f64_path::*;
 pub use crate::events::*;
 pub use crate::path_state::*;
 pub use crate::geom::ArcFlags;
@@ -17,7 +17,7 @@ use std::mem;
 /// more efficiently.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
-enum Verb {
+pub(crate) enum Verb {
     MoveTo,
     LineTo,
     QuadraticTo,
@@ -28,11 +28,18 @@ enum Verb {
 /// A simple path data structure.
 ///
 /// It can be created using a [Builder](struct.Builder.html), and can be iterated over.
+///
+/// A path can optionally store a fixed number of custom `f32` attributes per endpoint
+/// (for example a color or a width), see `Path::builder_with_attributes`.
 #[derive(Clone, Debug, Default)]
 #[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
 pub struct Path {
     points: Box<[Point]>,
     verbs: Box<[Verb]>,
+    // num_attributes values for each point (including control points, the
+    // attributes of which are not used).
+    attributes: Box<[f32]>,
+    num_attributes: usize,
 }
 
 /// A view on a `Path`.
@@ -40,17 +47,27 @@ pub struct Path {
 pub struct PathSlice<'l> {
     points: &'l [Point],
     verbs: &'l [Verb],
+    attributes: &'l [f32],
+    num_attributes: usize,
 }
 
 impl Path {
     /// Creates a [Builder](struct.Builder.html) to create a path.
     pub fn builder() -> Builder { Builder::new() }
 
+    /// Creates a [Builder](struct.Builder.html) to create a path with a given number
+    /// of custom attributes per endpoint.
+    pub fn builder_with_attributes(num_attributes: usize) -> Builder {
+        Builder::with_attributes(num_attributes)
+    }
+
     /// Creates an Empty `Path`.
     pub fn new() -> Path {
         Path {
             points: Box::new([]),
             verbs: Box::new([]),
+            attributes: Box::new([]),
+            num_attributes: 0,
         }
     }
 
@@ -59,29 +76,110 @@ impl Path {
         PathSlice {
             points: &self.points[..],
             verbs: &self.verbs[..],
+            attributes: &self.attributes[..],
+            num_attributes: self.num_attributes,
         }
     }
 
     /// Iterates over the entire `Path`.
     pub fn iter(&self) -> Iter { Iter::new(&self.points[..], &self.verbs[..]) }
 
+    /// Iterates over the entire `Path`, along with the custom attributes of the endpoints.
+    pub fn iter_with_attributes(&self) -> IterWithAttributes<'_> {
+        self.as_slice().iter_with_attributes()
+    }
+
     pub fn points(&self) -> &[Point] { &self.points[..] }
 
     pub fn mut_points(&mut self) -> &mut [Point] { &mut self.points[..] }
 
+    /// Number of custom attributes per endpoint.
+    pub fn num_attributes(&self) -> usize { self.num_attributes }
+
+    /// Returns the custom attributes of a point of the path.
+    pub fn attributes(&self, id: VertexId) -> &[f32] {
+        self.as_slice().attributes(id)
+    }
+
+    /// Returns the custom attributes of a point of the path.
+    pub fn mut_attributes(&mut self, id: VertexId) -> &mut [f32] {
+        let n = self.num_attributes;
+        let start = id.to_usize() * n;
+        &mut self.attributes[start..(start + n)]
+    }
+
     /// Concatenate two paths.
+    ///
+    /// Both paths must have the same number of custom attributes.
     pub fn merge(&self, other: &Self) -> Self {
+        assert_eq!(self.num_attributes, other.num_attributes);
         let mut verbs = Vec::with_capacity(self.verbs.len() + other.verbs.len());
         let mut points = Vec::with_capacity(self.points.len() + other.points.len());
+        let mut attributes = Vec::with_capacity(self.attributes.len() + other.attributes.len());
         verbs.extend_from_slice(&self.verbs);
         verbs.extend_from_slice(&other.verbs);
         points.extend_from_slice(&self.points);
         points.extend_from_slice(&other.points);
+        attributes.extend_from_slice(&self.attributes);
+        attributes.extend_from_slice(&other.attributes);
 
         Path {
             verbs: verbs.into_boxed_slice(),
             points: points.into_boxed_slice(),
+            attributes: attributes.into_boxed_slice(),
+            num_attributes: self.num_attributes,
+        }
+    }
+
+    /// Returns a copy of this path with a transformation applied to all of its points.
+    ///
+    /// The custom attributes are preserved.
+    pub fn transformed(&self, transform: &Transform2D) -> Self {
+        let points: Vec<Point> = self.points.iter().map(|p| transform.transform_point(*p)).collect();
+
+        Path {
+            verbs: self.verbs.clone(),
+            points: points.into_boxed_slice(),
+            attributes: self.attributes.clone(),
+            num_attributes: self.num_attributes,
+        }
+    }
+
+    /// Returns an approximation of this path made of line segments only.
+    ///
+    /// The custom attributes of the points generated along the curves are linearly
+    /// interpolated between the attributes of the endpoints of the curves.
+    pub fn flattened(&self, tolerance: f32) -> Self {
+        let n = self.num_attributes;
+        let mut builder = Builder::with_attributes(n);
+        let mut interpolated = vec![0.0; n];
+        for (event, attributes) in self.iter_with_attributes() {
+            match event {
+                PathEvent::MoveTo(to) => {
+                    builder.move_to_with_attributes(to, attributes.to);
+                }
+                PathEvent::Line(segment) => {
+                    builder.line_to_with_attributes(segment.to, attributes.to);
+                }
+                PathEvent::Quadratic(segment) => {
+                    segment.for_each_flattened_with_t(tolerance, &mut |to, t| {
+                        attributes.interpolate(t, &mut interpolated);
+                        builder.line_to_with_attributes(to, &interpolated);
+                    });
+                }
+                PathEvent::Cubic(segment) => {
+                    segment.for_each_flattened_with_t(tolerance, &mut |to, t| {
+                        attributes.interpolate(t, &mut interpolated);
+                        builder.line_to_with_attributes(to, &interpolated);
+                    });
+                }
+                PathEvent::Close(..) => {
+                    builder.close();
+                }
+            }
         }
+
+        builder.build()
     }
 
     /// Returns a `Cursor` pointing to the start of this `Path`.
@@ -136,7 +234,22 @@ impl<'l> PathSlice<'l> {
         )
     }
 
+    /// Iterates over the path, along with the custom attributes of the endpoints.
+    pub fn iter_with_attributes(&self) -> IterWithAttributes<'l> {
+        IterWithAttributes::new(self.points, self.verbs, self.attributes, self.num_attributes)
+    }
+
     pub fn points(&self) -> &[Point] { self.points }
+
+    /// Number of custom attributes per endpoint.
+    pub fn num_attributes(&self) -> usize { self.num_attributes }
+
+    /// Returns the custom attributes of a point of the path.
+    pub fn attributes(&self, id: VertexId) -> &'l [f32] {
+        let n = self.num_attributes;
+        let start = id.to_usize() * n;
+        &self.attributes[start..(start + n)]
+    }
 }
 
 impl<'l> IntoIterator for PathSlice<'l> {
@@ -156,9 +269,14 @@ impl<'l, 'a> IntoIterator for &'a PathSlice<'l> {
 /// Builds path object using the FlatPathBuilder interface.
 ///
 /// See the [builder module](builder/index.html) documentation.
+///
+/// When the path has custom attributes, the endpoints that are added without
+/// specifying attributes get the ones of the previous endpoint.
 pub struct Builder {
     points: Vec<Point>,
     verbs: Vec<Verb>,
+    attributes: Vec<f32>,
+    num_attributes: usize,
     current_position: Point,
     first_position: Point,
     first_vertex: VertexId,
@@ -173,6 +291,8 @@ impl Builder {
         Builder {
             points: Vec::with_capacity(cap),
             verbs: Vec::with_capacity(cap),
+            attributes: Vec::new(),
+            num_attributes: 0,
             current_position: Point::new(0.0, 0.0),
             first_position: Point::new(0.0, 0.0),
             first_vertex: VertexId(0),
@@ -181,6 +301,15 @@ impl Builder {
         }
     }
 
+    /// Creates a builder for paths with a given number of custom attributes per endpoint.
+    pub fn with_attributes(num_attributes: usize) -> Self {
+        let mut builder = Builder::new();
+        builder.num_attributes = num_attributes;
+        builder.attributes.reserve(128 * num_attributes);
+
+        builder
+    }
+
     pub fn with_svg(self) -> SvgPathBuilder<Self> { SvgPathBuilder::new(self) }
 
     pub fn flattened(self, tolerance: f32) -> FlatteningBuilder<Self> {
@@ -188,6 +317,14 @@ impl Builder {
     }
 
     pub fn move_to(&mut self, to: Point) {
+        self.move_to_impl(to, None);
+    }
+
+    pub fn move_to_with_attributes(&mut self, to: Point, attributes: &[f32]) {
+        self.move_to_impl(to, Some(attributes));
+    }
+
+    fn move_to_impl(&mut self, to: Point, attributes: Option<&[f32]>) {
         nan_check(to);
         self.need_moveto = false;
         self.first_position = to;
@@ -196,13 +333,23 @@ impl Builder {
         self.current_position = to;
         self.points.push(to);
         self.verbs.push(Verb::MoveTo);
+        self.push_attributes(attributes, 1);
     }
 
     pub fn line_to(&mut self, to: Point) {
+        self.line_to_impl(to, None);
+    }
+
+    pub fn line_to_with_attributes(&mut self, to: Point, attributes: &[f32]) {
+        self.line_to_impl(to, Some(attributes));
+    }
+
+    fn line_to_impl(&mut self, to: Point, attributes: Option<&[f32]>) {
         nan_check(to);
         self.move_to_if_needed();
         self.points.push(to);
         self.verbs.push(Verb::LineTo);
+        self.push_attributes(attributes, 1);
         self.current_position = to;
     }
 
@@ -227,16 +374,33 @@ impl Builder {
     }
 
     pub fn quadratic_bezier_to(&mut self, ctrl: Point, to: Point) {
+        self.quadratic_bezier_to_impl(ctrl, to, None);
+    }
+
+    pub fn quadratic_bezier_to_with_attributes(&mut self, ctrl: Point, to: Point, attributes: &[f32]) {
+        self.quadratic_bezier_to_impl(ctrl, to, Some(attributes));
+    }
+
+    fn quadratic_bezier_to_impl(&mut self, ctrl: Point, to: Point, attributes: Option<&[f32]>) {
         nan_check(ctrl);
         nan_check(to);
         self.move_to_if_needed();
         self.points.push(ctrl);
         self.points.push(to);
         self.verbs.push(Verb::QuadraticTo);
+        self.push_attributes(attributes, 2);
         self.current_position = to;
     }
 
     pub fn cubic_bezier_to(&mut self, ctrl1: Point, ctrl2: Point, to: Point) {
+        self.cubic_bezier_to_impl(ctrl1, ctrl2, to, None);
+    }
+
+    pub fn cubic_bezier_to_with_attributes(&mut self, ctrl1: Point, ctrl2: Point, to: Point, attributes: &[f32]) {
+        self.cubic_bezier_to_impl(ctrl1, ctrl2, to, Some(attributes));
+    }
+
+    fn cubic_bezier_to_impl(&mut self, ctrl1: Point, ctrl2: Point, to: Point, attributes: Option<&[f32]>) {
         nan_check(ctrl1);
         nan_check(ctrl2);
         nan_check(to);
@@ -245,6 +409,7 @@ impl Builder {
         self.points.push(ctrl2);
         self.points.push(to);
         self.verbs.push(Verb::CubicTo);
+        self.push_attributes(attributes, 3);
         self.current_position = to;
     }
 
@@ -277,7 +442,46 @@ impl Builder {
     fn move_to_if_needed(&mut self) {
         if self.need_moveto {
             let first = self.first_position;
+            let first_vertex = self.first_vertex.to_usize();
             self.move_to(first);
+
+            // The implicit sub-path starts with the attributes of the previous
+            // sub-path's first endpoint.
+            let n = self.num_attributes;
+            if n > 0 && first_vertex + 1 < self.points.len() {
+                let dst = self.attributes.len() - n;
+                for i in 0..n {
+                    self.attributes[dst + i] = self.attributes[first_vertex * n + i];
+                }
+            }
+        }
+    }
+
+    // Adds the attributes of the `num_points` points that were just pushed.
+    fn push_attributes(&mut self, attributes: Option<&[f32]>, num_points: usize) {
+        let n = self.num_attributes;
+        if n == 0 {
+            return;
+        }
+
+        for _ in 0..num_points {
+            match attributes {
+                Some(attributes) => {
+                    assert_eq!(attributes.len(), n);
+                    self.attributes.extend_from_slice(attributes);
+                }
+                None => {
+                    let len = self.attributes.len();
+                    if len >= n {
+                        for i in (len - n)..len {
+                            let value = self.attributes[i];
+                            self.attributes.push(value);
+                        }
+                    } else {
+                        self.attributes.resize(n, 0.0);
+                    }
+                }
+            }
         }
     }
 
@@ -308,6 +512,8 @@ impl Builder {
         Path {
             points: self.points.into_boxed_slice(),
             verbs: self.verbs.into_boxed_slice(),
+            attributes: self.attributes.into_boxed_slice(),
+            num_attributes: self.num_attributes,
         }
     }
 }
@@ -510,6 +716,8 @@ impl Build for Builder {
         Path {
             points: mem::replace(&mut self.points, Vec::new()).into_boxed_slice(),
             verbs: mem::replace(&mut self.verbs, Vec::new()).into_boxed_slice(),
+            attributes: mem::replace(&mut self.attributes, Vec::new()).into_boxed_slice(),
+            num_attributes: self.num_attributes,
         }
     }
 }
@@ -642,6 +850,105 @@ impl<'l> Iterator for Iter<'l> {
     }
 }
 
+/// The custom attributes at the start and at the end of a path event.
+///
+/// For `MoveTo` events, `from` and `to` are the same.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct EventAttributes<'l> {
+    pub from: &'l [f32],
+    pub to: &'l [f32],
+}
+
+impl<'l> EventAttributes<'l> {
+    /// Linearly interpolates the attributes at a given parameter along the event.
+    pub fn interpolate(&self, t: f32, output: &mut [f32]) {
+        for (i, out) in output.iter_mut().enumerate() {
+            *out = self.from[i] * (1.0 - t) + self.to[i] * t;
+        }
+    }
+}
+
+/// An iterator for `Path` and `PathSlice` that also provides the custom
+/// attributes of the endpoints.
+#[derive(Clone, Debug)]
+pub struct IterWithAttributes<'l> {
+    points: &'l [Point],
+    verbs: ::std::slice::Iter<'l, Verb>,
+    attributes: &'l [f32],
+    num_attributes: usize,
+    // Index of the next point to read.
+    next_point: usize,
+    current: usize,
+    first: usize,
+}
+
+impl<'l> IterWithAttributes<'l> {
+    fn new(points: &'l [Point], verbs: &'l [Verb], attributes: &'l [f32], num_attributes: usize) -> Self {
+        IterWithAttributes {
+            points,
+            verbs: verbs.iter(),
+            attributes,
+            num_attributes,
+            next_point: 0,
+            current: 0,
+            first: 0,
+        }
+    }
+
+    fn attributes(&self, point: usize) -> &'l [f32] {
+        let n = self.num_attributes;
+        &self.attributes[(point * n)..((point + 1) * n)]
+    }
+}
+
+impl<'l> Iterator for IterWithAttributes<'l> {
+    type Item = (PathEvent, EventAttributes<'l>);
+    fn next(&mut self) -> Option<(PathEvent, EventAttributes<'l>)> {
+        let verb = *self.verbs.next()?;
+
+        let mut from = self.current;
+        let to = match verb {
+            Verb::Close => self.first,
+            _ => self.next_point + n_stored_points(verb) as usize - 1,
+        };
+        let p = self.points;
+        let event = match verb {
+            Verb::MoveTo => {
+                from = to;
+                self.first = to;
+                PathEvent::MoveTo(p[to])
+            }
+            Verb::LineTo => PathEvent::Line(LineSegment {
+                from: p[from],
+                to: p[to],
+            }),
+            Verb::QuadraticTo => PathEvent::Quadratic(QuadraticBezierSegment {
+                from: p[from],
+                ctrl: p[to - 1],
+                to: p[to],
+            }),
+            Verb::CubicTo => PathEvent::Cubic(CubicBezierSegment {
+                from: p[from],
+                ctrl1: p[to - 2],
+                ctrl2: p[to - 1],
+                to: p[to],
+            }),
+            Verb::Close => PathEvent::Close(LineSegment {
+                from: p[from],
+                to: p[to],
+            }),
+        };
+
+        self.next_point += n_stored_points(verb) as usize;
+        self.current = to;
+
+        Some((event, EventAttributes {
+            from: self.attributes(from),
+            to: self.attributes(to),
+        }))
+    }
+}
+
 fn n_stored_points(verb: Verb) -> u32 {
     match verb {
         Verb::MoveTo => 1,
@@ -968,3 +1275,99 @@ fn test_prev_cursor() {
 
     assert_eq!(c2.first_verb, start1.verb);
 }
+
+#[test]
+fn test_path_attributes() {
+    let mut builder = Path::builder_with_attributes(2);
+    builder.move_to_with_attributes(point(0.0, 0.0), &[0.0, 1.0]);
+    builder.line_to_with_attributes(point(1.0, 0.0), &[1.0, 2.0]);
+    builder.quadratic_bezier_to_with_attributes(point(2.0, 0.0), point(2.0, 1.0), &[2.0, 3.0]);
+    builder.line_to(point(3.0, 1.0));
+    builder.close();
+    builder.cubic_bezier_to_with_attributes(point(0.0, 1.0), point(0.0, 2.0), point(1.0, 2.0), &[5.0, 6.0]);
+    let path = builder.build();
+
+    assert_eq!(path.num_attributes(), 2);
+
+    let mut it = path.iter_with_attributes();
+    let (event, attributes) = it.next().unwrap();
+    assert_eq!(event, PathEvent::MoveTo(point(0.0, 0.0)));
+    assert_eq!(attributes, EventAttributes { from: &[0.0, 1.0], to: &[0.0, 1.0] });
+    let (event, attributes) = it.next().unwrap();
+    assert_eq!(event, PathEvent::Line(LineSegment { from: point(0.0, 0.0), to: point(1.0, 0.0) }));
+    assert_eq!(attributes, EventAttributes { from: &[0.0, 1.0], to: &[1.0, 2.0] });
+    let (event, attributes) = it.next().unwrap();
+    assert_eq!(event, PathEvent::Quadratic(QuadraticBezierSegment {
+        from: point(1.0, 0.0),
+        ctrl: point(2.0, 0.0),
+        to: point(2.0, 1.0),
+    }));
+    assert_eq!(attributes, EventAttributes { from: &[1.0, 2.0], to: &[2.0, 3.0] });
+    // No attributes specified: they are copied from the previous endpoint.
+    let (event, attributes) = it.next().unwrap();
+    assert_eq!(event, PathEvent::Line(LineSegment { from: point(2.0, 1.0), to: point(3.0, 1.0) }));
+    assert_eq!(attributes, EventAttributes { from: &[2.0, 3.0], to: &[2.0, 3.0] });
+    let (event, attributes) = it.next().unwrap();
+    assert_eq!(event, PathEvent::Close(LineSegment { from: point(3.0, 1.0), to: point(0.0, 0.0) }));
+    assert_eq!(attributes, EventAttributes { from: &[2.0, 3.0], to: &[0.0, 1.0] });
+    // The implicit move_to reuses the attributes of the start of the previous sub-path.
+    let (event, attributes) = it.next().unwrap();
+    assert_eq!(event, PathEvent::MoveTo(point(0.0, 0.0)));
+    assert_eq!(attributes, EventAttributes { from: &[0.0, 1.0], to: &[0.0, 1.0] });
+    let (event, attributes) = it.next().unwrap();
+    assert_eq!(event, PathEvent::Cubic(CubicBezierSegment {
+        from: point(0.0, 0.0),
+        ctrl1: point(0.0, 1.0),
+        ctrl2: point(0.0, 2.0),
+        to: point(1.0, 2.0),
+    }));
+    assert_eq!(attributes, EventAttributes { from: &[0.0, 1.0], to: &[5.0, 6.0] });
+    assert_eq!(it.next(), None);
+
+    // The regular iterator yields the same events.
+    let events: Vec<PathEvent> = path.iter().collect();
+    let events_with_attributes: Vec<PathEvent> = path.iter_with_attributes().map(|(evt, _)| evt).collect();
+    assert_eq!(events, events_with_attributes);
+}
+
+#[test]
+fn test_flattened_path_attributes() {
+    let mut builder = Path::builder_with_attributes(1);
+    builder.move_to_with_attributes(point(0.0, 0.0), &[0.0]);
+    builder.quadratic_bezier_to_with_attributes(point(10.0, 0.0), point(10.0, 10.0), &[1.0]);
+    builder.close();
+    let path = builder.build();
+
+    let flattened = path.flattened(0.01);
+    assert_eq!(flattened.num_attributes(), 1);
+
+    let mut previous = 0.0;
+    let mut num_lines = 0;
+    for (event, attributes) in flattened.iter_with_attributes() {
+        match event {
+            PathEvent::Line(segment) => {
+                num_lines += 1;
+                assert!(attributes.to[0] >= previous);
+                assert!(attributes.to[0] <= 1.0);
+                previous = attributes.to[0];
+                if segment.to == point(10.0, 10.0) {
+                    assert_eq!(attributes.to[0], 1.0);
+                }
+            }
+            PathEvent::Close(..) => {
+                assert_eq!(attributes.from[0], 1.0);
+                assert_eq!(attributes.to[0], 0.0);
+            }
+            PathEvent::MoveTo(..) => {}
+            _ => panic!("Unexpected curve in a flattened path"),
+        }
+    }
+    assert!(num_lines > 2);
+
+    let transform = Transform2D::create_translation(1.0, 2.0);
+    let transformed = flattened.transformed(&transform);
+    for ((evt1, attr1), (evt2, attr2)) in flattened.iter_with_attributes().zip(transformed.iter_with_attributes()) {
+        assert_eq!(evt1.transform(&transform), evt2);
+        assert_eq!(attr1, attr2);
+    }
+}
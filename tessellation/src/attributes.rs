@@ -0,0 +1,156 @@
+//! Interpolation of the custom attributes of a path on the vertices produced by the tessellators.
+//!
+//! The tessellators describe where each vertex comes from with a `VertexSource`.
+//! Attributes are linearly interpolated along the path event a vertex comes from, and
+//! averaged between the two edges at intersections.
+
+use crate::geometry_builder::{GeometryBuilder, GeometryBuilderError, VertexId, Count};
+use crate::path::{PathSlice, PathEvent};
+use crate::{FillVertex, StrokeVertex, VertexSource, EventPosition};
+
+/// Vertices that know where they come from in the path.
+pub(crate) trait SourcedVertex: Copy {
+    fn source(&self) -> VertexSource;
+}
+
+impl SourcedVertex for FillVertex {
+    fn source(&self) -> VertexSource { self.source }
+}
+
+impl SourcedVertex for StrokeVertex {
+    fn source(&self) -> VertexSource { self.source }
+}
+
+/// A geometry builder adapter that computes the custom attributes of the vertices
+/// it receives and forwards the vertices to another geometry builder.
+///
+/// The attributes are written into a separate buffer, `num_attributes` values per
+/// vertex, at the offset given by the `VertexId` returned by the output builder.
+pub(crate) struct AttributeInterpolator<'l, V> {
+    output: &'l mut dyn GeometryBuilder<V>,
+    attributes: &'l mut Vec<f32>,
+    num_attributes: usize,
+    // The attributes at the start and end of each path event, indexed by event.
+    //
+    // MoveTo events are not the source of any vertex, their slot holds the attributes
+    // of the edge that implicitly closes the previous sub-path. The same goes for the
+    // extra slot after the last event.
+    events: Vec<f32>,
+    tmp: Vec<f32>,
+}
+
+impl<'l, V: SourcedVertex> AttributeInterpolator<'l, V> {
+    pub fn new(
+        path: PathSlice,
+        output: &'l mut dyn GeometryBuilder<V>,
+        attributes: &'l mut Vec<f32>,
+    ) -> Self {
+        let n = path.num_attributes();
+        let mut events = Vec::new();
+        let mut first: Option<&[f32]> = None;
+        let mut last: &[f32] = &[];
+
+        for (event, event_attributes) in path.iter_with_attributes() {
+            match event {
+                PathEvent::MoveTo(..) => {
+                    push_closing_edge(&mut events, first, last, n);
+                    first = Some(event_attributes.to);
+                }
+                _ => {
+                    events.extend_from_slice(event_attributes.from);
+                    events.extend_from_slice(event_attributes.to);
+                }
+            }
+            last = event_attributes.to;
+        }
+        push_closing_edge(&mut events, first, last, n);
+
+        AttributeInterpolator {
+            output,
+            attributes,
+            num_attributes: n,
+            events,
+            tmp: vec![0.0; n],
+        }
+    }
+}
+
+// Interpolate the attributes at a position, adding them to the output.
+fn accumulate(events: &[f32], position: &EventPosition, weight: f32, output: &mut [f32]) {
+    let n = output.len();
+    let start = position.event as usize * 2 * n;
+    if start + 2 * n > events.len() {
+        return;
+    }
+
+    let t = position.t;
+    for (i, out) in output.iter_mut().enumerate() {
+        let from = events[start + i];
+        let to = events[start + n + i];
+        *out += (from * (1.0 - t) + to * t) * weight;
+    }
+}
+
+fn push_closing_edge(events: &mut Vec<f32>, first: Option<&[f32]>, last: &[f32], n: usize) {
+    match first {
+        Some(first) => {
+            events.extend_from_slice(last);
+            events.extend_from_slice(first);
+        }
+        None => {
+            events.resize(events.len() + 2 * n, 0.0);
+        }
+    }
+}
+
+impl<'l, V: SourcedVertex> GeometryBuilder<V> for AttributeInterpolator<'l, V> {
+    fn begin_geometry(&mut self) {
+        self.attributes.clear();
+        self.output.begin_geometry();
+    }
+
+    fn end_geometry(&mut self) -> Count {
+        self.output.end_geometry()
+    }
+
+    fn abort_geometry(&mut self) {
+        self.attributes.clear();
+        self.output.abort_geometry();
+    }
+
+    fn add_vertex(&mut self, vertex: V) -> Result<VertexId, GeometryBuilderError> {
+        let id = self.output.add_vertex(vertex)?;
+
+        let n = self.num_attributes;
+        if n == 0 {
+            return Ok(id);
+        }
+
+        let tmp = &mut self.tmp[..];
+        for value in tmp.iter_mut() {
+            *value = 0.0;
+        }
+        match vertex.source() {
+            VertexSource::Endpoint(position) | VertexSource::Curve(position) => {
+                accumulate(&self.events, &position, 1.0, tmp);
+            }
+            VertexSource::Intersection(a, b) => {
+                accumulate(&self.events, &a, 0.5, tmp);
+                accumulate(&self.events, &b, 0.5, tmp);
+            }
+            VertexSource::Unknown => {}
+        }
+
+        let start = id.to_usize() * n;
+        if self.attributes.len() < start + n {
+            self.attributes.resize(start + n, 0.0);
+        }
+        self.attributes[start..(start + n)].copy_from_slice(tmp);
+
+        Ok(id)
+    }
+
+    fn add_triangle(&mut self, a: VertexId, b: VertexId, c: VertexId) {
+        self.output.add_triangle(a, b, c);
+    }
+}
@@ -0,0 +1,467 @@
+//! Fill tessellation split into horizontal bands that can be updated separately.
+//!
+//! Editing a path usually only changes a small region of it, but re-tessellating the path
+//! from scratch costs as much as the first tessellation. `BandedFill` clips the fill into
+//! horizontal bands of a fixed height that don't share any triangle, so that after an edit
+//! only the bands that overlap the modified region need to be tessellated again. Their
+//! geometry is then spliced into the mesh in place of the previous one.
+//!
+//! Since both sides of the limit between two bands are clipped against the same line,
+//! the bands fit together without cracks. Each band is tessellated from the edges that
+//! overlap it, so that the cost of an update mostly depends on the size of the edit.
+//!
+//! ## Example
+//!
+//! ```
+//! # extern crate lyon_tessellation as tess;
+//! # use tess::path::Path;
+//! # use tess::math::point;
+//! # use tess::{BandedFill, FillOptions};
+//! # fn main() {
+//! let mut builder = Path::builder();
+//! builder.polygon(&[
+//!     point(0.0, 0.0), point(100.0, 0.0), point(100.0, 40.0), point(110.0, 50.0),
+//!     point(100.0, 60.0), point(100.0, 100.0), point(0.0, 100.0),
+//! ]);
+//! let mut path = builder.build();
+//!
+//! let mut fill = BandedFill::new(10.0, &FillOptions::default());
+//! fill.tessellate(path.as_slice()).unwrap();
+//! assert_eq!(fill.bands(), 0..11);
+//!
+//! // Move the tip on the right side. Only the bands that overlap the old and new
+//! // positions of the two edges that share it are updated.
+//! path.mut_points()[3] = point(130.0, 50.0);
+//! let updated = fill.update(path.as_slice(), 40.0..60.0).unwrap();
+//! assert_eq!(updated, 3..7);
+//! # }
+//! ```
+
+use crate::geometry_builder::{BuffersBuilder, VertexBuffers, Identity};
+use crate::path::{PathSlice, PathEvent};
+use crate::math::{Rect, point, size};
+use crate::{FillTessellator, FillEvents, FillOptions, FillVertex, TessellationError};
+
+use std::ops::Range;
+
+/// A fill tessellation split into horizontal bands of a fixed height.
+///
+/// Band `i` contains the part of the fill between `i * band_height` and
+/// `(i + 1) * band_height`.
+///
+/// See the [module documentation](index.html).
+pub struct BandedFill {
+    tessellator: FillTessellator,
+    // The edges of the bands that are being tessellated.
+    events: FillEvents,
+    band_events: FillEvents,
+    // The first edge that wasn't considered by the previous bands and the edges that
+    // overlapped them.
+    next_edge: usize,
+    active_edges: Vec<usize>,
+    options: FillOptions,
+    band_height: f32,
+    // The horizontal extent of the path (with some margin).
+    x_range: Range<f32>,
+    // The bands covered by the geometry, some of which can be empty.
+    bands: Range<i32>,
+    // The start of each band's vertices and indices in the geometry, followed by the end.
+    vertex_offsets: Vec<usize>,
+    index_offsets: Vec<usize>,
+    geometry: VertexBuffers<FillVertex, u32>,
+    band_geometry: VertexBuffers<FillVertex, u32>,
+}
+
+impl BandedFill {
+    /// Creates an empty banded tessellation.
+    ///
+    /// If the options have a `clip_rect`, each band is clipped against it as well.
+    pub fn new(band_height: f32, options: &FillOptions) -> Self {
+        assert!(band_height > 0.0);
+
+        BandedFill {
+            tessellator: FillTessellator::new(),
+            events: FillEvents::new(),
+            band_events: FillEvents::new(),
+            next_edge: 0,
+            active_edges: Vec::new(),
+            options: *options,
+            band_height,
+            x_range: 0.0..0.0,
+            bands: 0..0,
+            vertex_offsets: vec![0],
+            index_offsets: vec![0],
+            geometry: VertexBuffers::new(),
+            band_geometry: VertexBuffers::new(),
+        }
+    }
+
+    /// Tessellates all of the bands of a path, replacing the previous geometry.
+    pub fn tessellate(&mut self, path: PathSlice) -> Result<(), TessellationError> {
+        self.geometry.vertices.clear();
+        self.geometry.indices.clear();
+        self.vertex_offsets.clear();
+        self.index_offsets.clear();
+        self.vertex_offsets.push(0);
+        self.index_offsets.push(0);
+        self.bands = 0..0;
+
+        let bands = match self.set_bounds(path) {
+            Some(bands) => bands,
+            None => { return Ok(()); }
+        };
+
+        self.events.set_path(self.options.tolerance, path.iter());
+        self.start_bands();
+
+        self.bands = bands.start..bands.start;
+        for band in bands {
+            self.tessellate_band(band)?;
+            let base = self.geometry.vertices.len() as u32;
+            self.geometry.vertices.append(&mut self.band_geometry.vertices);
+            self.geometry.indices.extend(self.band_geometry.indices.drain(..).map(|i| i + base));
+            self.vertex_offsets.push(self.geometry.vertices.len());
+            self.index_offsets.push(self.geometry.indices.len());
+            self.bands.end = band + 1;
+        }
+
+        Ok(())
+    }
+
+    /// Re-tessellates the bands that overlap a vertical range of a modified path.
+    ///
+    /// The range must contain the parts of the path that changed, both before and after
+    /// the modification. The other bands are assumed to be unaffected and keep their
+    /// geometry.
+    ///
+    /// Returns the bands that were updated. If an error occurs, the geometry of the bands
+    /// that were already updated is kept.
+    pub fn update(&mut self, path: PathSlice, y_range: Range<f32>) -> Result<Range<i32>, TessellationError> {
+        let path_bands = match self.set_bounds(path) {
+            Some(bands) => bands,
+            None => { return self.tessellate(path).map(|_| 0..0); }
+        };
+
+        // Make room for bands above and below the current ones if the path grew.
+        while self.bands.start > path_bands.start {
+            self.vertex_offsets.insert(0, 0);
+            self.index_offsets.insert(0, 0);
+            self.bands.start -= 1;
+        }
+        while self.bands.end < path_bands.end {
+            self.vertex_offsets.push(self.geometry.vertices.len());
+            self.index_offsets.push(self.geometry.indices.len());
+            self.bands.end += 1;
+        }
+
+        // A vertex on the limit between two bands affects both of them.
+        let mut first = (y_range.start / self.band_height).floor() as i32;
+        if first as f32 * self.band_height == y_range.start {
+            first -= 1;
+        }
+        let last = (y_range.end / self.band_height).floor() as i32;
+        let first = first.max(self.bands.start);
+        let end = (last + 1).min(self.bands.end);
+        if first >= end {
+            return Ok(first..first);
+        }
+
+        // Only the edges of the updated bands are needed.
+        self.events.set_path_in_y_range(
+            self.options.tolerance,
+            path.iter(),
+            (first as f32 * self.band_height)..(end as f32 * self.band_height),
+        );
+        self.start_bands();
+
+        for band in first..end {
+            self.tessellate_band(band)?;
+            self.splice_band(band);
+        }
+
+        Ok(first..end)
+    }
+
+    /// Re-tessellates the bands that are affected by a set of edited path events.
+    ///
+    /// `edited_events` must contain the edited events both before and after the
+    /// modification (for example the two segments that share a moved endpoint, at
+    /// their old and new positions).
+    ///
+    /// See `update`.
+    pub fn update_events(&mut self, path: PathSlice, edited_events: &[PathEvent]) -> Result<Range<i32>, TessellationError> {
+        let mut y_range: Option<Range<f32>> = None;
+        let mut add = |y: f32| {
+            y_range = Some(match y_range.take() {
+                Some(range) => range.start.min(y)..range.end.max(y),
+                None => y..y,
+            });
+        };
+
+        // Curves are within the convex hull of their control points.
+        for evt in edited_events {
+            match *evt {
+                PathEvent::MoveTo(to) => { add(to.y); }
+                PathEvent::Line(segment) | PathEvent::Close(segment) => {
+                    add(segment.from.y);
+                    add(segment.to.y);
+                }
+                PathEvent::Quadratic(segment) => {
+                    add(segment.from.y);
+                    add(segment.ctrl.y);
+                    add(segment.to.y);
+                }
+                PathEvent::Cubic(segment) => {
+                    add(segment.from.y);
+                    add(segment.ctrl1.y);
+                    add(segment.ctrl2.y);
+                    add(segment.to.y);
+                }
+            }
+        }
+
+        match y_range {
+            Some(range) => self.update(path, range),
+            None => Ok(0..0),
+        }
+    }
+
+    /// The geometry of all bands.
+    pub fn geometry(&self) -> &VertexBuffers<FillVertex, u32> { &self.geometry }
+
+    /// The range of bands covered by the geometry. Some of them can be empty.
+    pub fn bands(&self) -> Range<i32> { self.bands.clone() }
+
+    /// The range of a band's indices in the geometry.
+    ///
+    /// Returns an empty range if the band is not in `bands()`.
+    pub fn band_indices(&self, band: i32) -> Range<usize> {
+        if !(self.bands.start..self.bands.end).contains(&band) {
+            return 0..0;
+        }
+
+        let idx = (band - self.bands.start) as usize;
+
+        self.index_offsets[idx]..self.index_offsets[idx + 1]
+    }
+
+    // Computes the horizontal extent of the path and returns the bands it overlaps.
+    fn set_bounds(&mut self, path: PathSlice) -> Option<Range<i32>> {
+        let first = *path.points().first()?;
+        let mut min = first;
+        let mut max = first;
+        for p in path.points() {
+            min = min.min(*p);
+            max = max.max(*p);
+        }
+
+        // The margin keeps the vertical sides of the bands away from the path.
+        self.x_range = (min.x - 1.0)..(max.x + 1.0);
+
+        let first = (min.y / self.band_height).floor() as i32;
+        let last = (max.y / self.band_height).floor() as i32;
+
+        Some(first..(last + 1))
+    }
+
+    // Must be called before tessellating bands from top to bottom with new events.
+    fn start_bands(&mut self) {
+        self.next_edge = 0;
+        self.active_edges.clear();
+    }
+
+    fn tessellate_band(&mut self, band: i32) -> Result<(), TessellationError> {
+        self.band_geometry.vertices.clear();
+        self.band_geometry.indices.clear();
+
+        let rect = Rect {
+            origin: point(self.x_range.start, band as f32 * self.band_height),
+            size: size(self.x_range.end - self.x_range.start, self.band_height),
+        };
+        let rect = match self.options.clip_rect {
+            Some(clip) => match clip.intersection(&rect) {
+                Some(rect) => rect,
+                None => { return Ok(()); }
+            },
+            None => rect,
+        };
+
+        self.events.find_band_edges(&rect, &mut self.next_edge, &mut self.active_edges);
+        self.band_events.set_band(&self.events, &self.active_edges, &rect);
+
+        self.tessellator.tessellate_events(
+            &self.band_events,
+            &self.options.with_clip_rect(rect),
+            &mut BuffersBuilder::new(&mut self.band_geometry, Identity),
+        )?;
+
+        Ok(())
+    }
+
+    // Replaces the geometry of a band with the content of `band_geometry`.
+    fn splice_band(&mut self, band: i32) {
+        let idx = (band - self.bands.start) as usize;
+        let vertices = self.vertex_offsets[idx]..self.vertex_offsets[idx + 1];
+        let indices = self.index_offsets[idx]..self.index_offsets[idx + 1];
+        let num_vertices = self.band_geometry.vertices.len();
+        let num_indices = self.band_geometry.indices.len();
+
+        let base = vertices.start as u32;
+        self.geometry.vertices.splice(vertices.clone(), self.band_geometry.vertices.drain(..));
+        self.geometry.indices.splice(
+            indices.clone(),
+            self.band_geometry.indices.drain(..).map(|i| i + base),
+        );
+
+        // Shift the geometry of the next bands.
+        let vertex_delta = num_vertices as isize - vertices.len() as isize;
+        let index_delta = num_indices as isize - indices.len() as isize;
+        if vertex_delta != 0 {
+            for i in &mut self.geometry.indices[indices.start + num_indices..] {
+                *i = (*i as isize + vertex_delta) as u32;
+            }
+        }
+        for offset in &mut self.vertex_offsets[idx + 1..] {
+            *offset = (*offset as isize + vertex_delta) as usize;
+        }
+        for offset in &mut self.index_offsets[idx + 1..] {
+            *offset = (*offset as isize + index_delta) as usize;
+        }
+    }
+}
+
+#[cfg(test)]
+use crate::path::Path;
+#[cfg(test)]
+use crate::extra::rust_logo::build_logo_path;
+#[cfg(test)]
+use crate::path::builder::Build;
+
+#[cfg(test)]
+fn fill_area(geometry: &VertexBuffers<FillVertex, u32>, indices: Range<usize>) -> f32 {
+    geometry.indices[indices].chunks(3).map(|tri| {
+        let a = geometry.vertices[tri[0] as usize].position;
+        let b = geometry.vertices[tri[1] as usize].position;
+        let c = geometry.vertices[tri[2] as usize].position;
+        (b - a).cross(c - a).abs() * 0.5
+    }).sum()
+}
+
+#[test]
+fn banded_fill_area() {
+    let mut path = Path::builder().with_svg();
+    build_logo_path(&mut path);
+    let path = path.build();
+
+    let options = FillOptions::tolerance(0.05);
+
+    let mut expected: VertexBuffers<FillVertex, u32> = VertexBuffers::new();
+    FillTessellator::new().tessellate_path(
+        path.iter(),
+        &options,
+        &mut BuffersBuilder::new(&mut expected, Identity),
+    ).unwrap();
+
+    let mut fill = BandedFill::new(7.0, &options);
+    fill.tessellate(path.as_slice()).unwrap();
+
+    assert!(fill.bands().end - fill.bands().start > 5);
+
+    let expected_area = fill_area(&expected, 0..expected.indices.len());
+    let area = fill_area(fill.geometry(), 0..fill.geometry().indices.len());
+    assert!((area - expected_area).abs() < 0.1, "{} != {}", area, expected_area);
+
+    // Each band only has triangles within its own vertical range.
+    for band in fill.bands() {
+        let y0 = band as f32 * 7.0;
+        for &i in &fill.geometry().indices[fill.band_indices(band)] {
+            let y = fill.geometry().vertices[i as usize].position.y;
+            assert!(y >= y0 - 0.001 && y <= y0 + 7.001);
+        }
+    }
+}
+
+#[test]
+fn banded_fill_matches_clipped_fill() {
+    let mut path = Path::builder().with_svg();
+    build_logo_path(&mut path);
+    let path = path.build();
+
+    // The clip rectangle cuts through the path, so the left side of the bands is
+    // inside of it.
+    let clip = Rect { origin: point(20.0, -1000.0), size: size(60.0, 2000.0) };
+    for options in &[
+        FillOptions::tolerance(0.05),
+        FillOptions::tolerance(0.05).with_clip_rect(clip),
+    ] {
+        let mut fill = BandedFill::new(7.0, options);
+        fill.tessellate(path.as_slice()).unwrap();
+
+        for band in fill.bands() {
+            let band_rect = Rect {
+                origin: point(-1000.0, band as f32 * 7.0),
+                size: size(2000.0, 7.0),
+            };
+            let rect = match options.clip_rect {
+                Some(clip) => clip.intersection(&band_rect).unwrap(),
+                None => band_rect,
+            };
+
+            let mut expected: VertexBuffers<FillVertex, u32> = VertexBuffers::new();
+            FillTessellator::new().tessellate_path(
+                path.iter(),
+                &options.with_clip_rect(rect),
+                &mut BuffersBuilder::new(&mut expected, Identity),
+            ).unwrap();
+
+            let expected_area = fill_area(&expected, 0..expected.indices.len());
+            let area = fill_area(fill.geometry(), fill.band_indices(band));
+            assert!((area - expected_area).abs() < 0.01, "band {}: {} != {}", band, area, expected_area);
+        }
+    }
+}
+
+#[test]
+fn banded_fill_update() {
+    use crate::geom::LineSegment;
+
+    let mut points = Vec::new();
+    for i in 0..40 {
+        let angle = i as f32 * std::f32::consts::PI * 2.0 / 40.0;
+        let radius = if i % 2 == 0 { 40.0 } else { 30.0 };
+        points.push(point(50.0 + angle.cos() * radius, 50.0 + angle.sin() * radius));
+    }
+    let mut builder = Path::builder();
+    builder.polygon(&points);
+    let mut path = builder.build();
+
+    let options = FillOptions::tolerance(0.05);
+
+    let mut fill = BandedFill::new(5.0, &options);
+    fill.tessellate(path.as_slice()).unwrap();
+    let num_bands = fill.bands().end - fill.bands().start;
+
+    // Move two points, one of which goes below the previous bounds of the path.
+    for &(idx, offset) in &[(3, crate::math::vector(4.0, 3.0)), (10, crate::math::vector(0.0, 15.0))] {
+        let prev = path.points()[idx - 1];
+        let next = path.points()[idx + 1];
+        let old = path.points()[idx];
+        let new = old + offset;
+        path.mut_points()[idx] = new;
+
+        let updated = fill.update_events(path.as_slice(), &[
+            PathEvent::Line(LineSegment { from: prev, to: old }),
+            PathEvent::Line(LineSegment { from: old, to: next }),
+            PathEvent::Line(LineSegment { from: prev, to: new }),
+            PathEvent::Line(LineSegment { from: new, to: next }),
+        ]).unwrap();
+        assert!(updated.end - updated.start < num_bands / 2);
+    }
+
+    let mut expected = BandedFill::new(5.0, &options);
+    expected.tessellate(path.as_slice()).unwrap();
+
+    assert_eq!(fill.bands(), expected.bands());
+    assert_eq!(fill.geometry().indices, expected.geometry().indices);
+    assert_eq!(fill.geometry().vertices, expected.geometry().vertices);
+}
@@ -30,7 +30,7 @@ use crate::geom::math::*;
 use crate::geom::Arc;
 use crate::path::builder::FlatPathBuilder;
 use crate::path::iterator::{FlattenedIterator, FromPolyline};
-use crate::{FillOptions, FillVertex, StrokeVertex, StrokeOptions, Side};
+use crate::{FillOptions, FillVertex, StrokeVertex, StrokeOptions, Side, VertexSource};
 use crate::{FillTessellator, TessellationResult};
 
 use std::f32::consts::PI;
@@ -71,18 +71,21 @@ pub fn fill_triangle(
         FillVertex {
             position: v1,
             normal: compute_normal(t31, t12),
+            source: VertexSource::Unknown,
         }
     )?;
     let b = output.add_vertex(
         FillVertex {
             position: v2,
             normal: compute_normal(t12, t23),
+            source: VertexSource::Unknown,
         }
     )?;
     let c = output.add_vertex(
         FillVertex {
             position: v3,
             normal: compute_normal(t23, t31),
+            source: VertexSource::Unknown,
         }
     )?;
 
@@ -129,24 +132,28 @@ pub fn fill_quad(
         FillVertex {
             position: v1,
             normal: compute_normal(t41, t12),
+            source: VertexSource::Unknown,
         }
     )?;
     let b = output.add_vertex(
         FillVertex {
             position: v2,
             normal: compute_normal(t12, t23),
+            source: VertexSource::Unknown,
         }
     )?;
     let c = output.add_vertex(
         FillVertex {
             position: v3,
             normal: compute_normal(t23, t34),
+            source: VertexSource::Unknown,
         }
     )?;
     let d = output.add_vertex(
         FillVertex {
             position: v4,
             normal: compute_normal(t34, t41),
+            source: VertexSource::Unknown,
         }
     )?;
     output.add_triangle(a, b, c);
@@ -179,24 +186,28 @@ pub fn fill_rectangle(
         FillVertex {
             position: rect.origin,
             normal: vector(-1.0, -1.0),
+            source: VertexSource::Unknown,
         }
     )?;
     let b = output.add_vertex(
         FillVertex {
             position: bottom_left(&rect),
             normal: vector(-1.0, 1.0),
+            source: VertexSource::Unknown,
         }
     )?;
     let c = output.add_vertex(
         FillVertex {
             position: bottom_right(&rect),
             normal: vector(1.0, 1.0),
+            source: VertexSource::Unknown,
         }
     )?;
     let d = output.add_vertex(
         FillVertex {
             position: top_right(&rect),
             normal: vector(1.0, -1.0),
+            source: VertexSource::Unknown,
         }
     )?;
     output.add_triangle(a, b, c);
@@ -250,6 +261,7 @@ fn stroke_thin_rectangle(
             normal: vector(-1.0, -1.0),
             advancement: 0.0,
             side: Side::Left,
+            source: VertexSource::Unknown,
         }
     )?;
     let b = output.add_vertex(
@@ -258,6 +270,7 @@ fn stroke_thin_rectangle(
             normal: vector(-1.0, 1.0),
             advancement: 0.0,
             side: Side::Left,
+            source: VertexSource::Unknown,
         }
     )?;
     let c = output.add_vertex(
@@ -266,6 +279,7 @@ fn stroke_thin_rectangle(
             normal: vector(1.0, 1.0),
             advancement: 1.0,
             side: Side::Right,
+            source: VertexSource::Unknown,
         }
     )?;
     let d = output.add_vertex(
@@ -274,6 +288,7 @@ fn stroke_thin_rectangle(
             normal: vector(1.0, -1.0),
             advancement: 1.0,
             side: Side::Right,
+            source: VertexSource::Unknown,
         }
     )?;
 
@@ -383,14 +398,14 @@ pub fn fill_rounded_rectangle(
 
 
     let v = [
-        output.add_vertex(FillVertex { position: p7, normal: left })?,
-        output.add_vertex(FillVertex { position: p6, normal: down })?,
-        output.add_vertex(FillVertex { position: p5, normal: down })?,
-        output.add_vertex(FillVertex { position: p4, normal: right })?,
-        output.add_vertex(FillVertex { position: p3, normal: right })?,
-        output.add_vertex(FillVertex { position: p2, normal: up })?,
-        output.add_vertex(FillVertex { position: p1, normal: up })?,
-        output.add_vertex(FillVertex { position: p0, normal: left })?,
+        output.add_vertex(FillVertex { position: p7, normal: left, source: VertexSource::Unknown })?,
+        output.add_vertex(FillVertex { position: p6, normal: down, source: VertexSource::Unknown })?,
+        output.add_vertex(FillVertex { position: p5, normal: down, source: VertexSource::Unknown })?,
+        output.add_vertex(FillVertex { position: p4, normal: right, source: VertexSource::Unknown })?,
+        output.add_vertex(FillVertex { position: p3, normal: right, source: VertexSource::Unknown })?,
+        output.add_vertex(FillVertex { position: p2, normal: up, source: VertexSource::Unknown })?,
+        output.add_vertex(FillVertex { position: p1, normal: up, source: VertexSource::Unknown })?,
+        output.add_vertex(FillVertex { position: p0, normal: left, source: VertexSource::Unknown })?,
     ];
 
     output.add_triangle(v[6], v[7], v[0]);
@@ -463,6 +478,7 @@ fn fill_border_radius(
     let vertex = output.add_vertex(FillVertex {
         position,
         normal,
+        source: VertexSource::Unknown,
     })?;
 
     output.add_triangle(vb, vertex, va);
@@ -621,19 +637,23 @@ pub fn fill_circle(
     let v = [
         output.add_vertex(FillVertex {
             position: center + (left * radius),
-            normal: left
+            normal: left,
+            source: VertexSource::Unknown,
         })?,
         output.add_vertex(FillVertex {
             position: center + (up * radius),
-            normal: up
+            normal: up,
+            source: VertexSource::Unknown,
         })?,
         output.add_vertex(FillVertex {
             position: center + (right * radius),
-            normal: right
+            normal: right,
+            source: VertexSource::Unknown,
         })?,
         output.add_vertex(FillVertex {
             position: center + (down * radius),
-            normal: down
+            normal: down,
+            source: VertexSource::Unknown,
         })?,
     ];
 
@@ -839,12 +859,14 @@ where
             FillVertex {
                 position: a2,
                 normal: compute_normal(a2 - a1, a3 - a2),
+                source: VertexSource::Unknown,
             }
         )?;
         let mut b = output.add_vertex(
             FillVertex {
                 position: b3,
                 normal: compute_normal(b3 - b2, b4 - b3),
+                source: VertexSource::Unknown,
             }
         )?;
 
@@ -853,6 +875,7 @@ where
                 FillVertex {
                     position: p2,
                     normal: compute_normal(p2 - p1, p3 - p2),
+                    source: VertexSource::Unknown,
                 }
             )?;
 
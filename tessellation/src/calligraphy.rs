@@ -0,0 +1,474 @@
+//! Strokes drawn with a calligraphic pen.
+//!
+//! Instead of extruding the path perpendicularly to its direction, the stroke is the area
+//! swept by a pen nib of fixed orientation moving along the path. The width of the stroke
+//! depends on the direction of travel: it is the widest where the path is perpendicular
+//! to the nib and the thinnest where it is parallel to it.
+//!
+//! The nib is an ellipse (or a line segment for flat nibs) approximated by a polygon.
+//! Each edge of the flattened path is swept by the two points of the nib that are the
+//! furthest away from it on each side. At the joins between edges, the nib's outline
+//! between the previous and next extremities is added, and the whole nib is added at the
+//! ends of the sub-paths. The resulting triangles overlap at the joins but cover exactly
+//! the swept area (up to the tolerance).
+
+use crate::path_stroke::stroke_with_options;
+use crate::basic_shapes::circle_flattening_step;
+use crate::geometry_builder::{GeometryBuilder, GeometryBuilderError, VertexId};
+use crate::math::{Point, Vector, Angle, vector};
+use crate::path::PathEvent;
+use crate::{StrokeTessellator, StrokeOptions, StrokeVertex, Side, TessellationResult, TessellationError};
+use crate::{VertexSource, EventPosition};
+
+use std::f32::consts::PI;
+
+/// The shape of the pen used by `StrokeTessellator::tessellate_path_with_nib`.
+///
+/// The width of the nib is the line width of the stroke options.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct Nib {
+    /// The ratio between the thickness and the width of the nib, between 0.0 (flat nib)
+    /// and 1.0 (round nib).
+    pub thickness: f32,
+    /// The direction of the nib's width, from the x axis.
+    pub angle: Angle,
+}
+
+impl Nib {
+    /// An elliptical nib.
+    pub fn elliptical(thickness: f32, angle: Angle) -> Self {
+        Nib { thickness, angle }
+    }
+
+    /// A flat nib, which is a line segment.
+    pub fn flat(angle: Angle) -> Self {
+        Nib { thickness: 0.0, angle }
+    }
+
+    // The outline of the nib with a width of 2, in counter-clockwise order. The number
+    // of points is even and the outline is symmetric: the opposite of point `i` is
+    // point `i + n / 2`.
+    fn outline(&self, line_width: f32, tolerance: f32) -> Vec<Vector> {
+        let radius = line_width * 0.5;
+        let step = if radius > 0.0 { circle_flattening_step(radius, tolerance) / radius } else { PI };
+        let mut n = (2.0 * PI / step).ceil() as usize;
+        n = (n + n % 2).max(4).min(1024);
+
+        let (sin, cos) = self.angle.get().sin_cos();
+        let thickness = self.thickness.max(0.0).min(1.0);
+        (0..n).map(|i| {
+            let t = 2.0 * PI * i as f32 / n as f32;
+            let (x, y) = (t.cos(), t.sin() * thickness);
+            vector(x * cos - y * sin, x * sin + y * cos)
+        }).collect()
+    }
+}
+
+impl StrokeTessellator {
+    /// Compute the tessellation of a path stroked with a calligraphic nib.
+    ///
+    /// The `line_width` option is the width of the nib. The line caps, line join and miter
+    /// limit are not used since the shape of the nib determines the shape of the stroke.
+    ///
+    /// The normals of the generated vertices point from the path to the outline of the stroke
+    /// such that extruding the vertices along their normal by half of the line width produces
+    /// the stroke (which is what `apply_line_width` does). Vertices at the center of the joins
+    /// and caps have a null normal.
+    pub fn tessellate_path_with_nib<Iter>(
+        &mut self,
+        it: Iter,
+        nib: &Nib,
+        options: &StrokeOptions,
+        output: &mut dyn GeometryBuilder<StrokeVertex>,
+    ) -> TessellationResult
+    where
+        Iter: IntoIterator<Item = PathEvent>,
+    {
+        stroke_with_options(options, output, |output| {
+            let mut stroker = NibStroker {
+                outline: nib.outline(options.line_width, options.tolerance),
+                half_width: options.line_width * 0.5,
+                apply_line_width: options.apply_line_width,
+                points: Vec::new(),
+                output,
+            };
+
+            stroker.stroke(it.into_iter(), options.tolerance)
+                .map_err(TessellationError::from)
+        })
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct PathPoint {
+    position: Point,
+    advancement: f32,
+    source: VertexSource,
+}
+
+struct NibStroker<'l> {
+    outline: Vec<Vector>,
+    half_width: f32,
+    apply_line_width: bool,
+    // The flattened points of the current sub-path.
+    points: Vec<PathPoint>,
+    output: &'l mut dyn GeometryBuilder<StrokeVertex>,
+}
+
+type StrokeResult = Result<(), GeometryBuilderError>;
+
+impl<'l> NibStroker<'l> {
+    fn stroke(&mut self, it: impl Iterator<Item = PathEvent>, tolerance: f32) -> StrokeResult {
+        let mut sub_path = 0;
+        let mut first_sub_path = true;
+        for (idx, evt) in it.enumerate() {
+            let event = idx as u32;
+            let position = move |t| EventPosition { sub_path, event, t };
+            match evt {
+                PathEvent::MoveTo(to) => {
+                    if !self.points.is_empty() {
+                        self.sub_path(false)?;
+                    }
+                    if !first_sub_path {
+                        sub_path += 1;
+                    }
+                    first_sub_path = false;
+                    self.points.clear();
+                    self.add_point(to, VertexSource::Endpoint(EventPosition { sub_path, event, t: 0.0 }));
+                }
+                PathEvent::Line(segment) => {
+                    self.add_point(segment.to, VertexSource::Endpoint(position(1.0)));
+                }
+                PathEvent::Quadratic(segment) => {
+                    segment.for_each_flattened_with_t(tolerance, &mut |p, t| {
+                        self.add_point(p, curve_source(position(t)));
+                    });
+                }
+                PathEvent::Cubic(segment) => {
+                    segment.for_each_flattened_with_t(tolerance, &mut |p, t| {
+                        self.add_point(p, curve_source(position(t)));
+                    });
+                }
+                PathEvent::Close(..) => {
+                    self.sub_path(true)?;
+                    self.points.clear();
+                }
+            }
+        }
+
+        if !self.points.is_empty() {
+            self.sub_path(false)?;
+        }
+
+        Ok(())
+    }
+
+    fn add_point(&mut self, position: Point, source: VertexSource) {
+        let advancement = match self.points.last() {
+            Some(last) if last.position == position => { return; }
+            Some(last) => last.advancement + (position - last.position).length(),
+            None => 0.0,
+        };
+
+        self.points.push(PathPoint { position, advancement, source });
+    }
+
+    fn sub_path(&mut self, closed: bool) -> StrokeResult {
+        let mut points = std::mem::replace(&mut self.points, Vec::new());
+        if closed && points.len() > 1 && points[0].position == points[points.len() - 1].position {
+            points.pop();
+        }
+
+        if points.is_empty() {
+            self.points = points;
+            return Ok(());
+        }
+
+        if points.len() == 1 {
+            self.nib(&points[0], vector(0.0, 0.0))?;
+            self.points = points;
+            return Ok(());
+        }
+
+        let num_edges = if closed { points.len() } else { points.len() - 1 };
+        let edge = |i: usize| (points[i], points[(i + 1) % points.len()]);
+        let mut previous_direction = None;
+        let mut first_direction = None;
+        for i in 0..num_edges {
+            let (from, to) = edge(i);
+            let direction = (to.position - from.position).normalize();
+            let side = self.support(direction);
+
+            if let Some(previous) = previous_direction {
+                self.join(&from, previous, direction)?;
+            }
+            if first_direction.is_none() {
+                first_direction = Some(direction);
+            }
+
+            let from_left = self.add_vertex(&from, side, Side::Left)?;
+            let from_right = self.add_vertex(&from, self.opposite(side), Side::Right)?;
+            let to_left = self.add_vertex(&to, side, Side::Left)?;
+            let to_right = self.add_vertex(&to, self.opposite(side), Side::Right)?;
+            self.output.add_triangle(from_left, from_right, to_left);
+            self.output.add_triangle(from_right, to_right, to_left);
+
+            previous_direction = Some(direction);
+        }
+
+        let last_direction = previous_direction.unwrap();
+        let first_direction = first_direction.unwrap();
+        if closed {
+            self.join(&points[0], last_direction, first_direction)?;
+        } else {
+            self.nib(&points[0], first_direction)?;
+            self.nib(&points[points.len() - 1], last_direction)?;
+        }
+
+        self.points = points;
+
+        Ok(())
+    }
+
+    // The index of the outline point that is the furthest on the left of a direction.
+    fn support(&self, direction: Vector) -> usize {
+        let normal = vector(-direction.y, direction.x);
+        let mut best = 0;
+        let mut best_dot = std::f32::NEG_INFINITY;
+        for (i, p) in self.outline.iter().enumerate() {
+            let dot = p.dot(normal);
+            if dot > best_dot {
+                best_dot = dot;
+                best = i;
+            }
+        }
+
+        best
+    }
+
+    fn opposite(&self, idx: usize) -> usize {
+        (idx + self.outline.len() / 2) % self.outline.len()
+    }
+
+    // Covers the outline of the nib between the extremities of the previous and next edges,
+    // on both sides.
+    fn join(&mut self, point: &PathPoint, previous: Vector, next: Vector) -> StrokeResult {
+        let from = self.support(previous);
+        let to = self.support(next);
+        if from == to {
+            return Ok(());
+        }
+
+        // The extremities move in the same direction as the path turns.
+        let n = self.outline.len();
+        let step = if previous.cross(next) >= 0.0 { 1 } else { n - 1 };
+
+        let center = self.add_center(point)?;
+        for &(start, end, side) in &[(from, to, Side::Left), (self.opposite(from), self.opposite(to), Side::Right)] {
+            let mut idx = start;
+            let mut prev_id = self.add_vertex(point, idx, side)?;
+            while idx != end {
+                idx = (idx + step) % n;
+                let id = self.add_vertex(point, idx, side)?;
+                self.output.add_triangle(center, prev_id, id);
+                prev_id = id;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Adds the whole nib at a point.
+    fn nib(&mut self, point: &PathPoint, direction: Vector) -> StrokeResult {
+        let normal = vector(-direction.y, direction.x);
+        let center = self.add_center(point)?;
+        let first = self.add_vertex(point, 0, nib_side(self.outline[0], normal))?;
+        let mut prev_id = first;
+        for idx in 1..self.outline.len() {
+            let id = self.add_vertex(point, idx, nib_side(self.outline[idx], normal))?;
+            self.output.add_triangle(center, prev_id, id);
+            prev_id = id;
+        }
+        self.output.add_triangle(center, prev_id, first);
+
+        Ok(())
+    }
+
+    fn add_center(&mut self, point: &PathPoint) -> Result<VertexId, GeometryBuilderError> {
+        self.output.add_vertex(StrokeVertex {
+            position: point.position,
+            normal: vector(0.0, 0.0),
+            advancement: point.advancement,
+            side: Side::Left,
+            source: point.source,
+        })
+    }
+
+    fn add_vertex(
+        &mut self,
+        point: &PathPoint,
+        outline_idx: usize,
+        side: Side,
+    ) -> Result<VertexId, GeometryBuilderError> {
+        let normal = self.outline[outline_idx];
+        let mut position = point.position;
+        if self.apply_line_width {
+            position += normal * self.half_width;
+        }
+
+        self.output.add_vertex(StrokeVertex {
+            position,
+            normal,
+            advancement: point.advancement,
+            side,
+            source: point.source,
+        })
+    }
+}
+
+fn curve_source(position: EventPosition) -> VertexSource {
+    if position.t == 1.0 {
+        VertexSource::Endpoint(position)
+    } else {
+        VertexSource::Curve(position)
+    }
+}
+
+fn nib_side(p: Vector, normal: Vector) -> Side {
+    if p.dot(normal) >= 0.0 { Side::Left } else { Side::Right }
+}
+
+#[cfg(test)]
+use crate::geometry_builder::{VertexBuffers, BuffersBuilder, Identity};
+#[cfg(test)]
+use crate::math::point;
+#[cfg(test)]
+use crate::path::Path;
+
+// Approximates the area covered by the triangles by sampling a grid.
+#[cfg(test)]
+fn covered_area(buffers: &VertexBuffers<StrokeVertex, u16>, min: Point, max: Point, step: f32) -> f32 {
+    let triangles: Vec<[Point; 3]> = buffers.indices.chunks(3).map(|t| [
+        buffers.vertices[t[0] as usize].position,
+        buffers.vertices[t[1] as usize].position,
+        buffers.vertices[t[2] as usize].position,
+    ]).collect();
+
+    let inside = |p: Point, t: &[Point; 3]| {
+        let d1 = (t[1] - t[0]).cross(p - t[0]);
+        let d2 = (t[2] - t[1]).cross(p - t[1]);
+        let d3 = (t[0] - t[2]).cross(p - t[2]);
+        (d1 >= 0.0 && d2 >= 0.0 && d3 >= 0.0) || (d1 <= 0.0 && d2 <= 0.0 && d3 <= 0.0)
+    };
+
+    let mut count = 0;
+    let mut y = min.y + step * 0.5;
+    while y < max.y {
+        let mut x = min.x + step * 0.5;
+        while x < max.x {
+            if triangles.iter().any(|t| inside(point(x, y), t)) {
+                count += 1;
+            }
+            x += step;
+        }
+        y += step;
+    }
+
+    count as f32 * step * step
+}
+
+#[test]
+fn flat_nib_width_depends_on_direction() {
+    // A horizontal line followed by a vertical line, with a flat nib at 45 degrees.
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(10.0, 0.0));
+    builder.line_to(point(10.0, 10.0));
+    let path = builder.build();
+
+    let mut buffers: VertexBuffers<StrokeVertex, u16> = VertexBuffers::new();
+    StrokeTessellator::new().tessellate_path_with_nib(
+        path.iter(),
+        &Nib::flat(Angle::degrees(45.0)),
+        &StrokeOptions::default().with_line_width(2.0),
+        &mut BuffersBuilder::new(&mut buffers, Identity),
+    ).unwrap();
+
+    let h = 2.0f32.sqrt() * 0.5;
+    for v in &buffers.vertices {
+        assert!(v.normal.length() <= 1.0 + 1e-5);
+        // All vertices are on the nib placed on the path.
+        let on_horizontal = v.position.x - v.position.y >= -1e-4 && v.position.x - v.position.y <= 10.0 + 1e-4
+            && v.position.y.abs() <= h + 1e-4;
+        let on_vertical = (v.position.x - 10.0).abs() <= h + 1e-4;
+        assert!(on_horizontal || on_vertical);
+    }
+
+    // Each line is a parallelogram with an area of length * h * 2, and they overlap
+    // on a parallelogram of area h * h * 2 at the corner.
+    let area = covered_area(&buffers, point(-2.0, -2.0), point(12.0, 12.0), 0.02);
+    let expected = 2.0 * (10.0 * h * 2.0) - h * h * 2.0;
+    assert!((area - expected).abs() < 0.3, "{} {}", area, expected);
+}
+
+#[test]
+fn round_nib_is_a_round_stroke() {
+    // With a round nib, the stroke has round caps and joins.
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(10.0, 0.0));
+    builder.line_to(point(0.0, 10.0));
+    let path = builder.build();
+
+    let mut buffers: VertexBuffers<StrokeVertex, u16> = VertexBuffers::new();
+    StrokeTessellator::new().tessellate_path_with_nib(
+        path.iter(),
+        &Nib::elliptical(1.0, Angle::radians(0.0)),
+        &StrokeOptions::tolerance(0.01).with_line_width(2.0),
+        &mut BuffersBuilder::new(&mut buffers, Identity),
+    ).unwrap();
+
+    for v in &buffers.vertices {
+        if v.normal != vector(0.0, 0.0) {
+            assert!((v.normal.length() - 1.0).abs() < 1e-4);
+        }
+    }
+
+    // Two capsules that overlap on a disc at the join, and on a rhombus in the 45 degrees
+    // angle between the lines (minus the part of the rhombus that is in the disc).
+    let area = covered_area(&buffers, point(-2.0, -2.0), point(12.0, 12.0), 0.05);
+    let len = 200.0f32.sqrt();
+    let capsules = 10.0 * 2.0 + PI + len * 2.0 + PI;
+    let overlap = PI + 1.0 / (PI / 4.0).sin() - PI / 8.0;
+    assert!((area - (capsules - overlap)).abs() < 0.3, "{} {}", area, capsules - overlap);
+}
+
+#[test]
+fn closed_nib_stroke() {
+    let mut builder = Path::builder();
+    builder.polygon(&[point(0.0, 0.0), point(10.0, 0.0), point(10.0, 10.0), point(0.0, 10.0)]);
+    builder.move_to(point(20.0, 20.0));
+    let path = builder.build();
+
+    let mut buffers: VertexBuffers<StrokeVertex, u16> = VertexBuffers::new();
+    StrokeTessellator::new().tessellate_path_with_nib(
+        path.iter(),
+        &Nib::elliptical(0.5, Angle::degrees(30.0)),
+        &StrokeOptions::default().with_line_width(1.0),
+        &mut BuffersBuilder::new(&mut buffers, Identity),
+    ).unwrap();
+
+    // The interior of the square isn't covered, but its corners are.
+    let center = covered_area(&buffers, point(2.0, 2.0), point(8.0, 8.0), 0.1);
+    assert_eq!(center, 0.0);
+    let corners: [Point; 4] = [point(0.0, 0.0), point(10.0, 0.0), point(10.0, 10.0), point(0.0, 10.0)];
+    for corner in &corners {
+        let d = vector(0.1, 0.1);
+        let area = covered_area(&buffers, *corner - d, *corner + d, 0.01);
+        assert!((area - 0.04).abs() < 0.005);
+    }
+
+    // The lone move_to produces a dot.
+    assert!(buffers.vertices.iter().any(|v| v.position.x > 19.0 && v.position.y > 19.0));
+}
@@ -0,0 +1,270 @@
+//! Clipping of the generated triangles against a rectangle.
+//!
+//! Triangles that are partially inside of the rectangle are clipped against each of its sides
+//! and the resulting convex polygons are triangulated. Cut positions are always computed from
+//! the endpoints of an edge taken in the same order, so two triangles that share an edge (or
+//! two adjacent clip rectangles) place the vertices at the exact same positions on that edge.
+
+use crate::geom::math::*;
+use crate::geometry_builder::{GeometryBuilder, GeometryBuilderError, VertexId, Count};
+use crate::{StrokeVertex, VertexSource};
+
+use std::collections::HashMap;
+
+/// Vertices that can be interpolated along the edges of a triangle.
+pub(crate) trait ClipVertex: Copy {
+    fn position(&self) -> Point;
+    fn interpolate(&self, other: &Self, t: f32, position: Point) -> Self;
+}
+
+impl ClipVertex for StrokeVertex {
+    fn position(&self) -> Point { self.position }
+
+    // The new vertex is attributed to the side of the first vertex.
+    fn interpolate(&self, other: &Self, t: f32, position: Point) -> Self {
+        StrokeVertex {
+            position,
+            normal: self.normal.lerp(other.normal, t),
+            advancement: self.advancement + (other.advancement - self.advancement) * t,
+            side: self.side,
+            source: VertexSource::Unknown,
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+enum ClipSide { MinX, MaxX, MinY, MaxY }
+
+const SIDES: [ClipSide; 4] = [ClipSide::MinX, ClipSide::MaxX, ClipSide::MinY, ClipSide::MaxY];
+
+/// A geometry builder adapter that discards the parts of the triangles it receives that
+/// are outside of a rectangle, and forwards the result to another geometry builder.
+///
+/// Vertices are only forwarded once they are used by a triangle that is at least partially
+/// inside of the rectangle.
+///
+/// The adapter does not forward `begin_geometry`, `end_geometry` and `abort_geometry`,
+/// which are the responsibility of the tessellator that owns the output builder.
+pub(crate) struct TriangleClipper<'l, V> {
+    output: &'l mut dyn GeometryBuilder<V>,
+    rect: Rect,
+    vertices: Vec<V>,
+    output_ids: Vec<Option<VertexId>>,
+    cut_ids: HashMap<(u32, u32), VertexId>,
+    polygon: Vec<(V, Option<VertexId>)>,
+    tmp: Vec<(V, Option<VertexId>)>,
+    error: Option<GeometryBuilderError>,
+}
+
+impl<'l, V: ClipVertex> TriangleClipper<'l, V> {
+    pub fn new(rect: Rect, output: &'l mut dyn GeometryBuilder<V>) -> Self {
+        TriangleClipper {
+            output,
+            rect,
+            vertices: Vec::new(),
+            output_ids: Vec::new(),
+            cut_ids: HashMap::new(),
+            polygon: Vec::new(),
+            tmp: Vec::new(),
+            error: None,
+        }
+    }
+
+    /// The first error returned by the output builder while inserting vertices, if any.
+    pub fn error(&self) -> Option<GeometryBuilderError> { self.error }
+
+    fn is_inside(&self, position: Point, side: ClipSide) -> bool {
+        match side {
+            ClipSide::MinX => position.x >= self.rect.min_x(),
+            ClipSide::MaxX => position.x <= self.rect.max_x(),
+            ClipSide::MinY => position.y >= self.rect.min_y(),
+            ClipSide::MaxY => position.y <= self.rect.max_y(),
+        }
+    }
+
+    // The vertex where the edge between two vertices crosses a side of the rectangle.
+    fn cut(&self, a: &V, b: &V, side: ClipSide) -> V {
+        // Always interpolate in the same direction so that the result does not
+        // depend on the orientation of the edge.
+        let (a, b) = if (a.position().x, a.position().y) <= (b.position().x, b.position().y) {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        let (pa, pb) = (a.position(), b.position());
+
+        let (t, position) = match side {
+            ClipSide::MinX | ClipSide::MaxX => {
+                let x = if let ClipSide::MinX = side { self.rect.min_x() } else { self.rect.max_x() };
+                let t = (x - pa.x) / (pb.x - pa.x);
+                (t, point(x, pa.y + (pb.y - pa.y) * t))
+            }
+            ClipSide::MinY | ClipSide::MaxY => {
+                let y = if let ClipSide::MinY = side { self.rect.min_y() } else { self.rect.max_y() };
+                let t = (y - pa.y) / (pb.y - pa.y);
+                (t, point(pa.x + (pb.x - pa.x) * t, y))
+            }
+        };
+
+        a.interpolate(b, t, position)
+    }
+
+    fn output_id(&mut self, vertex: &(V, Option<VertexId>)) -> Result<VertexId, GeometryBuilderError> {
+        match vertex.1 {
+            Some(id) => {
+                if let Some(output_id) = self.output_ids[id.to_usize()] {
+                    return Ok(output_id);
+                }
+                let output_id = self.output.add_vertex(vertex.0)?;
+                self.output_ids[id.to_usize()] = Some(output_id);
+
+                Ok(output_id)
+            }
+            None => {
+                let position = vertex.0.position();
+                let key = (position.x.to_bits(), position.y.to_bits());
+                if let Some(output_id) = self.cut_ids.get(&key) {
+                    return Ok(*output_id);
+                }
+                let output_id = self.output.add_vertex(vertex.0)?;
+                self.cut_ids.insert(key, output_id);
+
+                Ok(output_id)
+            }
+        }
+    }
+
+    fn add_polygon(&mut self) -> Result<(), GeometryBuilderError> {
+        let polygon = std::mem::replace(&mut self.polygon, Vec::new());
+        let mut result = Ok(());
+        if polygon.len() >= 3 {
+            let mut ids = Vec::with_capacity(polygon.len());
+            for vertex in &polygon {
+                match self.output_id(vertex) {
+                    Ok(id) => { ids.push((id, vertex.0.position())); }
+                    Err(e) => {
+                        result = Err(e);
+                        break;
+                    }
+                }
+            }
+
+            if result.is_ok() {
+                for i in 1..(ids.len() - 1) {
+                    let (a, pa) = ids[0];
+                    let (b, pb) = ids[i];
+                    let (c, pc) = ids[i + 1];
+                    if (pb - pa).cross(pc - pa) != 0.0 {
+                        self.output.add_triangle(a, b, c);
+                    }
+                }
+            }
+        }
+        self.polygon = polygon;
+
+        result
+    }
+}
+
+impl<'l, V: ClipVertex> GeometryBuilder<V> for TriangleClipper<'l, V> {
+    fn begin_geometry(&mut self) {}
+
+    fn end_geometry(&mut self) -> Count { Count { vertices: 0, indices: 0 } }
+
+    fn abort_geometry(&mut self) {}
+
+    fn add_vertex(&mut self, vertex: V) -> Result<VertexId, GeometryBuilderError> {
+        let id = VertexId::from_usize(self.vertices.len());
+        self.vertices.push(vertex);
+        self.output_ids.push(None);
+
+        Ok(id)
+    }
+
+    fn add_triangle(&mut self, a: VertexId, b: VertexId, c: VertexId) {
+        if self.error.is_some() {
+            return;
+        }
+
+        self.polygon.clear();
+        for &id in &[a, b, c] {
+            self.polygon.push((self.vertices[id.to_usize()], Some(id)));
+        }
+
+        // Sutherland-Hodgman clipping against each side of the rectangle.
+        for &side in &SIDES {
+            let mut tmp = std::mem::replace(&mut self.tmp, Vec::new());
+            tmp.clear();
+            let n = self.polygon.len();
+            for i in 0..n {
+                let current = self.polygon[i];
+                let next = self.polygon[(i + 1) % n];
+                let current_inside = self.is_inside(current.0.position(), side);
+                let next_inside = self.is_inside(next.0.position(), side);
+                if current_inside {
+                    tmp.push(current);
+                }
+                if current_inside != next_inside {
+                    tmp.push((self.cut(&current.0, &next.0, side), None));
+                }
+            }
+            self.tmp = std::mem::replace(&mut self.polygon, tmp);
+
+            if self.polygon.is_empty() {
+                return;
+            }
+        }
+
+        if let Err(e) = self.add_polygon() {
+            self.error = Some(e);
+        }
+    }
+}
+
+#[test]
+fn test_clip_triangle() {
+    use crate::geometry_builder::{VertexBuffers, simple_builder};
+    use crate::Side;
+
+    let mut buffers: VertexBuffers<StrokeVertex, u16> = VertexBuffers::new();
+    {
+        let mut output = simple_builder(&mut buffers);
+        let mut clipper = TriangleClipper::new(rect(0.0, 0.0, 2.0, 2.0), &mut output);
+        let mut vertex = |x, y| clipper.add_vertex(StrokeVertex {
+            position: point(x, y),
+            normal: vector(0.0, 0.0),
+            advancement: x,
+            side: Side::Left,
+            source: VertexSource::Unknown,
+        }).unwrap();
+        let a = vertex(-1.0, 1.0);
+        let b = vertex(3.0, 1.0);
+        let c = vertex(1.0, 3.0);
+        let d = vertex(5.0, 5.0);
+        let e = vertex(6.0, 5.0);
+        let f = vertex(6.0, 6.0);
+        clipper.add_triangle(a, b, c);
+        // Completely outside.
+        clipper.add_triangle(d, e, f);
+        assert!(clipper.error().is_none());
+    }
+
+    // Only the vertices created on the sides of the rectangle are forwarded.
+    assert_eq!(buffers.vertices.len(), 4);
+
+    let mut area = 0.0;
+    for tri in buffers.indices.chunks(3) {
+        let a = buffers.vertices[tri[0] as usize].position;
+        let b = buffers.vertices[tri[1] as usize].position;
+        let c = buffers.vertices[tri[2] as usize].position;
+        area += (b - a).cross(c - a) * 0.5;
+    }
+    for vertex in &buffers.vertices {
+        assert!(vertex.position.x >= 0.0 && vertex.position.x <= 2.0);
+        assert!(vertex.position.y >= 0.0 && vertex.position.y <= 2.0);
+        assert!((vertex.advancement - vertex.position.x).abs() < 0.0001);
+    }
+
+    // The part of the triangle inside of the rectangle is its lower half.
+    assert!((area - 2.0).abs() < 0.0001);
+}
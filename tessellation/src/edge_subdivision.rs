@@ -0,0 +1,198 @@
+//! Subdivision of the generated triangles so that no edge exceeds a maximum length.
+//!
+//! Triangles are recursively split by inserting a vertex in the middle of their longest
+//! edge until all of their edges are short enough. Since an edge is always split at its
+//! midpoint and midpoints are shared through a cache, two triangles that share an edge
+//! always subdivide it the same way and the resulting mesh does not contain T-junctions.
+
+use crate::geom::math::*;
+use crate::geometry_builder::{GeometryBuilder, GeometryBuilderError, VertexId, Count};
+use crate::{FillVertex, StrokeVertex, VertexSource};
+
+use std::collections::HashMap;
+
+/// Vertices that can be interpolated along the edges of a triangle.
+pub(crate) trait SubdivisionVertex: Copy {
+    fn position(&self) -> Point;
+    fn midpoint(&self, other: &Self) -> Self;
+}
+
+impl SubdivisionVertex for FillVertex {
+    fn position(&self) -> Point { self.position }
+
+    fn midpoint(&self, other: &Self) -> Self {
+        FillVertex {
+            position: self.position.lerp(other.position, 0.5),
+            normal: self.normal.lerp(other.normal, 0.5),
+            source: VertexSource::Unknown,
+        }
+    }
+}
+
+impl SubdivisionVertex for StrokeVertex {
+    fn position(&self) -> Point { self.position }
+
+    // When splitting an edge that goes across the stroke, the new vertex is
+    // attributed to the side of the first vertex.
+    fn midpoint(&self, other: &Self) -> Self {
+        StrokeVertex {
+            position: self.position.lerp(other.position, 0.5),
+            normal: self.normal.lerp(other.normal, 0.5),
+            advancement: (self.advancement + other.advancement) * 0.5,
+            side: self.side,
+            source: VertexSource::Unknown,
+        }
+    }
+}
+
+/// A geometry builder adapter that splits the triangles it receives until none
+/// of their edges is longer than a given length, and forwards the result to
+/// another geometry builder.
+///
+/// The adapter does not forward `begin_geometry`, `end_geometry` and `abort_geometry`,
+/// which are the responsibility of the tessellator that owns the output builder.
+pub(crate) struct EdgeSubdivider<'l, V> {
+    output: &'l mut dyn GeometryBuilder<V>,
+    square_max_length: f32,
+    vertices: Vec<V>,
+    output_ids: Vec<VertexId>,
+    midpoints: HashMap<(VertexId, VertexId), VertexId>,
+    stack: Vec<[VertexId; 3]>,
+    error: Option<GeometryBuilderError>,
+}
+
+impl<'l, V: SubdivisionVertex> EdgeSubdivider<'l, V> {
+    pub fn new(max_edge_length: f32, output: &'l mut dyn GeometryBuilder<V>) -> Self {
+        debug_assert!(max_edge_length > 0.0);
+        EdgeSubdivider {
+            output,
+            square_max_length: max_edge_length * max_edge_length,
+            vertices: Vec::new(),
+            output_ids: Vec::new(),
+            midpoints: HashMap::new(),
+            stack: Vec::new(),
+            error: None,
+        }
+    }
+
+    /// The first error returned by the output builder while inserting vertices, if any.
+    pub fn error(&self) -> Option<GeometryBuilderError> { self.error }
+
+    fn square_length(&self, a: VertexId, b: VertexId) -> f32 {
+        let a = self.vertices[a.to_usize()].position();
+        let b = self.vertices[b.to_usize()].position();
+        (b - a).square_length()
+    }
+
+    fn midpoint(&mut self, a: VertexId, b: VertexId) -> Result<VertexId, GeometryBuilderError> {
+        let key = if a.0 < b.0 { (a, b) } else { (b, a) };
+        if let Some(id) = self.midpoints.get(&key) {
+            return Ok(*id);
+        }
+
+        let vertex = self.vertices[key.0.to_usize()].midpoint(&self.vertices[key.1.to_usize()]);
+        let id = self.add_vertex(vertex)?;
+        self.midpoints.insert(key, id);
+
+        Ok(id)
+    }
+}
+
+impl<'l, V: SubdivisionVertex> GeometryBuilder<V> for EdgeSubdivider<'l, V> {
+    fn begin_geometry(&mut self) {}
+
+    fn end_geometry(&mut self) -> Count { Count { vertices: 0, indices: 0 } }
+
+    fn abort_geometry(&mut self) {}
+
+    fn add_vertex(&mut self, vertex: V) -> Result<VertexId, GeometryBuilderError> {
+        let output_id = self.output.add_vertex(vertex)?;
+        let id = VertexId::from_usize(self.vertices.len());
+        self.vertices.push(vertex);
+        self.output_ids.push(output_id);
+
+        Ok(id)
+    }
+
+    fn add_triangle(&mut self, a: VertexId, b: VertexId, c: VertexId) {
+        if self.error.is_some() {
+            return;
+        }
+
+        self.stack.push([a, b, c]);
+        while let Some(triangle) = self.stack.pop() {
+            // Find the longest edge.
+            let mut longest = 0;
+            let mut longest_sq_len = 0.0;
+            for i in 0..3 {
+                let sq_len = self.square_length(triangle[i], triangle[(i + 1) % 3]);
+                if sq_len > longest_sq_len {
+                    longest = i;
+                    longest_sq_len = sq_len;
+                }
+            }
+
+            if longest_sq_len <= self.square_max_length {
+                self.output.add_triangle(
+                    self.output_ids[triangle[0].to_usize()],
+                    self.output_ids[triangle[1].to_usize()],
+                    self.output_ids[triangle[2].to_usize()],
+                );
+                continue;
+            }
+
+            // Split the longest edge in two, preserving the winding order.
+            let a = triangle[longest];
+            let b = triangle[(longest + 1) % 3];
+            let c = triangle[(longest + 2) % 3];
+            let mid = match self.midpoint(a, b) {
+                Ok(id) => id,
+                Err(e) => {
+                    self.error = Some(e);
+                    self.stack.clear();
+                    return;
+                }
+            };
+
+            self.stack.push([a, mid, c]);
+            self.stack.push([mid, b, c]);
+        }
+    }
+}
+
+#[test]
+fn test_subdivide_triangle() {
+    use crate::geometry_builder::{VertexBuffers, simple_builder};
+
+    let mut buffers: VertexBuffers<FillVertex, u16> = VertexBuffers::new();
+    {
+        let mut output = simple_builder(&mut buffers);
+        let mut subdivider = EdgeSubdivider::new(1.0, &mut output);
+        let mut vertex = |x, y| subdivider.add_vertex(FillVertex {
+            position: point(x, y),
+            normal: vector(0.0, 0.0),
+            source: VertexSource::Unknown,
+        }).unwrap();
+        let a = vertex(0.0, 0.0);
+        let b = vertex(4.0, 0.0);
+        let c = vertex(0.0, 4.0);
+        subdivider.add_triangle(a, b, c);
+        assert!(subdivider.error().is_none());
+    }
+
+    let mut area = 0.0;
+    for tri in buffers.indices.chunks(3) {
+        let a = buffers.vertices[tri[0] as usize].position;
+        let b = buffers.vertices[tri[1] as usize].position;
+        let c = buffers.vertices[tri[2] as usize].position;
+        for &(p0, p1) in &[(a, b), (b, c), (c, a)] {
+            assert!((p1 - p0).length() <= 1.0);
+        }
+        // The winding order must be preserved.
+        let signed_area = (b - a).cross(c - a) * 0.5;
+        assert!(signed_area > 0.0);
+        area += signed_area;
+    }
+
+    assert!((area - 8.0).abs() < 0.001);
+}
@@ -0,0 +1,311 @@
+//! Tessellation of paths with `f64` coordinates.
+//!
+//! The tessellators work with `f32` coordinates internally (and the fill tessellator
+//! with 16.16 fixed point numbers). Paths with large coordinates are first moved
+//! close to the origin, in double precision, and converted to `f32`. The generated
+//! vertices are moved back when they are passed to the output.
+
+use crate::{FillTessellator, StrokeTessellator, FillOptions, StrokeOptions, TessellationResult};
+use crate::{FillVertex, StrokeVertex, F64FillVertex, F64StrokeVertex};
+use crate::geometry_builder::{GeometryBuilder, GeometryBuilderError, Count, VertexId};
+use crate::math::{Point, F64Point, Rect, point, size};
+use crate::math_utils::fixed;
+use crate::path::{F64Path, F64PathEvent};
+
+use std::collections::HashMap;
+
+// The half-extent of the fill tessellator's input is scaled to be at most this value
+// which keeps the coordinates well within the range of the fixed point numbers.
+const FILL_RANGE: f64 = 4096.0;
+
+/// Maps a region of double precision coordinates to single precision coordinates
+/// around the origin.
+#[derive(Copy, Clone, Debug)]
+struct LocalSpace {
+    origin: F64Point,
+    scale: f64,
+}
+
+impl LocalSpace {
+    fn new(path: &F64Path, range: Option<f64>) -> Self {
+        let (min, max) = match path.bounds() {
+            Some(bounds) => bounds,
+            None => { return LocalSpace { origin: point(0.0, 0.0), scale: 1.0 }; }
+        };
+
+        let origin = min.lerp(max, 0.5);
+
+        // Scale by a power of two so that the conversion itself doesn't
+        // introduce rounding errors.
+        let half_extent = (max.x - min.x).max(max.y - min.y) * 0.5;
+        let scale = match range {
+            Some(range) if half_extent > 0.0 => {
+                2.0f64.powi((range / half_extent).log2().floor() as i32)
+            }
+            _ => 1.0,
+        };
+
+        LocalSpace { origin, scale }
+    }
+
+    fn local_point(&self, p: F64Point) -> Point {
+        ((p - self.origin) * self.scale).to_point().cast::<f32>()
+    }
+
+    fn global_point(&self, p: Point) -> F64Point {
+        self.origin + p.cast::<f64>().to_vector() / self.scale
+    }
+
+    fn local_rect(&self, rect: &Rect) -> Rect {
+        let origin = self.local_point(rect.origin.cast::<f64>());
+        let scale = self.scale as f32;
+        Rect {
+            origin,
+            size: size(rect.size.width * scale, rect.size.height * scale),
+        }
+    }
+}
+
+impl FillTessellator {
+    /// Compute the tessellation of a path with `f64` coordinates.
+    ///
+    /// The path is moved close to the origin and scaled by a power of two in double
+    /// precision before it is tessellated, so that the precision of the result depends on
+    /// the size of the path rather than on the magnitude of its coordinates.
+    /// The tolerance, `max_edge_length` and `clip_rect` options are expressed
+    /// in the coordinate space of the path.
+    ///
+    /// Vertices that correspond to endpoints of the path are given the exact position of
+    /// the endpoint, which avoids cracks between adjacent shapes that are tessellated
+    /// separately. The position of the other vertices is converted back to `f64` with
+    /// the precision of the tessellator.
+    pub fn tessellate_f64_path(
+        &mut self,
+        path: &F64Path,
+        options: &FillOptions,
+        output: &mut dyn GeometryBuilder<F64FillVertex>,
+    ) -> TessellationResult {
+        let space = LocalSpace::new(path, Some(FILL_RANGE));
+
+        let scale = space.scale as f32;
+        let mut options = *options;
+        options.tolerance *= scale;
+        options.max_edge_length = options.max_edge_length.map(|length| length * scale);
+        options.clip_rect = options.clip_rect.map(|rect| space.local_rect(&rect));
+
+        let mut endpoints = HashMap::new();
+        for evt in path.iter() {
+            let to = match evt {
+                F64PathEvent::MoveTo(to) => to,
+                F64PathEvent::Line(segment) => segment.to,
+                F64PathEvent::Quadratic(segment) => segment.to,
+                F64PathEvent::Cubic(segment) => segment.to,
+                F64PathEvent::Close(..) => { continue; }
+            };
+            endpoints.entry(fixed_key(space.local_point(to))).or_insert(to);
+        }
+
+        let mut adapter = F64FillOutput {
+            output,
+            space,
+            endpoints: &endpoints,
+        };
+
+        self.tessellate_path(
+            path.iter_f32_relative(space.origin, space.scale),
+            &options,
+            &mut adapter,
+        )
+    }
+}
+
+impl StrokeTessellator {
+    /// Compute the tessellation of a path with `f64` coordinates.
+    ///
+    /// The path is moved close to the origin in double precision before it is
+    /// tessellated, so that the precision of the result depends on the size of the
+    /// path rather than on the magnitude of its coordinates. The options are
+    /// expressed in the coordinate space of the path.
+    pub fn tessellate_f64_path(
+        &mut self,
+        path: &F64Path,
+        options: &StrokeOptions,
+        output: &mut dyn GeometryBuilder<F64StrokeVertex>,
+    ) -> TessellationResult {
+        let space = LocalSpace::new(path, None);
+
+        let mut options = *options;
+        options.clip_rect = options.clip_rect.map(|rect| space.local_rect(&rect));
+
+        let mut adapter = F64StrokeOutput { output, space };
+
+        self.tessellate_path(
+            path.iter_f32_relative(space.origin, space.scale),
+            &options,
+            &mut adapter,
+        )
+    }
+}
+
+// The fill tessellator snaps positions to its fixed point grid, so the endpoints
+// are looked up with the same precision.
+fn fixed_key(p: Point) -> (i32, i32) {
+    (fixed(p.x).raw(), fixed(p.y).raw())
+}
+
+struct F64FillOutput<'l> {
+    output: &'l mut dyn GeometryBuilder<F64FillVertex>,
+    space: LocalSpace,
+    endpoints: &'l HashMap<(i32, i32), F64Point>,
+}
+
+impl<'l> GeometryBuilder<FillVertex> for F64FillOutput<'l> {
+    fn begin_geometry(&mut self) { self.output.begin_geometry(); }
+
+    fn end_geometry(&mut self) -> Count { self.output.end_geometry() }
+
+    fn add_vertex(&mut self, vertex: FillVertex) -> Result<VertexId, GeometryBuilderError> {
+        let position = match self.endpoints.get(&fixed_key(vertex.position)) {
+            Some(position) => *position,
+            None => self.space.global_point(vertex.position),
+        };
+
+        self.output.add_vertex(F64FillVertex {
+            position,
+            normal: vertex.normal,
+            source: vertex.source,
+        })
+    }
+
+    fn add_triangle(&mut self, a: VertexId, b: VertexId, c: VertexId) {
+        self.output.add_triangle(a, b, c);
+    }
+
+    fn abort_geometry(&mut self) { self.output.abort_geometry(); }
+}
+
+struct F64StrokeOutput<'l> {
+    output: &'l mut dyn GeometryBuilder<F64StrokeVertex>,
+    space: LocalSpace,
+}
+
+impl<'l> GeometryBuilder<StrokeVertex> for F64StrokeOutput<'l> {
+    fn begin_geometry(&mut self) { self.output.begin_geometry(); }
+
+    fn end_geometry(&mut self) -> Count { self.output.end_geometry() }
+
+    fn add_vertex(&mut self, vertex: StrokeVertex) -> Result<VertexId, GeometryBuilderError> {
+        self.output.add_vertex(F64StrokeVertex {
+            position: self.space.global_point(vertex.position),
+            normal: vertex.normal,
+            advancement: f64::from(vertex.advancement) / self.space.scale,
+            side: vertex.side,
+            source: vertex.source,
+        })
+    }
+
+    fn add_triangle(&mut self, a: VertexId, b: VertexId, c: VertexId) {
+        self.output.add_triangle(a, b, c);
+    }
+
+    fn abort_geometry(&mut self) { self.output.abort_geometry(); }
+}
+
+#[cfg(test)]
+use crate::geometry_builder::{VertexBuffers, BuffersBuilder, Identity};
+
+#[cfg(test)]
+fn f64_fill_area(buffers: &VertexBuffers<F64FillVertex, u16>) -> f64 {
+    let mut area = 0.0;
+    for tri in buffers.indices.chunks(3) {
+        let a = buffers.vertices[tri[0] as usize].position;
+        let b = buffers.vertices[tri[1] as usize].position;
+        let c = buffers.vertices[tri[2] as usize].position;
+        area += ((b - a).cross(c - a) * 0.5).abs();
+    }
+
+    area
+}
+
+#[test]
+fn fill_f64_large_coordinates() {
+    // A 1x1 square far away from the origin, with a notch that is much smaller than
+    // the precision of f32 at this magnitude.
+    let x = 6_000_000.0;
+    let y = -3_000_000.0;
+    let mut builder = F64Path::builder();
+    builder.polygon(&[
+        point(x, y),
+        point(x + 1.0, y),
+        point(x + 1.0, y + 1.0),
+        point(x + 0.5 + 0.01, y + 1.0),
+        point(x + 0.5, y + 0.99),
+        point(x + 0.5 - 0.01, y + 1.0),
+        point(x, y + 1.0),
+    ]);
+    let path = builder.build();
+
+    let mut buffers: VertexBuffers<F64FillVertex, u16> = VertexBuffers::new();
+    FillTessellator::new().tessellate_f64_path(
+        &path,
+        &FillOptions::default(),
+        &mut BuffersBuilder::new(&mut buffers, Identity),
+    ).unwrap();
+
+    assert!((f64_fill_area(&buffers) - (1.0 - 0.0001)).abs() < 1e-7);
+
+    // Vertices at endpoints have the exact position of the input.
+    for p in path.points() {
+        assert!(buffers.vertices.iter().any(|v| v.position == *p));
+    }
+}
+
+#[test]
+fn fill_f64_curves() {
+    let mut builder = F64Path::builder();
+    builder.move_to(point(1.0e7, 1.0e7));
+    builder.quadratic_bezier_to(point(1.0e7 + 100.0, 1.0e7), point(1.0e7 + 100.0, 1.0e7 + 100.0));
+    builder.line_to(point(1.0e7, 1.0e7 + 100.0));
+    builder.close();
+    let path = builder.build();
+
+    let mut buffers: VertexBuffers<F64FillVertex, u16> = VertexBuffers::new();
+    FillTessellator::new().tessellate_f64_path(
+        &path,
+        &FillOptions::tolerance(0.01),
+        &mut BuffersBuilder::new(&mut buffers, Identity),
+    ).unwrap();
+
+    // The area between the curve and the line from its start to its end is 2/3 of
+    // the triangle formed by its control points.
+    let expected = 100.0 * 100.0 / 2.0 + 100.0 * 100.0 / 2.0 * 2.0 / 3.0;
+    assert!((f64_fill_area(&buffers) - expected).abs() < 1.0);
+
+    for v in &buffers.vertices {
+        assert!(v.position.x >= 1.0e7 && v.position.x <= 1.0e7 + 100.0);
+        assert!(v.position.y >= 1.0e7 && v.position.y <= 1.0e7 + 100.0);
+    }
+}
+
+#[test]
+fn stroke_f64_large_coordinates() {
+    let mut builder = F64Path::builder();
+    builder.move_to(point(1.0e8, 1.0e8));
+    builder.line_to(point(1.0e8 + 1.0, 1.0e8));
+    let path = builder.build();
+
+    let mut buffers: VertexBuffers<F64StrokeVertex, u16> = VertexBuffers::new();
+    StrokeTessellator::new().tessellate_f64_path(
+        &path,
+        &StrokeOptions::default().with_line_width(0.1),
+        &mut BuffersBuilder::new(&mut buffers, Identity),
+    ).unwrap();
+
+    assert_eq!(buffers.vertices.len(), 4);
+    for v in &buffers.vertices {
+        assert!((v.position.y - 1.0e8).abs() - 0.05 < 1e-6);
+        assert!(v.position.x == 1.0e8 || v.position.x == 1.0e8 + 1.0);
+        assert_eq!(v.normal.x, 0.0);
+    }
+    assert!(buffers.vertices.iter().any(|v| v.advancement == 1.0));
+}
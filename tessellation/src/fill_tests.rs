@@ -1,11 +1,13 @@
 use crate::geom::math::*;
 use crate::geometry_builder::*;
 use crate::path::builder::{Build, FlatPathBuilder, PathBuilder};
-use crate::path::{Path, PathSlice};
+use crate::path::{Path, PathSlice, PathEvent};
 use crate::extra::rust_logo::build_logo_path;
-use crate::{FillTessellator, TessellationError, FillOptions, FillVertex, OnError};
+use crate::{FillTessellator, TessellationError, FillOptions, FillVertex, OnError, PolygonMode};
+use crate::{VertexSource, EventPosition, PathId, FillRule, FillBudget, FillStatus};
 
 use std::env;
+use std::time::Duration;
 
 type Vertex = FillVertex;
 
@@ -191,6 +193,36 @@ fn test_simple_merge_split() {
     // "M 0 0 L 1 1 L 2 0 L 1 3 L 0 4 L 0 3 Z"
 }
 
+#[test]
+fn test_split_with_several_edges_below() {
+    // A split vertex that starts two holes.
+    //
+    //  x-------------x
+    //  |      x      |
+    //  |     /|\     |
+    //  |    / | \    |
+    //  |   /  |  \   |
+    //  |  x-x x-x    |
+    //  x-------------x
+    let mut path = Path::builder();
+    path.move_to(point(0.0, 0.0));
+    path.line_to(point(10.0, 0.0));
+    path.line_to(point(10.0, 10.0));
+    path.line_to(point(0.0, 10.0));
+    path.close();
+
+    path.move_to(point(5.0, 2.0));
+    path.line_to(point(2.0, 8.0));
+    path.line_to(point(4.0, 8.0));
+    path.close();
+    path.move_to(point(5.0, 2.0));
+    path.line_to(point(6.0, 8.0));
+    path.line_to(point(8.0, 8.0));
+    path.close();
+
+    test_path(path.build().as_slice());
+}
+
 #[test]
 fn test_simple_aligned() {
     let mut path = Path::builder();
@@ -1126,3 +1158,718 @@ fn test_exp_no_intersection_01() {
     // SVG path syntax:
     // "M 80.041534 19.24472 L 76.56131 23.062233 L 67.26949 23.039438 L 48.42367 28.978098 Z"
 }
+
+#[test]
+fn test_max_edge_length() {
+    let mut path = Path::builder().with_svg();
+    build_logo_path(&mut path);
+    let path = path.build();
+
+    let max_edge_length = 5.0;
+    let mut buffers: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+    FillTessellator::new().tessellate_path(
+        path.iter(),
+        &FillOptions::tolerance(0.05).with_max_edge_length(max_edge_length),
+        &mut simple_builder(&mut buffers),
+    ).unwrap();
+
+    assert!(!buffers.indices.is_empty());
+    for tri in buffers.indices.chunks(3) {
+        for i in 0..3 {
+            let a = buffers.vertices[tri[i] as usize].position;
+            let b = buffers.vertices[tri[(i + 1) % 3] as usize].position;
+            assert!((b - a).length() <= max_edge_length);
+        }
+    }
+}
+
+#[test]
+fn test_polygon_output() {
+    struct Polygons {
+        vertices: Vec<Point>,
+        polygons: Vec<Vec<VertexId>>,
+    }
+
+    impl GeometryBuilder<Vertex> for Polygons {
+        fn begin_geometry(&mut self) {}
+        fn end_geometry(&mut self) -> Count { Count { vertices: 0, indices: 0 } }
+        fn abort_geometry(&mut self) {}
+        fn add_vertex(&mut self, v: Vertex) -> Result<VertexId, GeometryBuilderError> {
+            self.vertices.push(v.position);
+            Ok(VertexId(self.vertices.len() as u32 - 1))
+        }
+        fn add_triangle(&mut self, _: VertexId, _: VertexId, _: VertexId) {
+            panic!("Unexpected triangle");
+        }
+    }
+
+    impl PolygonGeometryBuilder<Vertex> for Polygons {
+        fn add_polygon(&mut self, vertices: &[VertexId]) {
+            self.polygons.push(vertices.to_vec());
+        }
+    }
+
+    fn area(points: &[Point]) -> f32 {
+        let mut area = 0.0;
+        for i in 0..points.len() {
+            area += points[i].to_vector().cross(points[(i + 1) % points.len()].to_vector());
+        }
+        area * 0.5
+    }
+
+    let mut path = Path::builder().with_svg();
+    build_logo_path(&mut path);
+    let path = path.build();
+    let options = FillOptions::tolerance(0.05);
+
+    let mut buffers: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+    FillTessellator::new().tessellate_path(
+        path.iter(),
+        &options,
+        &mut simple_builder(&mut buffers),
+    ).unwrap();
+    let triangles_area: f32 = buffers.indices.chunks(3).map(|tri| area(&[
+        buffers.vertices[tri[0] as usize].position,
+        buffers.vertices[tri[1] as usize].position,
+        buffers.vertices[tri[2] as usize].position,
+    ])).sum();
+
+    for &mode in &[PolygonMode::Monotone, PolygonMode::Convex] {
+        let mut output = Polygons { vertices: Vec::new(), polygons: Vec::new() };
+        FillTessellator::new().tessellate_path_to_polygons(
+            path.iter(),
+            &options,
+            mode,
+            &mut output,
+        ).unwrap();
+
+        assert!(!output.polygons.is_empty());
+
+        let mut polygons_area = 0.0;
+        for polygon in &output.polygons {
+            assert!(polygon.len() >= 3);
+            let points: Vec<Point> = polygon.iter().map(|id| output.vertices[id.to_usize()]).collect();
+            polygons_area += area(&points);
+
+            if mode == PolygonMode::Convex {
+                for i in 0..points.len() {
+                    let prev = points[(i + points.len() - 1) % points.len()];
+                    let next = points[(i + 1) % points.len()];
+                    assert!((prev - points[i]).cross(next - points[i]) >= 0.0);
+                }
+            }
+        }
+
+        assert!((polygons_area - triangles_area).abs() < triangles_area.abs() * 0.001);
+    }
+}
+
+#[test]
+fn test_vertex_source() {
+    fn sample(events: &[PathEvent], position: &EventPosition) -> Point {
+        match events[position.event as usize] {
+            PathEvent::Line(segment) => segment.sample(position.t),
+            PathEvent::Close(segment) => segment.sample(position.t),
+            PathEvent::Quadratic(segment) => segment.sample(position.t),
+            PathEvent::Cubic(segment) => segment.sample(position.t),
+            PathEvent::MoveTo(_) => panic!("Unexpected MoveTo event"),
+        }
+    }
+
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(10.0, 0.0));
+    builder.quadratic_bezier_to(point(20.0, 5.0), point(10.0, 10.0));
+    builder.line_to(point(0.0, 10.0));
+    builder.close();
+    builder.move_to(point(20.0, 0.0));
+    builder.cubic_bezier_to(point(25.0, -5.0), point(30.0, 15.0), point(25.0, 10.0));
+    builder.line_to(point(20.0, 10.0));
+    let path = builder.build();
+    let events: Vec<PathEvent> = path.iter().collect();
+
+    let mut buffers: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+    FillTessellator::new().tessellate_path(
+        path.iter(),
+        &FillOptions::tolerance(0.05),
+        &mut simple_builder(&mut buffers),
+    ).unwrap();
+
+    let mut curve_vertices = 0;
+    for vertex in &buffers.vertices {
+        let expected_sub_path = if vertex.position.x < 15.0 { 0 } else { 1 };
+        let position = match vertex.source {
+            VertexSource::Endpoint(position) => {
+                assert!(position.t == 0.0 || position.t == 1.0);
+                position
+            }
+            VertexSource::Curve(position) => {
+                let event = position.event as usize;
+                assert!(event == 2 || event == 6);
+                curve_vertices += 1;
+                position
+            }
+            source => panic!("Unexpected source {:?} for {:?}", source, vertex.position),
+        };
+
+        assert_eq!(position.sub_path, expected_sub_path);
+        // The second sub-path is closed implicitly, its closing edge is
+        // attributed to the index following its last event.
+        if position.event == events.len() as u32 {
+            assert_eq!(position.sub_path, 1);
+            let closing = crate::geom::LineSegment { from: point(20.0, 10.0), to: point(20.0, 0.0) };
+            assert!((closing.sample(position.t) - vertex.position).length() < 0.01);
+        } else {
+            assert!((sample(&events, &position) - vertex.position).length() < 0.01);
+        }
+    }
+
+    assert!(curve_vertices > 0);
+}
+
+#[test]
+fn test_vertex_source_intersection() {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(10.0, 10.0));
+    builder.line_to(point(10.0, 0.0));
+    builder.line_to(point(0.0, 10.0));
+    builder.close();
+    let path = builder.build();
+
+    let mut buffers: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+    FillTessellator::new().tessellate_path(
+        path.iter(),
+        &FillOptions::default(),
+        &mut simple_builder(&mut buffers),
+    ).unwrap();
+
+    let mut intersections = 0;
+    for vertex in &buffers.vertices {
+        if let VertexSource::Intersection(a, b) = vertex.source {
+            assert_eq!(vertex.position, point(5.0, 5.0));
+            let mut events = [a.event, b.event];
+            events.sort();
+            assert_eq!(events, [1, 3]);
+            assert!((a.t - 0.5).abs() < 0.001);
+            assert!((b.t - 0.5).abs() < 0.001);
+            intersections += 1;
+        }
+    }
+
+    assert!(intersections > 0);
+}
+
+#[test]
+fn test_attributes() {
+    // The attributes are the coordinates of the endpoints, so they should match
+    // the position of the vertices after interpolation.
+    let mut builder = Path::builder_with_attributes(2);
+    builder.move_to_with_attributes(point(0.0, 0.0), &[0.0, 0.0]);
+    builder.line_to_with_attributes(point(10.0, 10.0), &[10.0, 10.0]);
+    builder.line_to_with_attributes(point(10.0, 0.0), &[10.0, 0.0]);
+    builder.line_to_with_attributes(point(0.0, 10.0), &[0.0, 10.0]);
+    builder.close();
+    // No close event.
+    builder.move_to_with_attributes(point(20.0, 0.0), &[20.0, 0.0]);
+    builder.line_to_with_attributes(point(30.0, 5.0), &[30.0, 5.0]);
+    builder.line_to_with_attributes(point(25.0, 10.0), &[25.0, 10.0]);
+    let path = builder.build();
+
+    let mut buffers: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+    let mut attributes = Vec::new();
+    FillTessellator::new().tessellate_path_with_attributes(
+        path.as_slice(),
+        &FillOptions::default(),
+        &mut simple_builder(&mut buffers),
+        &mut attributes,
+    ).unwrap();
+
+    assert_eq!(attributes.len(), buffers.vertices.len() * 2);
+    for (i, vertex) in buffers.vertices.iter().enumerate() {
+        let interpolated = point(attributes[i * 2], attributes[i * 2 + 1]);
+        assert!((interpolated - vertex.position).length() < 0.01);
+    }
+
+    assert_eq!(
+        FillTessellator::new().tessellate_path_with_attributes(
+            path.as_slice(),
+            &FillOptions::default().with_max_edge_length(1.0),
+            &mut simple_builder(&mut buffers),
+            &mut attributes,
+        ),
+        Err(TessellationError::UnsupportedParamater),
+    );
+}
+
+#[test]
+fn test_batch() {
+    struct Batch {
+        current_id: Option<PathId>,
+        vertex_ids: Vec<PathId>,
+        triangle_ids: Vec<PathId>,
+        num_geometries: u32,
+    }
+
+    impl GeometryBuilder<Vertex> for Batch {
+        fn begin_geometry(&mut self) { self.num_geometries += 1; }
+        fn end_geometry(&mut self) -> Count {
+            Count {
+                vertices: self.vertex_ids.len() as u32,
+                indices: self.triangle_ids.len() as u32 * 3,
+            }
+        }
+        fn abort_geometry(&mut self) { panic!(); }
+        fn add_vertex(&mut self, _: Vertex) -> Result<VertexId, GeometryBuilderError> {
+            self.vertex_ids.push(self.current_id.unwrap());
+            Ok(VertexId(self.vertex_ids.len() as u32 - 1))
+        }
+        fn add_triangle(&mut self, a: VertexId, b: VertexId, c: VertexId) {
+            let id = self.current_id.unwrap();
+            for v in &[a, b, c] {
+                assert_eq!(self.vertex_ids[v.to_usize()], id);
+            }
+            self.triangle_ids.push(id);
+        }
+    }
+
+    impl BatchGeometryBuilder<Vertex> for Batch {
+        fn set_path_id(&mut self, id: PathId) {
+            self.current_id = Some(id);
+        }
+    }
+
+    let mut builder = Path::builder();
+    builder.polygon(&[point(0.0, 0.0), point(10.0, 0.0), point(10.0, 10.0), point(0.0, 10.0)]);
+    let square = builder.build();
+
+    let mut builder = Path::builder();
+    builder.polygon(&[point(5.0, 5.0), point(20.0, 5.0), point(10.0, 20.0)]);
+    let triangle = builder.build();
+
+    let paths = [
+        (PathId(3), &square),
+        (PathId(7), &triangle),
+        (PathId(9), &square),
+    ];
+
+    let mut output = Batch {
+        current_id: None,
+        vertex_ids: Vec::new(),
+        triangle_ids: Vec::new(),
+        num_geometries: 0,
+    };
+
+    let count = FillTessellator::new().tessellate_batch(
+        paths.iter().map(|&(id, path)| (id, path.iter())),
+        &FillOptions::default(),
+        &mut output,
+    ).unwrap();
+
+    assert_eq!(output.num_geometries, 1);
+    assert_eq!(count.vertices, 11);
+    assert_eq!(count.indices, 15);
+    let triangles = |id| output.triangle_ids.iter().filter(|&&tri_id| tri_id == id).count();
+    // The overlapping paths are tessellated independently.
+    assert_eq!(triangles(PathId(3)), 2);
+    assert_eq!(triangles(PathId(7)), 1);
+    assert_eq!(triangles(PathId(9)), 2);
+}
+
+#[test]
+fn test_planar_map() {
+    struct PlanarMap {
+        current_id: Option<PathId>,
+        vertices: Vec<Point>,
+        areas: Vec<(PathId, f32)>,
+    }
+
+    impl GeometryBuilder<Vertex> for PlanarMap {
+        fn begin_geometry(&mut self) {}
+        fn end_geometry(&mut self) -> Count {
+            Count {
+                vertices: self.vertices.len() as u32,
+                indices: self.areas.len() as u32 * 3,
+            }
+        }
+        fn abort_geometry(&mut self) { panic!(); }
+        fn add_vertex(&mut self, v: Vertex) -> Result<VertexId, GeometryBuilderError> {
+            self.vertices.push(v.position);
+            Ok(VertexId(self.vertices.len() as u32 - 1))
+        }
+        fn add_triangle(&mut self, a: VertexId, b: VertexId, c: VertexId) {
+            let a = self.vertices[a.to_usize()];
+            let b = self.vertices[b.to_usize()];
+            let c = self.vertices[c.to_usize()];
+            self.areas.push((self.current_id.unwrap(), (b - a).cross(c - a) * 0.5));
+        }
+    }
+
+    impl BatchGeometryBuilder<Vertex> for PlanarMap {
+        fn set_path_id(&mut self, id: PathId) {
+            // Each path is reported at most once.
+            assert!(self.areas.iter().all(|&(prev_id, _)| prev_id != id));
+            self.current_id = Some(id);
+        }
+    }
+
+    fn rect(builder: &mut crate::path::Builder, x0: f32, y0: f32, x1: f32, y1: f32) {
+        builder.polygon(&[point(x0, y0), point(x1, y0), point(x1, y1), point(x0, y1)]);
+    }
+
+    // The winding order of the triangles is the same as with the regular fill tessellation.
+    let mut builder = Path::builder();
+    rect(&mut builder, 0.0, 0.0, 1.0, 1.0);
+    let mut buffers: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+    FillTessellator::new().tessellate_path(
+        builder.build().iter(),
+        &FillOptions::default(),
+        &mut simple_builder(&mut buffers),
+    ).unwrap();
+    let v = |i: u16| buffers.vertices[i as usize].position;
+    let expected_sign = (v(buffers.indices[1]) - v(buffers.indices[0]))
+        .cross(v(buffers.indices[2]) - v(buffers.indices[0])).signum();
+
+    let mut builder = Path::builder();
+    rect(&mut builder, 0.0, 0.0, 10.0, 10.0);
+    let bottom = builder.build();
+
+    let mut builder = Path::builder();
+    rect(&mut builder, 6.0, 6.0, 14.0, 14.0);
+    let hidden = builder.build();
+
+    let mut builder = Path::builder();
+    builder.polygon(&[point(5.0, 5.0), point(15.0, 5.0), point(15.0, 15.0), point(5.0, 15.0)]);
+    // A hole through which the hidden path is visible.
+    rect(&mut builder, 8.0, 8.0, 12.0, 12.0);
+    let top = builder.build();
+
+    let paths = [
+        (PathId(0), &bottom),
+        (PathId(1), &hidden),
+        (PathId(2), &top),
+    ];
+
+    let mut output = PlanarMap { current_id: None, vertices: Vec::new(), areas: Vec::new() };
+    FillTessellator::new().tessellate_planar_map(
+        paths.iter().map(|&(id, path)| (id, path.iter())),
+        &FillOptions::default(),
+        &mut output,
+    ).unwrap();
+
+    let area = |id| output.areas.iter()
+        .filter(|&&(tri_id, _)| tri_id == id)
+        .map(|&(_, area)| area.abs())
+        .sum::<f32>();
+
+    for &(_, area) in &output.areas {
+        assert_eq!(area.signum(), expected_sign);
+    }
+    assert!((area(PathId(0)) - 75.0).abs() < 0.001);
+    assert!((area(PathId(1)) - 16.0).abs() < 0.001);
+    assert!((area(PathId(2)) - 84.0).abs() < 0.001);
+}
+
+// Lets tests use a simple builder where a batch geometry builder is expected.
+struct BatchBuffers<'l>(SimpleBuffersBuilder<'l, Vertex>);
+
+impl<'l> GeometryBuilder<Vertex> for BatchBuffers<'l> {
+    fn begin_geometry(&mut self) { self.0.begin_geometry() }
+    fn end_geometry(&mut self) -> Count { self.0.end_geometry() }
+    fn abort_geometry(&mut self) { self.0.abort_geometry() }
+    fn add_vertex(&mut self, v: Vertex) -> Result<VertexId, GeometryBuilderError> { self.0.add_vertex(v) }
+    fn add_triangle(&mut self, a: VertexId, b: VertexId, c: VertexId) { self.0.add_triangle(a, b, c) }
+}
+
+impl<'l> BatchGeometryBuilder<Vertex> for BatchBuffers<'l> {
+    fn set_path_id(&mut self, _: PathId) {}
+}
+
+#[test]
+fn test_planar_map_intersecting_edges() {
+    // Two triangles with edges that cross each other.
+    let mut builder = Path::builder();
+    builder.polygon(&[point(0.0, 0.0), point(10.0, 2.0), point(3.0, 10.0)]);
+    let a = builder.build();
+
+    let mut builder = Path::builder();
+    builder.polygon(&[point(8.0, -1.0), point(6.0, 11.0), point(-1.0, 4.0)]);
+    let b = builder.build();
+
+    let mut buffers: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+    let count = FillTessellator::new().tessellate_planar_map(
+        vec![(PathId(0), a.iter()), (PathId(1), b.iter())],
+        &FillOptions::default(),
+        &mut BatchBuffers(simple_builder(&mut buffers)),
+    ).unwrap();
+
+    assert_eq!(count.indices as usize, buffers.indices.len());
+
+    // The union of both triangles is covered without overlap.
+    let area = |points: &[Point]| {
+        let mut area = 0.0;
+        for i in 0..points.len() {
+            area += points[i].to_vector().cross(points[(i + 1) % points.len()].to_vector());
+        }
+        (area * 0.5).abs()
+    };
+    let triangles_area: f32 = buffers.indices.chunks(3).map(|tri| area(&[
+        buffers.vertices[tri[0] as usize].position,
+        buffers.vertices[tri[1] as usize].position,
+        buffers.vertices[tri[2] as usize].position,
+    ])).sum();
+
+    let area_a = area(&[point(0.0, 0.0), point(10.0, 2.0), point(3.0, 10.0)]);
+    let area_b = area(&[point(8.0, -1.0), point(6.0, 11.0), point(-1.0, 4.0)]);
+    assert!(triangles_area > area_b);
+    assert!(triangles_area < area_a + area_b);
+
+    // The visible area is the area of the union of both triangles.
+    let options = FillOptions { fill_rule: FillRule::NonZero, ..FillOptions::default() };
+    let mut union = Path::builder();
+    union.polygon(&[point(0.0, 0.0), point(10.0, 2.0), point(3.0, 10.0)]);
+    union.polygon(&[point(8.0, -1.0), point(6.0, 11.0), point(-1.0, 4.0)]);
+    let union = union.build();
+    let mut buffers: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+    FillTessellator::new().tessellate_planar_map(
+        vec![(PathId(0), union.iter())],
+        &options,
+        &mut BatchBuffers(simple_builder(&mut buffers)),
+    ).unwrap();
+    let union_area: f32 = buffers.indices.chunks(3).map(|tri| area(&[
+        buffers.vertices[tri[0] as usize].position,
+        buffers.vertices[tri[1] as usize].position,
+        buffers.vertices[tri[2] as usize].position,
+    ])).sum();
+
+    assert!((union_area - triangles_area).abs() < 0.01);
+}
+
+#[test]
+fn test_planar_map_options() {
+    let mut builder = Path::builder();
+    builder.polygon(&[point(0.0, 0.0), point(10.0, 0.0), point(10.0, 10.0), point(0.0, 10.0)]);
+    let bottom = builder.build();
+
+    let mut builder = Path::builder();
+    builder.polygon(&[point(5.0, 5.0), point(15.0, 5.0), point(15.0, 15.0), point(5.0, 15.0)]);
+    let top = builder.build();
+
+    let max_edge_length = 2.0;
+    let mut buffers: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+    FillTessellator::new().tessellate_planar_map(
+        vec![(PathId(0), bottom.iter()), (PathId(1), top.iter())],
+        &FillOptions::default().with_normals(true).with_max_edge_length(max_edge_length),
+        &mut BatchBuffers(simple_builder(&mut buffers)),
+    ).unwrap();
+
+    assert!(!buffers.indices.is_empty());
+    for tri in buffers.indices.chunks(3) {
+        for i in 0..3 {
+            let a = buffers.vertices[tri[i] as usize].position;
+            let b = buffers.vertices[tri[(i + 1) % 3] as usize].position;
+            assert!((b - a).length() <= max_edge_length);
+        }
+    }
+
+    // The corners of the visible outlines have normals.
+    for corner in &[point(0.0, 0.0), point(15.0, 15.0), point(10.0, 5.0)] {
+        let vertex = buffers.vertices.iter().find(|v| v.position == *corner).unwrap();
+        assert!(vertex.normal != vector(0.0, 0.0));
+    }
+
+    let clip = rect(2.0, 2.0, 10.0, 10.0);
+    let mut buffers: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+    FillTessellator::new().tessellate_planar_map(
+        vec![(PathId(0), bottom.iter()), (PathId(1), top.iter())],
+        &FillOptions::default().with_clip_rect(clip),
+        &mut BatchBuffers(simple_builder(&mut buffers)),
+    ).unwrap();
+
+    assert!(!buffers.indices.is_empty());
+    for vertex in &buffers.vertices {
+        assert!(clip.min_x() <= vertex.position.x && vertex.position.x <= clip.max_x());
+        assert!(clip.min_y() <= vertex.position.y && vertex.position.y <= clip.max_y());
+    }
+}
+
+#[test]
+fn test_clip_rect() {
+    fn area(buffers: &VertexBuffers<Vertex, u16>) -> f32 {
+        buffers.indices.chunks(3).map(|tri| {
+            let a = buffers.vertices[tri[0] as usize].position;
+            let b = buffers.vertices[tri[1] as usize].position;
+            let c = buffers.vertices[tri[2] as usize].position;
+            (b - a).cross(c - a).abs() * 0.5
+        }).sum()
+    }
+
+    let mut path = Path::builder().with_svg();
+    build_logo_path(&mut path);
+    let path = path.build();
+
+    let options = FillOptions::tolerance(0.05);
+    let mut buffers: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+    FillTessellator::new().tessellate_path(
+        path.iter(),
+        &options,
+        &mut simple_builder(&mut buffers),
+    ).unwrap();
+    let expected_area = area(&buffers);
+
+    let mut min = point(std::f32::MAX, std::f32::MAX);
+    let mut max = point(std::f32::MIN, std::f32::MIN);
+    for vertex in &buffers.vertices {
+        min = min.min(vertex.position);
+        max = max.max(vertex.position);
+    }
+
+    // Split the shape into tiles, some of them exactly on the bounding rectangle.
+    let mut tiles_area = 0.0;
+    let mut tessellator = FillTessellator::new();
+    let tile_size = (max - min) / 3.0;
+    for y in 0..4 {
+        for x in 0..4 {
+            let origin = min + vector(x as f32 * tile_size.x, y as f32 * tile_size.y);
+            let tile = Rect::new(origin, size(tile_size.x, tile_size.y));
+            let mut buffers: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+            tessellator.tessellate_path(
+                path.iter(),
+                &options.with_clip_rect(tile),
+                &mut simple_builder(&mut buffers),
+            ).unwrap();
+
+            for vertex in &buffers.vertices {
+                let p = vertex.position;
+                assert!(p.x >= tile.min_x() - 0.001 && p.x <= tile.max_x() + 0.001);
+                assert!(p.y >= tile.min_y() - 0.001 && p.y <= tile.max_y() + 0.001);
+            }
+            tiles_area += area(&buffers);
+        }
+    }
+
+    assert!((tiles_area - expected_area).abs() < expected_area * 0.001);
+}
+
+#[test]
+fn test_clip_rect_vertex_count() {
+    // A shape with many vertices, most of which are outside of the clip rectangle.
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    for i in 0..1000 {
+        let y = i as f32 * 0.1;
+        builder.line_to(point(if i % 2 == 0 { -10.0 } else { -20.0 }, y));
+    }
+    builder.line_to(point(10.0, 100.0));
+    builder.close();
+    let path = builder.build();
+
+    let mut buffers: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+    FillTessellator::new().tessellate_path(
+        path.iter(),
+        &FillOptions::default().with_clip_rect(rect(-5.0, 0.0, 15.0, 100.0)),
+        &mut simple_builder(&mut buffers),
+    ).unwrap();
+
+    assert!(buffers.vertices.len() < 10);
+
+    let area: f32 = buffers.indices.chunks(3).map(|tri| {
+        let a = buffers.vertices[tri[0] as usize].position;
+        let b = buffers.vertices[tri[1] as usize].position;
+        let c = buffers.vertices[tri[2] as usize].position;
+        (b - a).cross(c - a).abs() * 0.5
+    }).sum();
+
+    // The zigzag is projected on the left side of the rectangle, the clipped shape is
+    // (almost) the trapezoid between x=-5 and the edge from (10, 100) to (0, 0).
+    let expected = 5.0 * 100.0 + 10.0 * 100.0 * 0.5;
+    assert!((area - expected).abs() < 1.0, "{} != {}", area, expected);
+}
+
+#[test]
+fn test_incremental() {
+    let mut path = Path::builder().with_svg();
+    build_logo_path(&mut path);
+    let path = path.build();
+
+    let options = FillOptions::tolerance(0.05);
+
+    let mut expected: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+    FillTessellator::new().tessellate_path(
+        path.iter(),
+        &options,
+        &mut simple_builder(&mut expected),
+    ).unwrap();
+
+    let mut tess = FillTessellator::new();
+    let mut buffers: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+    let mut builder = simple_builder(&mut buffers);
+
+    tess.begin_incremental(path.iter(), &options, &mut builder).unwrap();
+
+    let mut num_steps = 0;
+    let mut num_indices = 0;
+    let count = loop {
+        match tess.resume(FillBudget::Events(10), &mut builder).unwrap() {
+            FillStatus::InProgress => {
+                // The partial geometry grows as the sweep line moves down.
+                let n = builder.buffers().indices.len();
+                assert!(n >= num_indices);
+                num_indices = n;
+                num_steps += 1;
+            }
+            FillStatus::Finished(count) => { break count; }
+        }
+    };
+
+    assert!(num_steps > 10);
+    assert!(num_indices > 0);
+    assert_eq!(count.indices as usize, expected.indices.len());
+    assert_eq!(buffers.indices, expected.indices);
+    assert_eq!(buffers.vertices, expected.vertices);
+
+    // The tessellator can be reused after an incremental tessellation.
+    let mut buffers2: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+    tess.tessellate_path(path.iter(), &options, &mut simple_builder(&mut buffers2)).unwrap();
+    assert_eq!(buffers2.indices, expected.indices);
+}
+
+#[test]
+fn test_incremental_time_budget_and_cancel() {
+    let mut path = Path::builder().with_svg();
+    build_logo_path(&mut path);
+    let path = path.build();
+
+    let options = FillOptions::tolerance(0.05).with_clip_rect(rect(0.0, 0.0, 100.0, 60.0));
+
+    let mut expected: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+    FillTessellator::new().tessellate_path(
+        path.iter(),
+        &options,
+        &mut simple_builder(&mut expected),
+    ).unwrap();
+
+    let mut tess = FillTessellator::new();
+    let mut buffers: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+    {
+        let mut builder = simple_builder(&mut buffers);
+        tess.begin_incremental(path.iter(), &options, &mut builder).unwrap();
+        assert_eq!(tess.resume(FillBudget::Events(1), &mut builder), Ok(FillStatus::InProgress));
+        tess.cancel(&mut builder);
+    }
+    assert!(buffers.vertices.is_empty());
+    assert!(buffers.indices.is_empty());
+
+    let mut builder = simple_builder(&mut buffers);
+    tess.begin_incremental(path.iter(), &options, &mut builder).unwrap();
+    while tess.resume(FillBudget::Time(Duration::from_micros(100)), &mut builder).unwrap() == FillStatus::InProgress {}
+
+    assert_eq!(buffers.indices, expected.indices);
+
+    let unsupported = tess.begin_incremental(
+        path.iter(),
+        &options.with_max_edge_length(1.0),
+        &mut NoOutput::new(),
+    );
+    assert_eq!(unsupported, Err(TessellationError::UnsupportedParamater));
+}
@@ -548,9 +548,7 @@ fn fuzzing_test_case_12() {
     // "M 759.9981 59.831738 L 960.42285 418.38144 L 912.67645 193.0542 L 74.49103 176.2433 L 542.925 579.97253 L 920.04016 75.902466 L 658.5332 792.19904 L 134.72163 905.7226 Z"
 }
 
-#[test]
-#[ignore]
-fn fuzzing_test_case_13() {
+fn fuzzing_test_case_13_path() -> Path {
     let mut builder = Path::builder();
 
     // There are some very close almost horizontal segments somwhere around
@@ -583,12 +581,30 @@ fn fuzzing_test_case_13() {
     builder.line_to(point(303.23447, 681.25366));
     builder.close();
 
-    test_path(builder.build().as_slice());
+    builder.build()
 
     // SVG path syntax:
     // "M 410.68304 821.1684 L 930.137 143.92328 L 104.892136 433.69412 L 660.3361 814.7637 L 677.3176 775.74384 L 1.0851622 766.8102 L 422.32645 774.1579 L 965.11993 775.9433 L 543.46405 972.5189 L 498.56973 739.5371 L 59.104202 990.2475 L 222.88525 571.51117 L 454.01312 816.9873 L 219.92206 961.8081 L 198.50409 103.8456 L 409.76535 863.5788 L 273.72992 489.06696 L 479.42303 773.7393 L 61.974644 866.6973 L 769.39044 347.60333 L 594.88464 818.56824 L 36.028625 811.2928 L 333.66275 314.22592 L 110.678795 817.20044 L 303.23447 681.25366 Z"
 }
 
+#[test]
+#[ignore]
+fn fuzzing_test_case_13() {
+    test_path(fuzzing_test_case_13_path().as_slice());
+}
+
+#[test]
+fn fuzzing_test_case_13_robust() {
+    let mut buffers: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+    FillTessellator::new().tessellate_path(
+        &fuzzing_test_case_13_path(),
+        &FillOptions::tolerance(0.05).with_robust_mode(true),
+        &mut simple_builder(&mut buffers),
+    ).unwrap();
+
+    assert!(!buffers.indices.is_empty());
+}
+
 #[test]
 fn fuzzing_test_case_14() {
     let mut builder = Path::builder();
@@ -759,3 +775,129 @@ fn fuzzing_test_case_20() {
     // "M 300.44553 -951.7151 L -311.18967 952.4652 L -694.0007 725.4894 L 683.2565 -724.7392 L -559.072 -832.3412 L 548.32776 833.09143 L 132.19205 990.868 L -142.93622 -990.1178 Z"
 }
 
+
+#[test]
+fn robust_random_paths() {
+    // A simple deterministic generator, to avoid depending on a random crate.
+    let mut seed: u32 = 1;
+    let mut random = || {
+        seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+        (seed >> 16) as f32 / 65536.0
+    };
+
+    let mut tess = FillTessellator::new();
+    for i in 0..300 {
+        let mut builder = Path::builder();
+        let num_points = 3 + i % 30;
+        for j in 0..num_points {
+            // Some of the paths are on a coarse grid to produce many collinear
+            // and overlapping edges.
+            let (x, y) = if i % 3 == 0 {
+                ((random() * 10.0).floor() * 100.0, (random() * 10.0).floor() * 100.0)
+            } else {
+                (random() * 1000.0, random() * 1000.0)
+            };
+            if j == 0 {
+                builder.move_to(point(x, y));
+            } else {
+                builder.line_to(point(x, y));
+            }
+        }
+        builder.close();
+        let path = builder.build();
+
+        let mut buffers: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+        let result = tess.tessellate_path(
+            &path,
+            &FillOptions::tolerance(0.05).with_robust_mode(true),
+            &mut simple_builder(&mut buffers),
+        );
+
+        assert!(result.is_ok(), "{:?}", path);
+        for &idx in &buffers.indices {
+            assert!((idx as usize) < buffers.vertices.len());
+        }
+    }
+}
+
+#[test]
+fn robust_random_paths_on_the_grid_scale() {
+    // At this scale the snapped intersections move the edges a lot relative to their
+    // length, and many edges cross each other close to the same grid cells.
+    let mut seed: u32 = 1;
+    let mut random = || {
+        seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+        (seed >> 16) as f32 / 65536.0
+    };
+
+    let mut tess = FillTessellator::new();
+    for i in 0..500 {
+        let mut builder = Path::builder();
+        let num_points = 3 + i % 40;
+        for j in 0..num_points {
+            let (x, y) = (random() * 0.2, random() * 0.2);
+            if j == 0 {
+                builder.move_to(point(x, y));
+            } else {
+                builder.line_to(point(x, y));
+            }
+            if random() < 0.1 {
+                builder.close();
+            }
+        }
+        builder.close();
+        let path = builder.build();
+
+        let mut buffers: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+        let result = tess.tessellate_path(
+            &path,
+            &FillOptions::tolerance(0.05).with_robust_mode(true),
+            &mut simple_builder(&mut buffers),
+        );
+
+        assert!(result.is_ok(), "{:?}", path);
+    }
+}
+
+#[test]
+fn robust_snapped_intersections() {
+    // Coordinates in 1/256th of a unit, the size of the grid of the robust mode.
+    fn grid(x: i32, y: i32) -> Point {
+        point(x as f32 / 256.0, y as f32 / 256.0)
+    }
+
+    let cases: &[&[&[(i32, i32)]]] = &[
+        // Splitting the edges at the snapped intersections used to create new
+        // intersections.
+        &[
+            &[(32, 46), (18, 5), (19, 45), (37, 43)],
+            &[(30, 12), (42, 9), (31, 47)],
+        ],
+        // A split event right below a merge vertex, with four edges below.
+        &[
+            &[(2, 39), (15, 3), (32, 18)],
+            &[(10, 16), (40, 36), (4, 4)],
+            &[(12, 12), (2, 16), (37, 48)],
+        ],
+    ];
+
+    let mut tess = FillTessellator::new();
+    for polygons in cases {
+        let mut builder = Path::builder();
+        for polygon in polygons.iter() {
+            builder.move_to(grid(polygon[0].0, polygon[0].1));
+            for &(x, y) in &polygon[1..] {
+                builder.line_to(grid(x, y));
+            }
+            builder.close();
+        }
+        let path = builder.build();
+
+        let mut buffers: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+        tess.tessellate_path(
+            &path,
+            &FillOptions::tolerance(0.05).with_robust_mode(true),
+            &mut simple_builder(&mut buffers),
+        ).unwrap();
+    }
+}
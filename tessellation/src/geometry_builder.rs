@@ -26,6 +26,12 @@
 //! * The struct [`BuffersBuilder`](struct.BuffersBuilder.html) which implements
 //!   [`BezierGeometryBuilder`](trait.BezierGeometryBuilder.html) and writes into a
 //!   [`VertexBuffers`](struct.VertexBuffers.html).
+//! * The struct [`ChunkedBuffersBuilder`](struct.ChunkedBuffersBuilder.html) which writes
+//!   into a list of [`VertexBuffers`](struct.VertexBuffers.html) with `u16` indices, starting
+//!   a new one whenever the index range would overflow.
+//! * The struct [`ByteBuffersBuilder`](struct.ByteBuffersBuilder.html) which writes vertices
+//!   and indices directly into byte slices (for example mapped GPU memory), following a
+//!   [`VertexLayout`](struct.VertexLayout.html).
 //! * The trait [`VertexConstructor`](trait.VertexConstructor.html) used by
 //!   [`BuffersBuilder`](struct.BuffersBuilder.html) in order to generate any vertex type. In the
 //!   example below, a struct `WithColor` implements the `VertexConstructor` trait in order to
@@ -190,7 +196,7 @@
 //!
 //! ```
 //! use lyon_tessellation::geometry_builder::*;
-//! use lyon_tessellation::{FillVertex, TessellationResult};
+//! use lyon_tessellation::{FillVertex, VertexSource, TessellationResult};
 //! use lyon_tessellation::math::{Rect, vector, point};
 //!
 //! // A tessellator that generates an axis-aligned quad.
@@ -204,18 +210,26 @@
 //!     // Create the vertices...
 //!     let min = rect.min();
 //!     let max = rect.min();
-//!     let a = output.add_vertex(
-//!         FillVertex { position: min, normal: vector(-1.0, -1.0) }
-//!     )?;
-//!     let b = output.add_vertex(
-//!         FillVertex { position: point(max.x, min.y), normal: vector(1.0, -1.0) }
-//!     )?;
-//!     let c = output.add_vertex(
-//!         FillVertex { position: max, normal: vector(1.0, 1.0) }
-//!     )?;
-//!     let d = output.add_vertex(
-//!         FillVertex { position: point(min.x, max.y), normal: vector(-1.0, 1.0) }
-//!     )?;
+//!     let a = output.add_vertex(FillVertex {
+//!         position: min,
+//!         normal: vector(-1.0, -1.0),
+//!         source: VertexSource::Unknown,
+//!     })?;
+//!     let b = output.add_vertex(FillVertex {
+//!         position: point(max.x, min.y),
+//!         normal: vector(1.0, -1.0),
+//!         source: VertexSource::Unknown,
+//!     })?;
+//!     let c = output.add_vertex(FillVertex {
+//!         position: max,
+//!         normal: vector(1.0, 1.0),
+//!         source: VertexSource::Unknown,
+//!     })?;
+//!     let d = output.add_vertex(FillVertex {
+//!         position: point(min.x, max.y),
+//!         normal: vector(-1.0, 1.0),
+//!         source: VertexSource::Unknown,
+//!     })?;
 //!     // ...and create triangle form these points. a, b, c, and d are relative offsets in the
 //!     // vertex buffer.
 //!     output.add_triangle(a, b, c);
@@ -226,6 +240,9 @@
 //! ```
 
 pub use crate::path::{VertexId, Index};
+use crate::PathId;
+use crate::{FillVertex, StrokeVertex};
+use crate::math::{Point, Vector, vector};
 
 use std::marker::PhantomData;
 use std::ops::Add;
@@ -288,6 +305,47 @@ pub trait GeometryReceiver<Vertex> {
     );
 }
 
+/// An extension of `GeometryBuilder` for algorithms that can produce polygons
+/// with an arbitrary number of vertices instead of triangles.
+///
+/// See [`FillTessellator::tessellate_path_to_polygons`](../struct.FillTessellator.html#method.tessellate_path_to_polygons).
+pub trait PolygonGeometryBuilder<Input>: GeometryBuilder<Input> {
+    /// Insert a polygon made of vertices that were added after the last call to begin_geometry.
+    ///
+    /// The vertices are provided in the same winding order as the triangles passed to
+    /// `add_triangle`.
+    ///
+    /// This method can only be called between begin_geometry and end_geometry.
+    fn add_polygon(&mut self, vertices: &[VertexId]);
+}
+
+/// An extension of `GeometryBuilder` for algorithms that produce the geometry of
+/// several paths at once.
+///
+/// See [`FillTessellator::tessellate_batch`](../struct.FillTessellator.html#method.tessellate_batch)
+/// and [`FillTessellator::tessellate_planar_map`](../struct.FillTessellator.html#method.tessellate_planar_map).
+pub trait BatchGeometryBuilder<Input>: GeometryBuilder<Input> {
+    /// Called before the vertices and triangles of each path are added.
+    ///
+    /// All vertices and triangles added until the next call to `set_path_id`
+    /// belong to the path `id`.
+    ///
+    /// This method can only be called between begin_geometry and end_geometry.
+    fn set_path_id(&mut self, id: PathId);
+}
+
+/// An extension of `GeometryBuilder` for algorithms that produce line segments
+/// instead of triangles, for example to render them as a list of line primitives.
+///
+/// See [`StrokeTessellator::tessellate_path_hairline`](../struct.StrokeTessellator.html#method.tessellate_path_hairline).
+pub trait LineGeometryBuilder<Input>: GeometryBuilder<Input> {
+    /// Insert a line segment made of vertices that were added after the last call to
+    /// begin_geometry.
+    ///
+    /// This method can only be called between begin_geometry and end_geometry.
+    fn add_line(&mut self, a: VertexId, b: VertexId);
+}
+
 /// Structure that holds the vertex and index data.
 ///
 /// Usually written into though temporary `BuffersBuilder` objects.
@@ -457,6 +515,19 @@ where
     }
 }
 
+impl<'l, VertexType, IndexType, Input, Ctor> LineGeometryBuilder<Input>
+    for BuffersBuilder<'l, VertexType, IndexType, Input, Ctor>
+where
+    VertexType: 'l + Clone,
+    IndexType: Add + From<VertexId> + MaxIndex,
+    Ctor: VertexConstructor<Input, VertexType>,
+{
+    fn add_line(&mut self, a: VertexId, b: VertexId) {
+        self.buffers.indices.push((a + self.vertex_offset).into());
+        self.buffers.indices.push((b + self.vertex_offset).into());
+    }
+}
+
 impl<'l, VertexType, IndexType, InputVertex, Ctor> GeometryReceiver<InputVertex>
     for BuffersBuilder<'l, VertexType, IndexType, InputVertex, Ctor>
 where
@@ -480,6 +551,425 @@ where
     }
 }
 
+/// A geometry builder that writes into a list of `VertexBuffers` with `u16` indices,
+/// starting a new chunk when the current one can't fit more vertices.
+///
+/// This allows generating meshes of any size on platforms that only support 16 bits
+/// indices. The vertices that are used by triangles in several chunks are duplicated,
+/// and vertices that aren't used by any triangle are not added to the chunks.
+///
+/// The geometry is appended to the last chunk of the list, if any.
+pub struct ChunkedBuffersBuilder<'l, VertexType: 'l, Input, Ctor> {
+    chunks: &'l mut Vec<VertexBuffers<VertexType, u16>>,
+    vertex_constructor: Ctor,
+    max_vertices: usize,
+    // All of the vertices added since begin_geometry, indexed by VertexId.
+    vertices: Vec<VertexType>,
+    // The index of each vertex in the current chunk, or u16::MAX.
+    chunk_indices: Vec<u16>,
+    // The state of the chunks at the beginning of the geometry.
+    num_chunks: usize,
+    vertex_offset: usize,
+    index_offset: usize,
+    _marker: PhantomData<Input>,
+}
+
+impl<'l, VertexType: 'l, Input, Ctor> ChunkedBuffersBuilder<'l, VertexType, Input, Ctor> {
+    pub fn new(
+        chunks: &'l mut Vec<VertexBuffers<VertexType, u16>>,
+        ctor: Ctor,
+    ) -> Self {
+        ChunkedBuffersBuilder {
+            chunks,
+            vertex_constructor: ctor,
+            max_vertices: u16::max_index(),
+            vertices: Vec::new(),
+            chunk_indices: Vec::new(),
+            num_chunks: 0,
+            vertex_offset: 0,
+            index_offset: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Sets the maximum number of vertices per chunk.
+    ///
+    /// The default (and maximum) value is `u16::MAX`.
+    pub fn with_max_vertices(mut self, max_vertices: usize) -> Self {
+        assert!(max_vertices >= 3);
+        self.max_vertices = max_vertices.min(u16::max_index());
+        self
+    }
+
+    pub fn chunks<'a, 'b: 'a>(&'b self) -> &'a [VertexBuffers<VertexType, u16>] {
+        self.chunks
+    }
+
+    fn start_chunk(&mut self) {
+        self.chunks.push(VertexBuffers::new());
+        for idx in &mut self.chunk_indices {
+            *idx = std::u16::MAX;
+        }
+    }
+}
+
+impl<'l, VertexType, Input, Ctor> GeometryBuilder<Input>
+    for ChunkedBuffersBuilder<'l, VertexType, Input, Ctor>
+where
+    VertexType: 'l + Clone,
+    Ctor: VertexConstructor<Input, VertexType>,
+{
+    fn begin_geometry(&mut self) {
+        self.vertices.clear();
+        self.chunk_indices.clear();
+        self.num_chunks = self.chunks.len();
+        if self.chunks.is_empty() {
+            self.chunks.push(VertexBuffers::new());
+        }
+        let chunk = self.chunks.last().unwrap();
+        self.vertex_offset = chunk.vertices.len();
+        self.index_offset = chunk.indices.len();
+    }
+
+    fn end_geometry(&mut self) -> Count {
+        let first_chunk = self.num_chunks.max(1) - 1;
+        let mut count = Count { vertices: 0, indices: 0 };
+        for chunk in &self.chunks[first_chunk..] {
+            count.vertices += chunk.vertices.len() as u32;
+            count.indices += chunk.indices.len() as u32;
+        }
+        count.vertices -= self.vertex_offset as u32;
+        count.indices -= self.index_offset as u32;
+
+        count
+    }
+
+    fn add_vertex(&mut self, v: Input) -> Result<VertexId, GeometryBuilderError> {
+        if self.vertices.len() >= std::u32::MAX as usize {
+            return Err(GeometryBuilderError::TooManyVertices);
+        }
+        self.vertices.push(self.vertex_constructor.new_vertex(v));
+        self.chunk_indices.push(std::u16::MAX);
+
+        Ok(VertexId(self.vertices.len() as Index - 1))
+    }
+
+    fn add_triangle(&mut self, a: VertexId, b: VertexId, c: VertexId) {
+        let ids = [a.to_usize(), b.to_usize(), c.to_usize()];
+
+        let mut missing = 0;
+        for (i, &id) in ids.iter().enumerate() {
+            if self.chunk_indices[id] == std::u16::MAX && !ids[..i].contains(&id) {
+                missing += 1;
+            }
+        }
+
+        if self.chunks.last().unwrap().vertices.len() + missing > self.max_vertices {
+            self.start_chunk();
+        }
+
+        let chunk = self.chunks.last_mut().unwrap();
+        for &id in &ids {
+            if self.chunk_indices[id] == std::u16::MAX {
+                self.chunk_indices[id] = chunk.vertices.len() as u16;
+                chunk.vertices.push(self.vertices[id].clone());
+            }
+            chunk.indices.push(self.chunk_indices[id]);
+        }
+    }
+
+    fn abort_geometry(&mut self) {
+        if self.num_chunks == 0 {
+            self.chunks.clear();
+            return;
+        }
+
+        self.chunks.truncate(self.num_chunks);
+        let chunk = self.chunks.last_mut().unwrap();
+        chunk.vertices.truncate(self.vertex_offset);
+        chunk.indices.truncate(self.index_offset);
+    }
+}
+
+/// Vertices that can be written into a byte buffer by a
+/// [`ByteBuffersBuilder`](struct.ByteBuffersBuilder.html).
+pub trait LayoutVertex {
+    fn position(&self) -> Point;
+    fn normal(&self) -> Vector { vector(0.0, 0.0) }
+    fn advancement(&self) -> f32 { 0.0 }
+    /// `-1.0` for the left side and `1.0` for the right side, if applicable.
+    fn side(&self) -> f32 { 0.0 }
+}
+
+impl LayoutVertex for Point {
+    fn position(&self) -> Point { *self }
+}
+
+impl LayoutVertex for FillVertex {
+    fn position(&self) -> Point { self.position }
+    fn normal(&self) -> Vector { self.normal }
+}
+
+impl LayoutVertex for StrokeVertex {
+    fn position(&self) -> Point { self.position }
+    fn normal(&self) -> Vector { self.normal }
+    fn advancement(&self) -> f32 { self.advancement }
+    fn side(&self) -> f32 { if self.side.is_left() { -1.0 } else { 1.0 } }
+}
+
+/// The vertex attributes that a `ByteBuffersBuilder` can write.
+///
+/// All values are written as native endian `f32`s.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub enum VertexAttribute {
+    /// Two floats.
+    Position,
+    /// Two floats.
+    Normal,
+    /// One float.
+    Advancement,
+    /// One float, see `LayoutVertex::side`.
+    Side,
+}
+
+impl VertexAttribute {
+    /// The size of the attribute in bytes.
+    pub fn size(self) -> usize {
+        match self {
+            VertexAttribute::Position | VertexAttribute::Normal => 8,
+            VertexAttribute::Advancement | VertexAttribute::Side => 4,
+        }
+    }
+}
+
+/// Describes how vertices are laid out in a byte buffer.
+///
+/// Each vertex occupies `stride` bytes. The bytes that aren't covered by an attribute
+/// are left untouched, or can be written by the custom attribute callback of the
+/// `ByteBuffersBuilder`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct VertexLayout {
+    pub stride: usize,
+    /// Attributes and their offset in bytes from the start of the vertex.
+    pub attributes: Vec<(VertexAttribute, usize)>,
+}
+
+impl VertexLayout {
+    pub fn new(stride: usize) -> Self {
+        VertexLayout { stride, attributes: Vec::new() }
+    }
+
+    pub fn with_attribute(mut self, attribute: VertexAttribute, offset: usize) -> Self {
+        assert!(offset + attribute.size() <= self.stride);
+        self.attributes.push((attribute, offset));
+        self
+    }
+}
+
+/// The type of the indices written by a `ByteBuffersBuilder`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub enum IndexFormat {
+    U16,
+    U32,
+}
+
+impl IndexFormat {
+    /// The size of an index in bytes.
+    pub fn size(self) -> usize {
+        match self {
+            IndexFormat::U16 => 2,
+            IndexFormat::U32 => 4,
+        }
+    }
+}
+
+/// Which of the buffers of a `ByteBuffersBuilder` ran out of space.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BufferOverflow {
+    Vertices,
+    Indices,
+}
+
+type CustomAttributes<'l, Input> = Box<dyn FnMut(&Input, &mut [u8]) + 'l>;
+
+/// A geometry builder that writes vertices and indices directly into byte buffers,
+/// for example mapped GPU memory.
+///
+/// The vertices are written according to a `VertexLayout`, and custom attributes
+/// can be written with a callback (see `with_custom_attributes`). Indices are
+/// written as native endian `u16` or `u32`.
+///
+/// When one of the buffers is full, `add_vertex` fails with
+/// `GeometryBuilderError::TooManyVertices` (which interrupts the tessellation) and
+/// `overflow` tells which buffer ran out of space, so that the caller can grow or flush
+/// the buffers and start again. If the index buffer fills up after the last vertex of the
+/// geometry was added, the geometry is discarded in `end_geometry`, which returns an empty
+/// `Count`. In both cases the buffers are left as they were before the geometry. Like
+/// with `BuffersBuilder`, several geometries can be written one after the other in the
+/// same buffers.
+pub struct ByteBuffersBuilder<'l, Input> {
+    vertices: &'l mut [u8],
+    indices: &'l mut [u8],
+    layout: VertexLayout,
+    index_format: IndexFormat,
+    custom_attributes: Option<CustomAttributes<'l, Input>>,
+    num_vertices: usize,
+    num_indices: usize,
+    vertex_offset: usize,
+    index_offset: usize,
+    overflow: Option<BufferOverflow>,
+}
+
+impl<'l, Input> ByteBuffersBuilder<'l, Input> {
+    pub fn new(
+        vertices: &'l mut [u8],
+        indices: &'l mut [u8],
+        layout: VertexLayout,
+        index_format: IndexFormat,
+    ) -> Self {
+        ByteBuffersBuilder {
+            vertices,
+            indices,
+            layout,
+            index_format,
+            custom_attributes: None,
+            num_vertices: 0,
+            num_indices: 0,
+            vertex_offset: 0,
+            index_offset: 0,
+            overflow: None,
+        }
+    }
+
+    /// Sets a callback that is invoked for each vertex with the bytes of the vertex
+    /// (`stride` bytes), after the attributes of the layout are written.
+    pub fn with_custom_attributes<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(&Input, &mut [u8]) + 'l
+    {
+        self.custom_attributes = Some(Box::new(callback));
+        self
+    }
+
+    /// The number of vertices written so far.
+    pub fn num_vertices(&self) -> usize { self.num_vertices }
+
+    /// The number of indices written so far.
+    pub fn num_indices(&self) -> usize { self.num_indices }
+
+    /// The buffer that ran out of space during the current geometry, if any.
+    pub fn overflow(&self) -> Option<BufferOverflow> { self.overflow }
+}
+
+fn write_f32(dst: &mut [u8], offset: usize, val: f32) {
+    dst[offset..offset + 4].copy_from_slice(&val.to_bits().to_ne_bytes());
+}
+
+impl<'l, Input: LayoutVertex> GeometryBuilder<Input> for ByteBuffersBuilder<'l, Input> {
+    fn begin_geometry(&mut self) {
+        self.vertex_offset = self.num_vertices;
+        self.index_offset = self.num_indices;
+        self.overflow = None;
+    }
+
+    fn end_geometry(&mut self) -> Count {
+        if self.overflow.is_some() {
+            // Some triangles were dropped, don't keep a partial geometry.
+            self.num_vertices = self.vertex_offset;
+            self.num_indices = self.index_offset;
+        }
+
+        Count {
+            vertices: (self.num_vertices - self.vertex_offset) as u32,
+            indices: (self.num_indices - self.index_offset) as u32,
+        }
+    }
+
+    fn add_vertex(&mut self, v: Input) -> Result<VertexId, GeometryBuilderError> {
+        // Also stop the tessellation if the index buffer is full.
+        if self.overflow.is_some() {
+            return Err(GeometryBuilderError::TooManyVertices);
+        }
+
+        let max_index = match self.index_format {
+            IndexFormat::U16 => u16::max_index(),
+            IndexFormat::U32 => u32::max_index(),
+        };
+        if self.num_vertices >= max_index {
+            return Err(GeometryBuilderError::TooManyVertices);
+        }
+
+        let stride = self.layout.stride;
+        let start = self.num_vertices * stride;
+        if start + stride > self.vertices.len() {
+            self.overflow = Some(BufferOverflow::Vertices);
+            return Err(GeometryBuilderError::TooManyVertices);
+        }
+
+        let dst = &mut self.vertices[start..start + stride];
+        for &(attribute, offset) in &self.layout.attributes {
+            match attribute {
+                VertexAttribute::Position => {
+                    let p = v.position();
+                    write_f32(dst, offset, p.x);
+                    write_f32(dst, offset + 4, p.y);
+                }
+                VertexAttribute::Normal => {
+                    let n = v.normal();
+                    write_f32(dst, offset, n.x);
+                    write_f32(dst, offset + 4, n.y);
+                }
+                VertexAttribute::Advancement => {
+                    write_f32(dst, offset, v.advancement());
+                }
+                VertexAttribute::Side => {
+                    write_f32(dst, offset, v.side());
+                }
+            }
+        }
+
+        if let Some(callback) = &mut self.custom_attributes {
+            callback(&v, dst);
+        }
+
+        self.num_vertices += 1;
+
+        Ok(VertexId((self.num_vertices - 1 - self.vertex_offset) as Index))
+    }
+
+    fn add_triangle(&mut self, a: VertexId, b: VertexId, c: VertexId) {
+        let size = self.index_format.size();
+        let start = self.num_indices * size;
+        if self.overflow.is_some() || start + size * 3 > self.indices.len() {
+            self.overflow = Some(self.overflow.unwrap_or(BufferOverflow::Indices));
+            return;
+        }
+
+        for (i, id) in [a, b, c].iter().enumerate() {
+            let index = id.to_usize() + self.vertex_offset;
+            let offset = start + i * size;
+            match self.index_format {
+                IndexFormat::U16 => {
+                    self.indices[offset..offset + 2].copy_from_slice(&(index as u16).to_ne_bytes());
+                }
+                IndexFormat::U32 => {
+                    self.indices[offset..offset + 4].copy_from_slice(&(index as u32).to_ne_bytes());
+                }
+            }
+        }
+
+        self.num_indices += 3;
+    }
+
+    fn abort_geometry(&mut self) {
+        self.num_vertices = self.vertex_offset;
+        self.num_indices = self.index_offset;
+    }
+}
+
 /// A geometry builder that does not output any geometry.
 ///
 /// Mostly useful for testing.
@@ -519,6 +1009,24 @@ impl<T> GeometryBuilder<T> for NoOutput
     fn abort_geometry(&mut self) {}
 }
 
+impl<T> PolygonGeometryBuilder<T> for NoOutput {
+    fn add_polygon(&mut self, vertices: &[VertexId]) {
+        debug_assert!(vertices.len() >= 3);
+        self.count.indices += vertices.len() as u32;
+    }
+}
+
+impl<T> BatchGeometryBuilder<T> for NoOutput {
+    fn set_path_id(&mut self, _id: PathId) {}
+}
+
+impl<T> LineGeometryBuilder<T> for NoOutput {
+    fn add_line(&mut self, a: VertexId, b: VertexId) {
+        debug_assert!(a != b);
+        self.count.indices += 2;
+    }
+}
+
 impl<V> GeometryReceiver<V> for NoOutput {
     fn set_geometry(&mut self, _vertices: &[V], _indices: &[u32]) {}
 }
@@ -692,3 +1200,201 @@ fn test_closure() {
         point(1.0, 1.0),
     ]);
 }
+
+#[test]
+fn test_chunked_builder() {
+    use crate::{FillTessellator, FillOptions, FillVertex, OnError};
+    use crate::math::point;
+    use crate::path::Path;
+
+    // A polygon with more vertices than fit in a u16 index buffer.
+    let n = 70000;
+    let mut points = Vec::with_capacity(n);
+    for i in 0..n {
+        let angle = i as f32 * std::f32::consts::PI * 2.0 / n as f32;
+        points.push(point(angle.cos() * 1000.0, angle.sin() * 1000.0));
+    }
+    let mut builder = Path::builder();
+    builder.polygon(&points);
+    let path = builder.build();
+
+    let mut tess = FillTessellator::new();
+
+    let mut buffers: VertexBuffers<FillVertex, u16> = VertexBuffers::new();
+    assert!(tess.tessellate_path(
+        path.iter(),
+        &FillOptions::default().on_error(OnError::Stop),
+        &mut BuffersBuilder::new(&mut buffers, Identity),
+    ).is_err());
+
+    let mut expected: VertexBuffers<FillVertex, u32> = VertexBuffers::new();
+    tess.tessellate_path(
+        path.iter(),
+        &FillOptions::default(),
+        &mut BuffersBuilder::new(&mut expected, Identity),
+    ).unwrap();
+
+    let mut chunks: Vec<VertexBuffers<FillVertex, u16>> = Vec::new();
+    let count = tess.tessellate_path(
+        path.iter(),
+        &FillOptions::default(),
+        &mut ChunkedBuffersBuilder::new(&mut chunks, Identity),
+    ).unwrap();
+
+    assert!(chunks.len() >= 2);
+    let num_indices: usize = chunks.iter().map(|chunk| chunk.indices.len()).sum();
+    let num_vertices: usize = chunks.iter().map(|chunk| chunk.vertices.len()).sum();
+    assert_eq!(num_indices, expected.indices.len());
+    assert_eq!(count.indices as usize, num_indices);
+    assert_eq!(count.vertices as usize, num_vertices);
+
+    // The chunks contain the same triangles, in the same order.
+    let mut expected_triangles = expected.indices.iter().map(|&i| expected.vertices[i as usize].position);
+    for chunk in &chunks {
+        assert!(chunk.vertices.len() <= std::u16::MAX as usize);
+        for &i in &chunk.indices {
+            assert_eq!(Some(chunk.vertices[i as usize].position), expected_triangles.next());
+        }
+    }
+}
+
+#[test]
+fn test_chunked_builder_abort() {
+    use crate::math::{Point, point};
+
+    let mut chunks: Vec<VertexBuffers<Point, u16>> = Vec::new();
+    let mut builder = ChunkedBuffersBuilder::new(&mut chunks, Identity).with_max_vertices(4);
+
+    builder.begin_geometry();
+    let a = builder.add_vertex(point(0.0, 0.0)).unwrap();
+    let b = builder.add_vertex(point(1.0, 0.0)).unwrap();
+    let c = builder.add_vertex(point(1.0, 1.0)).unwrap();
+    let d = builder.add_vertex(point(0.0, 1.0)).unwrap();
+    builder.add_triangle(a, b, c);
+    builder.add_triangle(a, c, d);
+    let count = builder.end_geometry();
+    assert_eq!(count, Count { vertices: 4, indices: 6 });
+
+    builder.begin_geometry();
+    let e = builder.add_vertex(point(2.0, 0.0)).unwrap();
+    let f = builder.add_vertex(point(3.0, 0.0)).unwrap();
+    let g = builder.add_vertex(point(3.0, 1.0)).unwrap();
+    builder.add_triangle(e, f, g);
+    builder.add_triangle(e, g, f);
+    assert_eq!(builder.chunks().len(), 2);
+    builder.abort_geometry();
+
+    assert_eq!(chunks.len(), 1);
+    assert_eq!(chunks[0].vertices.len(), 4);
+    assert_eq!(chunks[0].indices, vec![0, 1, 2, 0, 2, 3]);
+}
+
+#[cfg(test)]
+fn read_f32(src: &[u8], offset: usize) -> f32 {
+    let mut bytes = [0; 4];
+    bytes.copy_from_slice(&src[offset..offset + 4]);
+    f32::from_bits(u32::from_ne_bytes(bytes))
+}
+
+#[test]
+fn test_byte_buffers_builder() {
+    use crate::{StrokeTessellator, StrokeOptions};
+    use crate::math::point;
+    use crate::path::Path;
+
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(10.0, 0.0));
+    builder.line_to(point(10.0, 10.0));
+    let path = builder.build();
+
+    let mut expected: VertexBuffers<StrokeVertex, u16> = VertexBuffers::new();
+    StrokeTessellator::new().tessellate_path(
+        path.iter(),
+        &StrokeOptions::default(),
+        &mut BuffersBuilder::new(&mut expected, Identity),
+    ).unwrap();
+
+    // position, padding, side, advancement, custom u32, normal.
+    let layout = VertexLayout::new(32)
+        .with_attribute(VertexAttribute::Position, 0)
+        .with_attribute(VertexAttribute::Side, 12)
+        .with_attribute(VertexAttribute::Advancement, 8)
+        .with_attribute(VertexAttribute::Normal, 20);
+
+    let mut vertices = vec![0u8; 32 * 100];
+    let mut indices = vec![0u8; 2 * 100];
+    let count = {
+        let mut output = ByteBuffersBuilder::new(&mut vertices, &mut indices, layout, IndexFormat::U16)
+            .with_custom_attributes(|_: &StrokeVertex, dst: &mut [u8]| {
+                dst[16..20].copy_from_slice(&0xdeadbeef_u32.to_ne_bytes());
+            });
+        let count = StrokeTessellator::new().tessellate_path(
+            path.iter(),
+            &StrokeOptions::default(),
+            &mut output,
+        ).unwrap();
+        assert_eq!(output.overflow(), None);
+        count
+    };
+
+    assert_eq!(count.vertices as usize, expected.vertices.len());
+    assert_eq!(count.indices as usize, expected.indices.len());
+
+    for (i, v) in expected.vertices.iter().enumerate() {
+        let bytes = &vertices[i * 32..(i + 1) * 32];
+        assert_eq!(read_f32(bytes, 0), v.position.x);
+        assert_eq!(read_f32(bytes, 4), v.position.y);
+        assert_eq!(read_f32(bytes, 8), v.advancement);
+        assert_eq!(read_f32(bytes, 12), if v.side.is_left() { -1.0 } else { 1.0 });
+        assert_eq!(&bytes[16..20], &0xdeadbeef_u32.to_ne_bytes());
+        assert_eq!(read_f32(bytes, 20), v.normal.x);
+        assert_eq!(read_f32(bytes, 24), v.normal.y);
+    }
+
+    for (i, idx) in expected.indices.iter().enumerate() {
+        assert_eq!(&indices[i * 2..i * 2 + 2], &idx.to_ne_bytes());
+    }
+}
+
+#[test]
+fn test_byte_buffers_builder_overflow() {
+    use crate::{FillTessellator, FillOptions, OnError, TessellationError};
+    use crate::math::point;
+    use crate::path::Path;
+
+    let mut builder = Path::builder();
+    builder.polygon(&[point(0.0, 0.0), point(10.0, 0.0), point(10.0, 10.0), point(0.0, 10.0), point(-5.0, 5.0)]);
+    let path = builder.build();
+    let options = FillOptions::default().on_error(OnError::Stop);
+    let layout = VertexLayout::new(8).with_attribute(VertexAttribute::Position, 0);
+
+    let mut vertices = vec![0u8; 8 * 4];
+    let mut indices = vec![0u8; 4 * 100];
+    let mut output = ByteBuffersBuilder::new(&mut vertices, &mut indices, layout.clone(), IndexFormat::U32);
+    let result = FillTessellator::new().tessellate_path(path.iter(), &options, &mut output);
+    assert_eq!(result, Err(TessellationError::TooManyVertices));
+    assert_eq!(output.overflow(), Some(BufferOverflow::Vertices));
+    assert_eq!(output.num_vertices(), 0);
+    assert_eq!(output.num_indices(), 0);
+
+    // The index buffer has room for a triangle and two of the three triangles of
+    // the polygon.
+    let mut triangle = Path::builder();
+    triangle.polygon(&[point(0.0, 0.0), point(10.0, 0.0), point(10.0, 10.0)]);
+    let triangle = triangle.build();
+
+    let mut vertices = vec![0u8; 8 * 100];
+    let mut indices = vec![0u8; 4 * 11];
+    let mut output = ByteBuffersBuilder::new(&mut vertices, &mut indices, layout, IndexFormat::U32);
+    let mut tess = FillTessellator::new();
+    let result = tess.tessellate_path(triangle.iter(), &options, &mut output);
+    assert_eq!(result, Ok(Count { vertices: 3, indices: 3 }));
+
+    // The polygon is discarded and the triangle is preserved.
+    let result = tess.tessellate_path(path.iter(), &options, &mut output);
+    assert_eq!(result, Ok(Count { vertices: 0, indices: 0 }));
+    assert_eq!(output.overflow(), Some(BufferOverflow::Indices));
+    assert_eq!(output.num_vertices(), 3);
+    assert_eq!(output.num_indices(), 3);
+}
@@ -0,0 +1,259 @@
+//! Hairline strokes.
+//!
+//! A hairline is a stroke that is always drawn one pixel wide regardless of the
+//! transformation, for example in wireframes and CAD drawings. Instead of triangles,
+//! the flattened path is produced as a list of line segments that can be rendered with
+//! line primitives.
+
+use crate::geometry_builder::{LineGeometryBuilder, GeometryBuilderError, VertexId};
+use crate::math::{Point, vector};
+use crate::path::PathEvent;
+use crate::{StrokeTessellator, StrokeOptions, StrokeVertex, Side, TessellationResult, TessellationError};
+use crate::{VertexSource, EventPosition};
+
+impl StrokeTessellator {
+    /// Compute the hairline of a path as a list of line segments.
+    ///
+    /// Only the `tolerance` option is used, since hairlines have no width, caps or joins.
+    /// The `clip_rect` and `max_edge_length` options are not supported.
+    ///
+    /// Consecutive segments of a sub-path share their vertices, except for the last segment
+    /// of closed sub-paths which ends on a new vertex at the start of the sub-path so that
+    /// the advancement keeps increasing along the path. The normal of the vertices is null.
+    pub fn tessellate_path_hairline<Iter>(
+        &mut self,
+        it: Iter,
+        options: &StrokeOptions,
+        output: &mut dyn LineGeometryBuilder<StrokeVertex>,
+    ) -> TessellationResult
+    where
+        Iter: IntoIterator<Item = PathEvent>,
+    {
+        if options.clip_rect.is_some() || options.max_edge_length.is_some() {
+            return Err(TessellationError::UnsupportedParamater);
+        }
+
+        output.begin_geometry();
+
+        let mut builder = HairlineBuilder {
+            first: None,
+            previous: None,
+            length: 0.0,
+            output,
+        };
+
+        if let Err(e) = builder.build(it.into_iter(), options.tolerance) {
+            builder.output.abort_geometry();
+            return Err(e.into());
+        }
+
+        Ok(builder.output.end_geometry())
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct PathPoint {
+    position: Point,
+    source: VertexSource,
+}
+
+struct HairlineBuilder<'l> {
+    // The start of the current sub-path.
+    first: Option<PathPoint>,
+    // The last point of the current sub-path and its vertex, if it was added.
+    previous: Option<(PathPoint, Option<VertexId>)>,
+    length: f32,
+    output: &'l mut dyn LineGeometryBuilder<StrokeVertex>,
+}
+
+type BuildResult = Result<(), GeometryBuilderError>;
+
+impl<'l> HairlineBuilder<'l> {
+    fn build(&mut self, it: impl Iterator<Item = PathEvent>, tolerance: f32) -> BuildResult {
+        let mut sub_path = 0;
+        let mut first_sub_path = true;
+        for (idx, evt) in it.enumerate() {
+            let event = idx as u32;
+            let position = move |t| EventPosition { sub_path, event, t };
+            match evt {
+                PathEvent::MoveTo(to) => {
+                    if !first_sub_path {
+                        sub_path += 1;
+                    }
+                    first_sub_path = false;
+                    let point = PathPoint {
+                        position: to,
+                        source: VertexSource::Endpoint(EventPosition { sub_path, event, t: 0.0 }),
+                    };
+                    self.first = Some(point);
+                    self.previous = Some((point, None));
+                }
+                PathEvent::Line(segment) => {
+                    self.line_to(segment.to, VertexSource::Endpoint(position(1.0)))?;
+                }
+                PathEvent::Quadratic(segment) => {
+                    let mut result = Ok(());
+                    segment.for_each_flattened_with_t(tolerance, &mut |p, t| {
+                        if result.is_ok() {
+                            result = self.line_to(p, curve_source(position(t)));
+                        }
+                    });
+                    result?;
+                }
+                PathEvent::Cubic(segment) => {
+                    let mut result = Ok(());
+                    segment.for_each_flattened_with_t(tolerance, &mut |p, t| {
+                        if result.is_ok() {
+                            result = self.line_to(p, curve_source(position(t)));
+                        }
+                    });
+                    result?;
+                }
+                PathEvent::Close(..) => {
+                    if let Some(first) = self.first {
+                        let source = VertexSource::Endpoint(position(1.0));
+                        self.line_to(first.position, source)?;
+                        self.previous = Some((first, None));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn line_to(&mut self, to: Point, source: VertexSource) -> BuildResult {
+        let (from, from_id) = match self.previous {
+            Some(previous) => previous,
+            None => { return Ok(()); }
+        };
+
+        if from.position == to {
+            return Ok(());
+        }
+
+        // The first vertex of a sub-path is only added along with its first segment.
+        let from_id = match from_id {
+            Some(id) => id,
+            None => self.add_vertex(&from)?,
+        };
+
+        self.length += (to - from.position).length();
+        let point = PathPoint { position: to, source };
+        let to_id = self.add_vertex(&point)?;
+        self.output.add_line(from_id, to_id);
+
+        self.previous = Some((point, Some(to_id)));
+
+        Ok(())
+    }
+
+    fn add_vertex(&mut self, point: &PathPoint) -> Result<VertexId, GeometryBuilderError> {
+        self.output.add_vertex(StrokeVertex {
+            position: point.position,
+            normal: vector(0.0, 0.0),
+            advancement: self.length,
+            side: Side::Left,
+            source: point.source,
+        })
+    }
+}
+
+fn curve_source(position: EventPosition) -> VertexSource {
+    if position.t == 1.0 {
+        VertexSource::Endpoint(position)
+    } else {
+        VertexSource::Curve(position)
+    }
+}
+
+#[cfg(test)]
+use crate::path::Path;
+#[cfg(test)]
+use crate::path::builder::Build;
+#[cfg(test)]
+use crate::math::point;
+#[cfg(test)]
+use crate::geometry_builder::{VertexBuffers, simple_builder, NoOutput};
+
+#[test]
+fn hairline_segments() {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(10.0, 0.0));
+    builder.line_to(point(10.0, 0.0));
+    builder.line_to(point(10.0, 10.0));
+    builder.close();
+    builder.move_to(point(20.0, 0.0));
+    builder.line_to(point(30.0, 0.0));
+    // A sub-path with a single point produces nothing.
+    builder.move_to(point(50.0, 0.0));
+    let path = builder.build();
+
+    let mut buffers: VertexBuffers<StrokeVertex, u16> = VertexBuffers::new();
+    let count = StrokeTessellator::new().tessellate_path_hairline(
+        &path,
+        &StrokeOptions::default(),
+        &mut simple_builder(&mut buffers),
+    ).unwrap();
+
+    assert_eq!(count.vertices, 6);
+    assert_eq!(count.indices, 8);
+    assert_eq!(buffers.indices, vec![0, 1, 1, 2, 2, 3, 4, 5]);
+
+    let positions: Vec<Point> = buffers.vertices.iter().map(|v| v.position).collect();
+    assert_eq!(positions, vec![
+        point(0.0, 0.0), point(10.0, 0.0), point(10.0, 10.0), point(0.0, 0.0),
+        point(20.0, 0.0), point(30.0, 0.0),
+    ]);
+
+    let closed_length = 20.0 + 200.0f32.sqrt();
+    let advancements: Vec<f32> = buffers.vertices.iter().map(|v| v.advancement).collect();
+    assert_eq!(&advancements[..3], &[0.0, 10.0, 20.0]);
+    assert!((advancements[3] - closed_length).abs() < 0.001);
+    assert!((advancements[4] - closed_length).abs() < 0.001);
+    assert!((advancements[5] - closed_length - 10.0).abs() < 0.001);
+}
+
+#[test]
+fn hairline_curves() {
+    use crate::extra::rust_logo::build_logo_path;
+
+    let mut builder = Path::builder().with_svg();
+    build_logo_path(&mut builder);
+    let path = builder.build();
+
+    let mut tess = StrokeTessellator::new();
+    let mut buffers: VertexBuffers<StrokeVertex, u16> = VertexBuffers::new();
+    let options = StrokeOptions::tolerance(0.05);
+    let count = tess.tessellate_path_hairline(
+        &path,
+        &options,
+        &mut simple_builder(&mut buffers),
+    ).unwrap();
+
+    assert_eq!(count.indices % 2, 0);
+    assert!(count.indices > 0);
+
+    // Segments are connected and the advancement increases along them.
+    for line in buffers.indices.chunks(2) {
+        let a = &buffers.vertices[line[0] as usize];
+        let b = &buffers.vertices[line[1] as usize];
+        assert!(a.position != b.position);
+        assert!(((b.advancement - a.advancement) - (b.position - a.position).length()).abs() < 0.01);
+    }
+
+    assert_eq!(
+        tess.tessellate_path_hairline(&path, &options, &mut NoOutput::new()),
+        Ok(count),
+    );
+
+    assert_eq!(
+        tess.tessellate_path_hairline(
+            &path,
+            &options.with_max_edge_length(1.0),
+            &mut NoOutput::new(),
+        ),
+        Err(TessellationError::UnsupportedParamater),
+    );
+}
@@ -189,11 +189,26 @@ pub extern crate serde;
 
 pub mod basic_shapes;
 pub mod geometry_builder;
+pub mod post_processing;
 pub mod debugger;
 mod path_fill;
 mod path_stroke;
 mod math_utils;
 mod fixed;
+mod edge_subdivision;
+mod clipping;
+mod polygons;
+mod attributes;
+mod planar_map;
+mod f64_path;
+mod banded_fill;
+mod stencil_cover;
+mod transform;
+mod calligraphy;
+mod hairline;
+
+#[cfg(feature = "rayon")]
+pub mod parallel;
 
 #[cfg(test)]
 mod earcut_tests;
@@ -213,7 +228,19 @@ pub use crate::path_fill::*;
 pub use crate::path_stroke::*;
 
 #[doc(inline)]
-pub use crate::geometry_builder::{GeometryBuilder, GeometryReceiver, VertexBuffers, BuffersBuilder, VertexConstructor, Count};
+pub use crate::banded_fill::BandedFill;
+
+#[doc(inline)]
+pub use crate::stencil_cover::{StencilCoverTessellator, StencilCoverCount};
+
+#[doc(inline)]
+pub use crate::transform::OutputSpace;
+
+#[doc(inline)]
+pub use crate::calligraphy::Nib;
+
+#[doc(inline)]
+pub use crate::geometry_builder::{GeometryBuilder, GeometryReceiver, PolygonGeometryBuilder, BatchGeometryBuilder, LineGeometryBuilder, VertexBuffers, BuffersBuilder, ChunkedBuffersBuilder, ByteBuffersBuilder, VertexConstructor, Count};
 
 pub use crate::path::FillRule;
 
@@ -296,6 +323,8 @@ pub struct StrokeVertex {
     pub advancement: f32,
     /// Whether the vertex is on the left or right side of the path.
     pub side: Side,
+    /// Where the vertex comes from in the input path.
+    pub source: VertexSource,
 }
 
 /// Vertex produced by the fill tessellators.
@@ -310,6 +339,80 @@ pub struct FillVertex {
     /// Note that some tessellators aren't fully implemented and don't provide the
     /// normal (a nil vector is provided instead). Refer the documentation of each tessellator.
     pub normal: math::Vector,
+    /// Where the vertex comes from in the input path.
+    ///
+    /// This can be used to interpolate custom attributes from the path to the mesh.
+    pub source: VertexSource,
+}
+
+/// Vertex produced by the stroke tessellator for paths with `f64` coordinates.
+///
+/// See `StrokeTessellator::tessellate_f64_path`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct F64StrokeVertex {
+    /// Position of the vertex.
+    pub position: math::F64Point,
+    /// See `StrokeVertex::normal`.
+    pub normal: math::Vector,
+    /// How far along the path this vertex is.
+    pub advancement: f64,
+    /// Whether the vertex is on the left or right side of the path.
+    pub side: Side,
+    /// Where the vertex comes from in the input path.
+    pub source: VertexSource,
+}
+
+/// Vertex produced by the fill tessellator for paths with `f64` coordinates.
+///
+/// See `FillTessellator::tessellate_f64_path`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct F64FillVertex {
+    /// Position of the vertex.
+    pub position: math::F64Point,
+    /// See `FillVertex::normal`.
+    pub normal: math::Vector,
+    /// Where the vertex comes from in the input path.
+    pub source: VertexSource,
+}
+
+/// A position on the input path, expressed as a path event and a parameter on that event.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct EventPosition {
+    /// Index of the sub-path, in the order in which they appear in the path.
+    pub sub_path: u32,
+    /// Index of the event in the sequence of path events (including `MoveTo` and `Close` events).
+    ///
+    /// Edges that close a sub-path without a `Close` event are attributed to the
+    /// index that the `Close` event would have, that is the index of the event
+    /// following the last event of the sub-path.
+    pub event: u32,
+    /// Parameter of the position on the event's segment, between 0.0 and 1.0.
+    pub t: f32,
+}
+
+/// Describes where a vertex produced by the tessellators comes from.
+///
+/// The stroke tessellator doesn't compute intersections and only produces
+/// `Endpoint`, `Curve` and `Unknown` sources.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub enum VertexSource {
+    /// An endpoint of a path event.
+    ///
+    /// Since endpoints are shared by two consecutive events, the position can
+    /// refer to the end of one event (`t = 1.0`) or the start of the next one (`t = 0.0`).
+    Endpoint(EventPosition),
+    /// A point along a path event that isn't one of its endpoints, typically produced
+    /// when flattening a curve.
+    Curve(EventPosition),
+    /// The intersection of two edges.
+    Intersection(EventPosition, EventPosition),
+    /// The vertex does not come from the path (for example vertices generated by
+    /// the `basic_shapes` functions or inserted to satisfy `max_edge_length`).
+    Unknown,
 }
 
 /// Line cap as defined by the SVG specification.
@@ -353,6 +456,27 @@ pub enum LineCap {
     /// If a sub-path has zero length, then the resulting effect is that the stroke for
     /// that sub-path consists solely of a full circle centered at the sub-path's point.
     Round,
+    /// At each end of each sub-path, the shape representing the stroke will be extended
+    /// by a triangle with the same width as the stroke width and whose length is half of
+    /// the stroke width.
+    Triangle,
+    /// An arrowhead with its tip at the end of the sub-path.
+    ///
+    /// The length and width of the arrowhead are expressed in multiples of the line width.
+    /// The stroke is shortened by the length of the arrowhead so that it does not extend
+    /// beyond the tip (as long as the last edge is longer than the arrowhead).
+    Arrow { length: f32, width: f32 },
+    /// A circle centered on the end of the sub-path, with a radius expressed in
+    /// multiples of the line width.
+    Circle { radius: f32 },
+    /// A square rotated by 45 degrees and centered on the end of the sub-path, with its
+    /// diagonals aligned with the tangent at the end of the sub-path. The distance between
+    /// the center and the corners is expressed in multiples of the line width.
+    Diamond { size: f32 },
+    /// A user-defined shape, set with `StrokeTessellator::set_custom_cap`.
+    ///
+    /// See `CapShape`. If no shape is set, this behaves like `LineCap::Butt`.
+    Custom,
 }
 
 /// Line join as defined by the SVG specification.
@@ -373,6 +497,13 @@ pub enum LineJoin {
     /// The bevel shape is a triangle that fills the area between the two stroked
     /// segments.
     Bevel,
+    /// The outer edges of the stroke are extended with arcs that have the same
+    /// curvature as the outer edges at the join point, up to their intersection.
+    ///
+    /// Like `MiterClip`, the extensions are clipped if they exceed the miter limit.
+    /// Falls back to `MiterClip` if the arcs don't intersect or if both segments are
+    /// straight.
+    Arcs,
 }
 
 /// Parameters for the tessellator.
@@ -420,6 +551,34 @@ pub struct StrokeOptions {
     /// Default value: `true`.
     pub apply_line_width: bool,
 
+    /// Maximum length of the edges of the generated triangles.
+    ///
+    /// When set, the tessellator splits the edges of the stroke and inserts interior
+    /// vertices so that no edge of the output triangles is longer than this value.
+    /// This is useful when the geometry is deformed afterwards (for example in a vertex
+    /// shader) and needs to be densely subdivided.
+    ///
+    /// If `apply_line_width` is false, edge lengths are measured on the vertex positions
+    /// before the line width is applied.
+    ///
+    /// Must be greater than zero.
+    /// Default value: `None`.
+    pub max_edge_length: Option<f32>,
+
+    /// Discard the geometry that is outside of this rectangle.
+    ///
+    /// The triangles of the stroke are clipped as they are generated, and only the vertices
+    /// that are inside of the rectangle are passed to the output. The vertices created where
+    /// triangles are cut are exactly on the sides of the rectangle, so that the geometry of
+    /// adjacent clip rectangles line up. Their attributes are interpolated and their source
+    /// is `VertexSource::Unknown`.
+    ///
+    /// If `apply_line_width` is false, vertex positions are clipped before the line width
+    /// is applied.
+    ///
+    /// Default value: `None`.
+    pub clip_rect: Option<math::Rect>,
+
     // To be able to add fields without making it a breaking change, add an empty private field
     // which makes it impossible to create a StrokeOptions without calling the constructor.
     _private: (),
@@ -447,6 +606,8 @@ impl StrokeOptions {
         miter_limit: Self::DEFAULT_MITER_LIMIT,
         tolerance: Self::DEFAULT_TOLERANCE,
         apply_line_width: true,
+        max_edge_length: None,
+        clip_rect: None,
         _private: (),
     };
 
@@ -504,6 +665,19 @@ impl StrokeOptions {
         self.apply_line_width = false;
         self
     }
+
+    #[inline]
+    pub fn with_max_edge_length(mut self, length: f32) -> Self {
+        assert!(length > 0.0);
+        self.max_edge_length = Some(length);
+        self
+    }
+
+    #[inline]
+    pub fn with_clip_rect(mut self, rect: math::Rect) -> Self {
+        self.clip_rect = Some(rect);
+        self
+    }
 }
 
 /// Parameters for the fill tessellator.
@@ -547,6 +721,39 @@ pub struct FillOptions {
     /// What to do if the tessellator detects an error.
     pub on_error: OnError,
 
+    /// Maximum length of the edges of the generated triangles.
+    ///
+    /// When set, the tessellator splits the boundary of the shape and inserts interior
+    /// vertices so that no edge of the output triangles is longer than this value.
+    /// This is useful when the geometry is deformed afterwards (for example in a vertex
+    /// shader) and needs to be densely subdivided.
+    ///
+    /// Must be greater than zero.
+    /// Default value: `None`.
+    pub max_edge_length: Option<f32>,
+
+    /// Discard the geometry that is outside of this rectangle.
+    ///
+    /// The edges of the path are clipped before the tessellation, so that the amount of
+    /// work and the number of generated vertices only depend on the part of the path that
+    /// is inside of the rectangle. Edges are cut exactly on the sides of the rectangle, so
+    /// that the geometry of adjacent clip rectangles line up.
+    ///
+    /// Default value: `None`.
+    pub clip_rect: Option<math::Rect>,
+
+    /// Trade some precision and performance for robustness.
+    ///
+    /// In robust mode the input is snapped to a grid of 1/256th of a unit and the intersections
+    /// between the edges are resolved with exact predicates before the tessellation. The
+    /// geometry is only sent to the output once the tessellation has succeeded, and errors
+    /// are handled according to `on_error`.
+    ///
+    /// Robust mode is not supported by `tessellate_path_to_polygons`.
+    ///
+    /// Default value: `false`.
+    pub robust: bool,
+
     // To be able to add fields without making it a breaking change, add an empty private field
     // which makes it impossible to create a FillOptions without the calling constructor.
     _private: (),
@@ -568,6 +775,9 @@ impl FillOptions {
         compute_normals: true,
         assume_no_intersections: false,
         on_error: OnError::DEFAULT,
+        max_edge_length: None,
+        clip_rect: None,
+        robust: false,
         _private: (),
     };
 
@@ -609,6 +819,25 @@ impl FillOptions {
         self.on_error = policy;
         self
     }
+
+    #[inline]
+    pub fn with_max_edge_length(mut self, length: f32) -> Self {
+        assert!(length > 0.0);
+        self.max_edge_length = Some(length);
+        self
+    }
+
+    #[inline]
+    pub fn with_clip_rect(mut self, rect: math::Rect) -> Self {
+        self.clip_rect = Some(rect);
+        self
+    }
+
+    #[inline]
+    pub fn with_robust_mode(mut self, robust: bool) -> Self {
+        self.robust = robust;
+        self
+    }
 }
 
 impl Default for FillOptions {
@@ -639,6 +868,55 @@ impl OnError {
     pub const DEFAULT: Self = OnError::Stop;
 }
 
+/// The kind of polygons produced by the fill tessellator's polygon output.
+///
+/// See [`FillTessellator::tessellate_path_to_polygons`](struct.FillTessellator.html#method.tessellate_path_to_polygons).
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub enum PolygonMode {
+    /// The y-monotone polygons that the tessellator decomposes the shape into.
+    Monotone,
+    /// Convex polygons obtained by merging adjacent triangles of the tessellation.
+    Convex,
+}
+
+/// How much work an incremental fill tessellation can do before returning.
+///
+/// See [`FillTessellator::resume`](struct.FillTessellator.html#method.resume).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FillBudget {
+    /// Process at most this number of sweep line positions (each position is a vertex of the
+    /// path or an intersection, with all of the edges that start or end there).
+    Events(u32),
+    /// Stop after roughly this amount of time.
+    ///
+    /// Not available on `wasm32-unknown-unknown`, where the standard library can't read
+    /// the clock.
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    Time(std::time::Duration),
+    /// Run until the tessellation is finished.
+    Unlimited,
+}
+
+/// The status of an incremental fill tessellation.
+///
+/// See [`FillTessellator::resume`](struct.FillTessellator.html#method.resume).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FillStatus {
+    /// The tessellation is complete and `end_geometry` was called on the output.
+    Finished(Count),
+    /// The budget ran out before the end of the path. The geometry generated so far
+    /// is in the output and `resume` must be called again to continue.
+    InProgress,
+}
+
+/// Identifies a path among the paths tessellated in a batch.
+///
+/// See [`FillTessellator::tessellate_batch`](struct.FillTessellator.html#method.tessellate_batch).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct PathId(pub u32);
+
 impl Default for OnError {
     fn default() -> Self { Self::DEFAULT }
 }
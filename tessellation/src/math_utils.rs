@@ -93,6 +93,135 @@ pub(crate) fn segment_intersection(
     None
 }
 
+/// The size of the grid that the input is snapped to in robust mode, in fixed point units
+/// (1/256th of a unit).
+const ROBUST_GRID: i128 = 1 << 8;
+
+// Round a fixed point value multiplied by `den` to the nearest multiple of the grid.
+//
+// Halfway values are rounded up, so that each value is rounded to the center of the grid
+// cell that contains it (see `segment_crosses_grid_cell`).
+fn round_to_grid(num: i128, den: i128) -> FixedPoint32 {
+    debug_assert!(den > 0);
+    let grid = ROBUST_GRID;
+    let d = den * grid;
+    let n = num + d / 2;
+    let q = if n >= 0 { n / d } else { -((-n + d - 1) / d) };
+    FixedPoint32::from_raw((q * grid) as i32)
+}
+
+/// Snap a point to the grid used in robust mode.
+pub(crate) fn snap_to_grid(p: TessPoint) -> TessPoint {
+    TessPoint::new(
+        round_to_grid(p.x.raw() as i128, 1),
+        round_to_grid(p.y.raw() as i128, 1),
+    )
+}
+
+/// The sign of the orientation of `c` relative to the line from `a` to `b`, computed exactly.
+pub(crate) fn orientation(a: TessPoint, b: TessPoint, c: TessPoint) -> i32 {
+    let abx = b.x.raw() as i128 - a.x.raw() as i128;
+    let aby = b.y.raw() as i128 - a.y.raw() as i128;
+    let acx = c.x.raw() as i128 - a.x.raw() as i128;
+    let acy = c.y.raw() as i128 - a.y.raw() as i128;
+
+    (abx * acy - aby * acx).signum() as i32
+}
+
+// Whether a point that is on the line supporting a segment is strictly between its endpoints.
+fn strictly_inside(p: TessPoint, a: TessPoint, b: TessPoint) -> bool {
+    if p == a || p == b {
+        return false;
+    }
+    let (min_x, max_x) = a.x.min_max(b.x);
+    let (min_y, max_y) = a.y.min_max(b.y);
+
+    p.x >= min_x && p.x <= max_x && p.y >= min_y && p.y <= max_y
+}
+
+/// Find where two edges intersect using exact predicates, with the result snapped to the
+/// grid of the robust mode.
+///
+/// Returns a point if the edges cross, or if an endpoint of one edge is in the interior of
+/// the other one. Returns `None` if the edges only touch at their endpoints.
+pub(crate) fn exact_segment_intersection(e1: &Edge, e2: &Edge) -> Option<TessPoint> {
+    if !x_aabb_test(e1.upper.x, e1.lower.x, e2.upper.x, e2.lower.x) {
+        return None;
+    }
+
+    let (a1, b1, a2, b2) = (e1.upper, e1.lower, e2.upper, e2.lower);
+    let o1 = orientation(a1, b1, a2);
+    let o2 = orientation(a1, b1, b2);
+    let o3 = orientation(a2, b2, a1);
+    let o4 = orientation(a2, b2, b1);
+
+    if o1 * o2 < 0 && o3 * o4 < 0 {
+        let v1x = b1.x.raw() as i128 - a1.x.raw() as i128;
+        let v1y = b1.y.raw() as i128 - a1.y.raw() as i128;
+        let v2x = b2.x.raw() as i128 - a2.x.raw() as i128;
+        let v2y = b2.y.raw() as i128 - a2.y.raw() as i128;
+        let dx = a2.x.raw() as i128 - a1.x.raw() as i128;
+        let dy = a2.y.raw() as i128 - a1.y.raw() as i128;
+
+        // a1 + v1 * num / den
+        let mut den = v1x * v2y - v1y * v2x;
+        let mut num = dx * v2y - dy * v2x;
+        if den < 0 {
+            den = -den;
+            num = -num;
+        }
+
+        return Some(TessPoint::new(
+            round_to_grid(a1.x.raw() as i128 * den + v1x * num, den),
+            round_to_grid(a1.y.raw() as i128 * den + v1y * num, den),
+        ));
+    }
+
+    // T-junctions and overlapping collinear edges.
+    if o1 == 0 && strictly_inside(a2, a1, b1) { return Some(a2); }
+    if o2 == 0 && strictly_inside(b2, a1, b1) { return Some(b2); }
+    if o3 == 0 && strictly_inside(a1, a2, b2) { return Some(a1); }
+    if o4 == 0 && strictly_inside(b1, a2, b2) { return Some(b1); }
+
+    None
+}
+
+/// Whether a segment with endpoints on the grid of the robust mode passes through the cell
+/// of the grid centered on `center`.
+///
+/// The cells contain their top and left sides but not their bottom and right sides, so
+/// that each point belongs to a single cell, the one whose center it is rounded to.
+pub(crate) fn segment_crosses_grid_cell(a: TessPoint, b: TessPoint, center: TessPoint) -> bool {
+    let half = (ROBUST_GRID / 2) as i32;
+    let (x0, x1) = (center.x.raw() - half, center.x.raw() + half);
+    let (y0, y1) = (center.y.raw() - half, center.y.raw() + half);
+    let (min_x, max_x) = a.x.min_max(b.x);
+    let (min_y, max_y) = a.y.min_max(b.y);
+    let (min_x, max_x, min_y, max_y) = (min_x.raw(), max_x.raw(), min_y.raw(), max_y.raw());
+    if max_x < x0 || min_x > x1 || max_y < y0 || min_y > y1 {
+        return false;
+    }
+
+    let corner = |x, y| TessPoint::new(FixedPoint32::from_raw(x), FixedPoint32::from_raw(y));
+    let top_left = orientation(a, b, corner(x0, y0));
+    let corners = [
+        top_left,
+        orientation(a, b, corner(x1, y0)),
+        orientation(a, b, corner(x0, y1)),
+        orientation(a, b, corner(x1, y1)),
+    ];
+
+    // The endpoints are on the grid and the sides of the cells are between grid lines, so the
+    // segment can't end on a side of the cell or run along it. If the bounding boxes overlap
+    // and the line separates the corners, the segment goes through the inside of the cell.
+    if corners.contains(&1) && corners.contains(&-1) {
+        return true;
+    }
+
+    // Otherwise the line at most touches a corner, and only the top left one is in the cell.
+    top_left == 0 && min_x <= x0 && x0 <= max_x && min_y <= y0 && y0 <= max_y
+}
+
 /// Compute a normal vector at a point P such that ```x ---e1----> P ---e2---> x```
 ///
 /// The resulting vector is not normalized. The length is such that extruding the shape
@@ -0,0 +1,234 @@
+//! Tessellation of many paths in parallel.
+//!
+//! This module is only available with the `rayon` feature.
+//!
+//! The paths are distributed over rayon's thread pool. Each worker thread keeps its own
+//! `FillTessellator` and `StrokeTessellator` so that their internal allocations are reused
+//! from one path to the next. The geometry of each path is then appended to the output
+//! buffers in the order of the input slice, regardless of which thread produced it.
+//!
+//! ## Example
+//!
+//! ```
+//! # extern crate lyon_tessellation as tess;
+//! # use tess::path::Path;
+//! # use tess::math::point;
+//! # use tess::{FillOptions, FillVertex, VertexBuffers};
+//! # use tess::geometry_builder::Identity;
+//! # use tess::parallel::fill_paths;
+//! # fn main() {
+//! let mut paths = Vec::new();
+//! for i in 0..100 {
+//!     let x = i as f32 * 10.0;
+//!     let mut builder = Path::builder();
+//!     builder.polygon(&[point(x, 0.0), point(x + 5.0, 0.0), point(x + 5.0, 5.0), point(x, 5.0)]);
+//!     paths.push(builder.build());
+//! }
+//!
+//! let mut buffers: VertexBuffers<FillVertex, u32> = VertexBuffers::new();
+//! fill_paths(&paths, &FillOptions::default(), &mut buffers, Identity).unwrap();
+//!
+//! assert_eq!(buffers.indices.len(), 100 * 6);
+//! # }
+//! ```
+
+use crate::geometry_builder::{BuffersBuilder, VertexBuffers, VertexConstructor, Identity, MaxIndex, VertexId};
+use crate::path::Path;
+use crate::{FillTessellator, FillOptions, FillVertex};
+use crate::{StrokeTessellator, StrokeOptions, StrokeVertex};
+use crate::{TessellationResult, TessellationError, Count};
+
+use rayon::prelude::*;
+
+use std::cell::RefCell;
+
+thread_local! {
+    static FILL_TESSELLATOR: RefCell<FillTessellator> = RefCell::new(FillTessellator::new());
+    static STROKE_TESSELLATOR: RefCell<StrokeTessellator> = RefCell::new(StrokeTessellator::new());
+}
+
+/// Tessellates the fill of each path in parallel and appends the result to `output`.
+///
+/// The paths are tessellated independently. The geometry of `paths[i]` comes before the
+/// geometry of `paths[i + 1]` in the output buffers.
+///
+/// If the tessellation of any of the paths fails, the error of the first failing path is
+/// returned and `output` is left untouched.
+pub fn fill_paths<VertexType, IndexType, Ctor>(
+    paths: &[Path],
+    options: &FillOptions,
+    output: &mut VertexBuffers<VertexType, IndexType>,
+    vertex_constructor: Ctor,
+) -> TessellationResult
+where
+    IndexType: From<VertexId> + MaxIndex,
+    Ctor: VertexConstructor<FillVertex, VertexType>,
+{
+    let geometries: Vec<Result<VertexBuffers<FillVertex, u32>, TessellationError>> = paths.par_iter().map(|path| {
+        FILL_TESSELLATOR.with(|tessellator| {
+            let mut buffers = VertexBuffers::with_capacity(0, 0);
+            tessellator.borrow_mut().tessellate_path(
+                path.iter(),
+                options,
+                &mut BuffersBuilder::new(&mut buffers, Identity),
+            )?;
+
+            Ok(buffers)
+        })
+    }).collect();
+
+    merge(geometries, output, vertex_constructor)
+}
+
+/// Tessellates the stroke of each path in parallel and appends the result to `output`.
+///
+/// The paths are tessellated independently. The geometry of `paths[i]` comes before the
+/// geometry of `paths[i + 1]` in the output buffers.
+///
+/// If the tessellation of any of the paths fails, the error of the first failing path is
+/// returned and `output` is left untouched.
+pub fn stroke_paths<VertexType, IndexType, Ctor>(
+    paths: &[Path],
+    options: &StrokeOptions,
+    output: &mut VertexBuffers<VertexType, IndexType>,
+    vertex_constructor: Ctor,
+) -> TessellationResult
+where
+    IndexType: From<VertexId> + MaxIndex,
+    Ctor: VertexConstructor<StrokeVertex, VertexType>,
+{
+    let geometries: Vec<Result<VertexBuffers<StrokeVertex, u32>, TessellationError>> = paths.par_iter().map(|path| {
+        STROKE_TESSELLATOR.with(|tessellator| {
+            let mut buffers = VertexBuffers::with_capacity(0, 0);
+            tessellator.borrow_mut().tessellate_path(
+                path.iter(),
+                options,
+                &mut BuffersBuilder::new(&mut buffers, Identity),
+            )?;
+
+            Ok(buffers)
+        })
+    }).collect();
+
+    merge(geometries, output, vertex_constructor)
+}
+
+// Append the per-path geometries to the output, offsetting their indices.
+fn merge<Input, VertexType, IndexType, Ctor>(
+    geometries: Vec<Result<VertexBuffers<Input, u32>, TessellationError>>,
+    output: &mut VertexBuffers<VertexType, IndexType>,
+    mut vertex_constructor: Ctor,
+) -> TessellationResult
+where
+    IndexType: From<VertexId> + MaxIndex,
+    Ctor: VertexConstructor<Input, VertexType>,
+{
+    let mut count = Count { vertices: 0, indices: 0 };
+    for geometry in &geometries {
+        match *geometry {
+            Ok(ref buffers) => {
+                count.vertices += buffers.vertices.len() as u32;
+                count.indices += buffers.indices.len() as u32;
+            }
+            Err(ref e) => {
+                return Err(e.clone());
+            }
+        }
+    }
+
+    if output.vertices.len() + count.vertices as usize > IndexType::max_index() {
+        return Err(TessellationError::TooManyVertices);
+    }
+
+    output.vertices.reserve(count.vertices as usize);
+    output.indices.reserve(count.indices as usize);
+
+    for buffers in geometries {
+        let buffers = buffers.unwrap();
+        let offset = output.vertices.len() as u32;
+        for vertex in buffers.vertices {
+            output.vertices.push(vertex_constructor.new_vertex(vertex));
+        }
+        for index in buffers.indices {
+            output.indices.push(VertexId(index + offset).into());
+        }
+    }
+
+    Ok(count)
+}
+
+#[test]
+fn test_parallel_fill_matches_sequential() {
+    use crate::math::point;
+
+    let mut paths = Vec::new();
+    for i in 0..50 {
+        let x = i as f32 * 10.0;
+        let mut builder = Path::builder();
+        builder.move_to(point(x, 0.0));
+        builder.line_to(point(x + 8.0, 0.0));
+        builder.quadratic_bezier_to(point(x + 12.0, 5.0), point(x + 8.0, 10.0));
+        builder.line_to(point(x + (i % 7) as f32, 10.0));
+        builder.close();
+        paths.push(builder.build());
+    }
+
+    let options = FillOptions::tolerance(0.05);
+
+    let mut expected: VertexBuffers<FillVertex, u32> = VertexBuffers::new();
+    let mut tessellator = FillTessellator::new();
+    for path in &paths {
+        tessellator.tessellate_path(
+            path.iter(),
+            &options,
+            &mut BuffersBuilder::new(&mut expected, Identity),
+        ).unwrap();
+    }
+
+    let mut buffers: VertexBuffers<FillVertex, u32> = VertexBuffers::new();
+    let count = fill_paths(&paths, &options, &mut buffers, Identity).unwrap();
+
+    assert_eq!(count.vertices as usize, expected.vertices.len());
+    assert_eq!(count.indices as usize, expected.indices.len());
+    assert_eq!(buffers.indices, expected.indices);
+    for (a, b) in buffers.vertices.iter().zip(expected.vertices.iter()) {
+        assert_eq!(a.position, b.position);
+    }
+}
+
+#[test]
+fn test_parallel_stroke_index_offsets() {
+    use crate::math::point;
+
+    let mut paths = Vec::new();
+    for i in 0..20 {
+        let y = i as f32 * 5.0;
+        let mut builder = Path::builder();
+        builder.move_to(point(0.0, y));
+        builder.line_to(point(10.0, y));
+        builder.line_to(point(10.0, y + 2.0));
+        paths.push(builder.build());
+    }
+
+    // Start with some geometry in the buffers to check that it is preserved.
+    let mut buffers: VertexBuffers<StrokeVertex, u16> = VertexBuffers::new();
+    stroke_paths(&paths[..1], &StrokeOptions::default(), &mut buffers, Identity).unwrap();
+    let first_count = buffers.vertices.len();
+
+    let count = stroke_paths(&paths, &StrokeOptions::default(), &mut buffers, Identity).unwrap();
+
+    assert_eq!(buffers.vertices.len(), first_count + count.vertices as usize);
+    for &idx in &buffers.indices {
+        assert!((idx as usize) < buffers.vertices.len());
+    }
+    // The geometry of each path only refers to its own vertices.
+    let per_path = count.indices as usize / paths.len();
+    let vertices_per_path = count.vertices as usize / paths.len();
+    for (i, tri) in buffers.indices[buffers.indices.len() - count.indices as usize..].chunks(3).enumerate() {
+        let path_idx = i * 3 / per_path;
+        let start = first_count + path_idx * vertices_per_path;
+        for &idx in tri {
+            assert!((idx as usize) >= start && (idx as usize) < start + vertices_per_path);
+        }
+    }
+}
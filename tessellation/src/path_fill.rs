@@ -21,18 +21,27 @@
 //
 
 use std::mem::{replace, swap};
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::time::Instant;
 use std::cmp::{PartialOrd, Ordering};
+use std::ops::Range;
 
 use sid::{Id, IdVec};
 
 use crate::FillVertex as Vertex;
-use crate::{FillOptions, FillRule, Side, OnError, TessellationError, TessellationResult, InternalError};
+use crate::{FillOptions, FillRule, Side, OnError, PolygonMode, PathId, TessellationError, TessellationResult, InternalError};
+use crate::{FillBudget, FillStatus};
+use crate::{VertexSource, EventPosition};
 use crate::geom::math::*;
 use crate::geom::{QuadraticBezierSegment, CubicBezierSegment};
 use crate::geom::euclid::{self, Trig};
 use crate::math_utils::*;
-use crate::geometry_builder::{GeometryBuilder, GeometryBuilderError, Count, VertexId};
-use crate::path::PathEvent;
+use crate::geometry_builder::{GeometryBuilder, PolygonGeometryBuilder, BatchGeometryBuilder, GeometryBuilderError, Count, VertexId};
+use crate::edge_subdivision::EdgeSubdivider;
+use crate::polygons::PolygonCollector;
+use crate::attributes::AttributeInterpolator;
+use crate::planar_map::PlanarMap;
+use crate::path::{PathEvent, PathSlice};
 use crate::path::builder::{Build, FlatPathBuilder};
 
 #[cfg(feature="debugger")]
@@ -102,41 +111,104 @@ impl Edge {
     }
 }
 
+// Where an edge comes from in the input path.
 #[derive(Copy, Clone, Debug)]
-struct OrientedEdge {
-    upper: TessPoint,
-    lower: TessPoint,
-    winding: i16,
+pub(crate) struct EdgeSource {
+    sub_path: u32,
+    event: u32,
+    // Parameter on the path event at the upper and lower endpoints of the edge.
+    upper_t: f32,
+    lower_t: f32,
+    // Whether the endpoints were introduced by splitting the edge (for
+    // example at an intersection) rather than coming from the path.
+    upper_is_cut: bool,
+    lower_is_cut: bool,
+}
+
+impl EdgeSource {
+    fn swapped(&self) -> Self {
+        EdgeSource {
+            sub_path: self.sub_path,
+            event: self.event,
+            upper_t: self.lower_t,
+            lower_t: self.upper_t,
+            upper_is_cut: self.lower_is_cut,
+            lower_is_cut: self.upper_is_cut,
+        }
+    }
+
+    // Split the edge at a position between its endpoints, returning the source
+    // of the part above and the part below the split.
+    fn split(&self, edge: &Edge, position: TessPoint) -> (EdgeSource, EdgeSource) {
+        let d = edge.lower - edge.upper;
+        let p = position - edge.upper;
+        let ratio = if d.x.abs() > d.y.abs() {
+            p.x.to_f32() / d.x.to_f32()
+        } else if d.y.raw() != 0 {
+            p.y.to_f32() / d.y.to_f32()
+        } else {
+            0.0
+        };
+        let ratio = ratio.max(0.0).min(1.0);
+        let t = self.upper_t + (self.lower_t - self.upper_t) * ratio;
+
+        let mut above = *self;
+        above.lower_t = t;
+        above.lower_is_cut = true;
+        let mut below = *self;
+        below.upper_t = t;
+        below.upper_is_cut = true;
+
+        (above, below)
+    }
+
+    // The source of the edges that are added on the sides of the clip rectangle.
+    pub(crate) const CLIP: EdgeSource = EdgeSource {
+        sub_path: 0,
+        event: std::u32::MAX,
+        upper_t: 0.0,
+        lower_t: 1.0,
+        upper_is_cut: true,
+        lower_is_cut: true,
+    };
+
+    fn is_clip(&self) -> bool { self.event == std::u32::MAX }
+
+    fn upper_position(&self) -> EventPosition {
+        EventPosition { sub_path: self.sub_path, event: self.event, t: self.upper_t }
+    }
+
+    fn lower_position(&self) -> EventPosition {
+        EventPosition { sub_path: self.sub_path, event: self.event, t: self.lower_t }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct OrientedEdge {
+    pub(crate) upper: TessPoint,
+    pub(crate) lower: TessPoint,
+    pub(crate) winding: i16,
+    pub(crate) source: EdgeSource,
 }
 
 impl OrientedEdge {
-    fn new(mut a: TessPoint, mut b: TessPoint) -> Self {
+    fn new(mut a: TessPoint, mut b: TessPoint, mut source: EdgeSource) -> Self {
         let mut winding = 1;
         if is_after(a, b) {
             swap(&mut a, &mut b);
+            source = source.swapped();
             winding = -1;
         }
-        OrientedEdge { upper: a, lower: b, winding }
+        OrientedEdge { upper: a, lower: b, winding, source }
     }
 
-    fn with_winding(mut a: TessPoint, mut b: TessPoint, winding: i16) -> Self {
+    fn with_winding(mut a: TessPoint, mut b: TessPoint, winding: i16, mut source: EdgeSource) -> Self {
         debug_assert!(winding != 0);
         if is_after(a, b) {
             swap(&mut a, &mut b);
+            source = source.swapped();
         }
-        OrientedEdge { upper: a, lower: b, winding }
-    }
-
-    fn to_active_edge(&self, upper_id: VertexId) -> ActiveEdge {
-        ActiveEdge {
-            points: Edge {
-                upper: self.upper,
-                lower: self.lower,
-            },
-            upper_id,
-            winding: self.winding,
-            merge: false,
-        }
+        OrientedEdge { upper: a, lower: b, winding, source }
     }
 
     fn edge(&self) -> Edge {
@@ -150,6 +222,7 @@ struct PendingEdge {
     lower: TessPoint,
     angle: f32,
     winding: i16,
+    source: EdgeSource,
 }
 
 impl PendingEdge {
@@ -158,6 +231,7 @@ impl PendingEdge {
             upper,
             lower: self.lower,
             winding: self.winding,
+            source: self.source,
         }
     }
 
@@ -170,6 +244,7 @@ impl PendingEdge {
             upper_id,
             winding: self.winding,
             merge: false,
+            source: self.source,
         }
     }
 
@@ -277,6 +352,10 @@ pub struct FillTessellator {
     pending_edges: Vec<PendingEdge>,
     // The current position of the sweep line.
     current_position: TessPoint,
+    // The next edge and vertex to process when the sweep is resumed.
+    sweep_cursor: (usize, usize),
+    // Whether an incremental tessellation is in progress.
+    incremental: bool,
 
     // various options
     options: FillOptions,
@@ -285,10 +364,20 @@ pub struct FillTessellator {
     // Events and intersections that haven't been processed yet.
     events: FillEvents,
     intersections: Vec<OrientedEdge>,
+    // The events clipped to the clip rectangle, if any.
+    clipped_events: FillEvents,
+    // The snapped events and the geometry of the robust mode.
+    robust_events: FillEvents,
+    robust_geometry: RecordedGeometry,
 
     monotone_tessellators: IdVec<SpanId, MonotoneTessellator>,
     tess_pool: Vec<MonotoneTessellator>,
 
+    // The monotone polygons, recorded when requested by the polygon output.
+    record_polygons: bool,
+    polygons: Vec<VertexId>,
+    polygon_ends: Vec<usize>,
+
     error: Option<TessellationError>,
 
     #[cfg(feature="debugger")]
@@ -300,15 +389,23 @@ impl FillTessellator {
     pub fn new() -> Self {
         FillTessellator {
             events: FillEvents::new(),
+            clipped_events: FillEvents::new(),
+            robust_events: FillEvents::new(),
+            robust_geometry: RecordedGeometry::default(),
             active_edges: ActiveEdges::with_capacity(16),
             pending_edges: Vec::with_capacity(8),
             monotone_tessellators: IdVec::with_capacity(16),
             intersections: Vec::with_capacity(8),
             current_position: TessPoint::new(FixedPoint32::min_val(), FixedPoint32::min_val()),
+            sweep_cursor: (0, 0),
+            incremental: false,
             error: None,
             options: FillOptions::DEFAULT,
             log: false,
             tess_pool: Vec::with_capacity(8),
+            record_polygons: false,
+            polygons: Vec::new(),
+            polygon_ends: Vec::new(),
 
             #[cfg(feature="debugger")]
             debugger: None,
@@ -341,20 +438,25 @@ impl FillTessellator {
         options: &FillOptions,
         output: &mut dyn GeometryBuilder<Vertex>,
     ) -> TessellationResult {
-        if options.fill_rule != FillRule::EvenOdd {
-            println!("warning: Fill rule {:?} is not supported yet.", options.fill_rule);
-            match options.on_error {
-                OnError::Stop => { return Err(TessellationError::UnsupportedParamater); }
-                OnError::Panic => { panic!("Unsupported fill rule"); }
-                OnError::Recover => {}
-            }
-        }
+        self.check_options(options)?;
 
         self.options = *options;
 
+        if options.robust {
+            return self.tessellate_robust(events, output);
+        }
+
         self.begin_tessellation(output);
 
-        self.tessellator_loop(events, output);
+        if let Some(max_edge_length) = options.max_edge_length {
+            let mut subdivider = EdgeSubdivider::new(max_edge_length, output);
+            self.tessellator_loop(events, &mut subdivider);
+            if let Some(err) = subdivider.error() {
+                self.builder_error(err);
+            }
+        } else {
+            self.tessellator_loop(events, output);
+        }
 
         let mut error = None;
         swap(&mut error, &mut self.error);
@@ -370,6 +472,301 @@ impl FillTessellator {
         Ok(res)
     }
 
+    /// Start an incremental tessellation of a path.
+    ///
+    /// This only builds the events and calls `begin_geometry` on the output. The actual
+    /// work is done by `resume`, which sweeps through the path until its budget runs out
+    /// and can be called again later to continue from where it stopped. The triangles
+    /// are added to the output as soon as they are generated, so the geometry of the
+    /// parts of the path above the sweep line is available before the end.
+    ///
+    /// The same output must be passed to `resume` until it returns `FillStatus::Finished`
+    /// or an error, or until the tessellation is cancelled with `cancel`. The tessellator
+    /// can't be used for anything else in the mean time.
+    ///
+    /// The `robust` and `max_edge_length` options are not supported in this mode.
+    pub fn begin_incremental<Iter>(
+        &mut self,
+        it: Iter,
+        options: &FillOptions,
+        output: &mut dyn GeometryBuilder<Vertex>,
+    ) -> Result<(), TessellationError>
+    where
+        Iter: IntoIterator<Item = PathEvent>,
+    {
+        assert!(!self.incremental, "An incremental tessellation is already in progress.");
+
+        self.check_options(options)?;
+        if options.robust || options.max_edge_length.is_some() {
+            return Err(TessellationError::UnsupportedParamater);
+        }
+
+        self.options = *options;
+
+        self.events.clear();
+        self.events.set_path(options.tolerance, it.into_iter());
+        if let Some(rect) = options.clip_rect {
+            let mut clipped = replace(&mut self.clipped_events, FillEvents::new());
+            clipped.set_clipped(&self.events, &rect, options.fill_rule);
+            self.clipped_events = clipped;
+        }
+
+        self.begin_tessellation(output);
+        self.begin_sweep();
+        self.incremental = true;
+
+        Ok(())
+    }
+
+    /// Continue the incremental tessellation started with `begin_incremental`.
+    ///
+    /// Returns `FillStatus::InProgress` if the budget ran out before the end of the path.
+    /// When the tessellation is finished or fails, `end_geometry` or `abort_geometry` is
+    /// called on the output and the tessellator can be used again.
+    pub fn resume(
+        &mut self,
+        budget: FillBudget,
+        output: &mut dyn GeometryBuilder<Vertex>,
+    ) -> Result<FillStatus, TessellationError> {
+        assert!(self.incremental, "No incremental tessellation in progress.");
+
+        let clipped = self.options.clip_rect.is_some();
+        let events = if clipped {
+            replace(&mut self.clipped_events, FillEvents::new())
+        } else {
+            replace(&mut self.events, FillEvents::new())
+        };
+
+        let finished = self.sweep_with_budget(&events, output, budget);
+
+        if clipped {
+            self.clipped_events = events;
+        } else {
+            self.events = events;
+        }
+
+        if let Some(err) = self.error.take() {
+            output.abort_geometry();
+            self.reset();
+            self.incremental = false;
+            return Err(err);
+        }
+
+        if !finished {
+            return Ok(FillStatus::InProgress);
+        }
+
+        self.incremental = false;
+
+        Ok(FillStatus::Finished(self.end_tessellation(output)))
+    }
+
+    /// Cancel the incremental tessellation in progress, if any, and call `abort_geometry`
+    /// on its output.
+    pub fn cancel(&mut self, output: &mut dyn GeometryBuilder<Vertex>) {
+        if !self.incremental {
+            return;
+        }
+
+        output.abort_geometry();
+        self.reset();
+        self.incremental = false;
+    }
+
+    /// Compute the tessellation of many paths into a single geometry.
+    ///
+    /// The paths are tessellated independently from each other (overlapping paths don't
+    /// affect each other's fill), but the whole batch is produced between a single pair of
+    /// `begin_geometry` and `end_geometry` calls and the internal buffers of the tessellator
+    /// are reused from one path to the next.
+    ///
+    /// `output.set_path_id` is called with the id of each path before its vertices and
+    /// triangles are added. If an error occurs, the geometry of the whole batch is aborted.
+    pub fn tessellate_batch<Paths, Events>(
+        &mut self,
+        paths: Paths,
+        options: &FillOptions,
+        output: &mut dyn BatchGeometryBuilder<Vertex>,
+    ) -> TessellationResult
+    where
+        Paths: IntoIterator<Item = (PathId, Events)>,
+        Events: IntoIterator<Item = PathEvent>,
+    {
+        self.check_options(options)?;
+
+        output.begin_geometry();
+
+        let mut events = replace(&mut self.events, FillEvents::new());
+        let mut result = Ok(());
+        for (id, path) in paths {
+            output.set_path_id(id);
+            events.set_path(options.tolerance, path.into_iter());
+            if let Err(e) = self.tessellate_events(&events, options, &mut BatchOutput { output }) {
+                result = Err(e);
+                break;
+            }
+        }
+        self.events = events;
+
+        if let Err(e) = result {
+            output.abort_geometry();
+            return Err(e);
+        }
+
+        Ok(output.end_geometry())
+    }
+
+    /// Compute the tessellation of overlapping paths, removing the parts that are hidden.
+    ///
+    /// The paths are provided in z-order, from the bottom-most to the top-most. The output is
+    /// a planar map: each triangle belongs to the top-most path that covers it and the
+    /// parts of the paths that are covered by other paths are not tessellated, so that the
+    /// triangles don't overlap. This is useful to render opaque shapes without overdraw.
+    ///
+    /// `output.set_path_id` is called once per visible path, before its vertices and triangles
+    /// are added. Vertices are not shared between paths.
+    ///
+    /// The outline of the visible part of each path is tessellated like a regular fill, so
+    /// the other options are supported. The intersections are always handled like in robust
+    /// mode. The vertices that are only on the edges of other paths have no source.
+    pub fn tessellate_planar_map<Paths, Events>(
+        &mut self,
+        paths: Paths,
+        options: &FillOptions,
+        output: &mut dyn BatchGeometryBuilder<Vertex>,
+    ) -> TessellationResult
+    where
+        Paths: IntoIterator<Item = (PathId, Events)>,
+        Events: IntoIterator<Item = PathEvent>,
+    {
+        let mut map = PlanarMap::new();
+        for (id, path) in paths {
+            map.add_path(id, path.into_iter(), options.tolerance);
+        }
+
+        // The edges of the outlines only touch at their endpoints and each of them separates
+        // the inside and the outside of the visible part.
+        let outline_options = FillOptions {
+            fill_rule: FillRule::EvenOdd,
+            assume_no_intersections: true,
+            robust: false,
+            ..*options
+        };
+
+        output.begin_geometry();
+
+        for (id, outline) in &map.build(options.fill_rule) {
+            output.set_path_id(*id);
+            if let Err(e) = self.tessellate_events(outline, &outline_options, &mut BatchOutput { output }) {
+                output.abort_geometry();
+                return Err(e);
+            }
+        }
+
+        Ok(output.end_geometry())
+    }
+
+    /// Compute the tessellation of a path and interpolate its custom attributes on the
+    /// generated vertices.
+    ///
+    /// The attributes are written in `attributes`, `path.num_attributes()` values per
+    /// vertex, at the offset given by the `VertexId` that the output returns for each vertex.
+    /// Vertices at intersections get the average of the attributes of both edges.
+    ///
+    /// The `max_edge_length` option is not supported with this output.
+    pub fn tessellate_path_with_attributes(
+        &mut self,
+        path: PathSlice,
+        options: &FillOptions,
+        output: &mut dyn GeometryBuilder<Vertex>,
+        attributes: &mut Vec<f32>,
+    ) -> TessellationResult {
+        if options.max_edge_length.is_some() {
+            return Err(TessellationError::UnsupportedParamater);
+        }
+
+        let mut interpolator = AttributeInterpolator::new(path, output, attributes);
+
+        self.tessellate_path(path.iter(), options, &mut interpolator)
+    }
+
+    /// Compute the tessellation from a path iterator and produce polygons instead of triangles.
+    ///
+    /// Depending on the `mode`, the output receives the monotone polygons that the
+    /// tessellator would otherwise triangulate, or convex polygons obtained by merging
+    /// the triangles of the tessellation.
+    ///
+    /// The `max_edge_length` option is not supported with this output and is ignored.
+    pub fn tessellate_path_to_polygons<Iter>(
+        &mut self,
+        it: Iter,
+        options: &FillOptions,
+        mode: PolygonMode,
+        output: &mut dyn PolygonGeometryBuilder<Vertex>,
+    ) -> TessellationResult
+    where
+        Iter: IntoIterator<Item = PathEvent>,
+    {
+        let mut events = replace(&mut self.events, FillEvents::new());
+        events.clear();
+        events.set_path(options.tolerance, it.into_iter());
+        let result = self.tessellate_events_to_polygons(&events, options, mode, output);
+        self.events = events;
+
+        result
+    }
+
+    /// Compute the polygons from pre-sorted events.
+    ///
+    /// See `tessellate_path_to_polygons`.
+    pub fn tessellate_events_to_polygons(
+        &mut self,
+        events: &FillEvents,
+        options: &FillOptions,
+        mode: PolygonMode,
+        output: &mut dyn PolygonGeometryBuilder<Vertex>,
+    ) -> TessellationResult {
+        self.check_options(options)?;
+
+        self.options = *options;
+        self.options.max_edge_length = None;
+        self.record_polygons = mode == PolygonMode::Monotone;
+
+        let mut collector = PolygonCollector::new(output, mode == PolygonMode::Convex);
+
+        self.begin_tessellation(&mut collector);
+
+        self.tessellator_loop(events, &mut collector);
+
+        self.record_polygons = false;
+
+        let mut error = None;
+        swap(&mut error, &mut self.error);
+        if let Some(err) = error {
+            collector.abort_geometry();
+            self.reset();
+            return Err(err);
+        }
+
+        match mode {
+            PolygonMode::Monotone => {
+                let mut start = 0;
+                for &end in &self.polygon_ends {
+                    collector.output.add_polygon(&self.polygons[start..end]);
+                    start = end;
+                }
+            }
+            PolygonMode::Convex => {
+                collector.flush_convex_polygons();
+            }
+        }
+
+        let res = self.end_tessellation(&mut collector);
+        self.reset();
+
+        Ok(res)
+    }
+
     /// Enable some verbose logging during the tessellation, for debugging purposes.
     pub fn enable_logging(&mut self) { self.log = true; }
 
@@ -382,13 +779,30 @@ impl FillTessellator {
         self.options.on_error == OnError::Panic
     }
 
+    fn check_options(&self, options: &FillOptions) -> Result<(), TessellationError> {
+        if options.fill_rule != FillRule::EvenOdd {
+            println!("warning: Fill rule {:?} is not supported yet.", options.fill_rule);
+            match options.on_error {
+                OnError::Stop => { return Err(TessellationError::UnsupportedParamater); }
+                OnError::Panic => { panic!("Unsupported fill rule"); }
+                OnError::Recover => {}
+            }
+        }
+
+        Ok(())
+    }
+
     fn reset(&mut self) {
         self.active_edges.clear();
+        self.intersections.clear();
         self.monotone_tessellators.clear();
         self.pending_edges.clear();
+        self.polygons.clear();
+        self.polygon_ends.clear();
     }
 
     fn begin_tessellation(&mut self, output: &mut dyn GeometryBuilder<Vertex>) {
+        debug_assert!(!self.incremental);
         debug_assert!(self.active_edges.is_empty());
         debug_assert!(self.monotone_tessellators.is_empty());
         debug_assert!(self.pending_edges.is_empty());
@@ -408,20 +822,108 @@ impl FillTessellator {
         output.end_geometry()
     }
 
+    // Snap the events to a grid and resolve their intersections before the sweep.
+    //
+    // The geometry is recorded and only sent to the output once the sweep has succeeded.
+    fn tessellate_robust(
+        &mut self,
+        events: &FillEvents,
+        output: &mut dyn GeometryBuilder<Vertex>,
+    ) -> TessellationResult {
+        let mut robust_events = replace(&mut self.robust_events, FillEvents::new());
+        let mut geometry = replace(&mut self.robust_geometry, RecordedGeometry::default());
+        geometry.clear();
+
+        if let Some(rect) = self.options.clip_rect {
+            let mut clipped = replace(&mut self.clipped_events, FillEvents::new());
+            clipped.set_clipped(events, &rect, self.options.fill_rule);
+            robust_events.set_robust(&clipped, self.options.fill_rule);
+            self.clipped_events = clipped;
+        } else {
+            robust_events.set_robust(events, self.options.fill_rule);
+        }
+
+        self.options.assume_no_intersections = true;
+        self.sweep(&robust_events, &mut geometry);
+        self.reset();
+
+        let mut result = match self.error.take() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        };
+
+        if result.is_ok() {
+            output.begin_geometry();
+            result = if let Some(max_edge_length) = self.options.max_edge_length {
+                let mut subdivider = EdgeSubdivider::new(max_edge_length, output);
+                geometry.replay(&mut subdivider).and_then(|_| match subdivider.error() {
+                    Some(error) => Err(error),
+                    None => Ok(()),
+                }).map_err(Into::into)
+            } else {
+                geometry.replay(output).map_err(Into::into)
+            };
+            if result.is_err() {
+                output.abort_geometry();
+            }
+        }
+
+        self.robust_events = robust_events;
+        self.robust_geometry = geometry;
+
+        result.map(|_| output.end_geometry())
+    }
+
     fn tessellator_loop(
         &mut self,
         events: &FillEvents,
         output: &mut dyn GeometryBuilder<Vertex>,
     ) {
+        if let Some(rect) = self.options.clip_rect {
+            let mut clipped = replace(&mut self.clipped_events, FillEvents::new());
+            clipped.set_clipped(events, &rect, self.options.fill_rule);
+            self.sweep(&clipped, output);
+            self.clipped_events = clipped;
+        } else {
+            self.sweep(events, output);
+        }
+    }
+
+    fn sweep(
+        &mut self,
+        events: &FillEvents,
+        output: &mut dyn GeometryBuilder<Vertex>,
+    ) {
+        self.begin_sweep();
+        self.sweep_with_budget(events, output, FillBudget::Unlimited);
+    }
+
+    fn begin_sweep(&mut self) {
         self.current_position = TessPoint::new(FixedPoint32::min_val(), FixedPoint32::min_val());
+        self.sweep_cursor = (0, 0);
+    }
 
-        let mut edge_iter = events.edges.iter();
-        let mut vertex_iter = events.vertices.iter();
-        let mut next_edge = edge_iter.next();
-        let mut next_vertex = vertex_iter.next();
+    // Runs the sweep from where it stopped last time, until the end of the events
+    // or until the budget runs out.
+    //
+    // Returns true if the sweep reached the end of the events.
+    fn sweep_with_budget(
+        &mut self,
+        events: &FillEvents,
+        output: &mut dyn GeometryBuilder<Vertex>,
+        budget: FillBudget,
+    ) -> bool {
+        #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+        let deadline = match budget {
+            FillBudget::Time(duration) => Some(Instant::now() + duration),
+            _ => None,
+        };
+        let mut num_positions = 0;
+
+        let (mut edge_idx, mut vertex_idx) = self.sweep_cursor;
         loop {
             if self.error.is_some() && self.options.on_error != OnError::Recover {
-                return;
+                return true;
             }
 
             let mut next_position = None;
@@ -432,9 +934,9 @@ impl FillTessellator {
             // or merge events), and the list of intersections that we find along the way.
 
             // Look at the sorted list of edges.
-            while let Some(edge) = next_edge {
+            while let Some(edge) = events.edges.get(edge_idx) {
                 if edge.upper == self.current_position {
-                    next_edge = edge_iter.next();
+                    edge_idx += 1;
                     if edge.lower == self.current_position {
                         continue;
                     }
@@ -444,6 +946,7 @@ impl FillTessellator {
                         lower: edge.lower,
                         angle,
                         winding: edge.winding,
+                        source: edge.source,
                     });
                     tess_log!(self, " edge at {:?} -> {:?} (angle={:?})", edge.upper, edge.lower, angle);
 
@@ -456,9 +959,9 @@ impl FillTessellator {
             }
 
             // Look at the sorted list of vertices.
-            while let Some(vertex) = next_vertex {
+            while let Some(vertex) = events.vertices.get(vertex_idx) {
                 if *vertex == self.current_position {
-                    next_vertex = vertex_iter.next();
+                    vertex_idx += 1;
                     pending_events = true;
                     tess_log!(self, " vertex at {:?}", self.current_position);
                     continue;
@@ -481,6 +984,7 @@ impl FillTessellator {
                                 lower: inter.lower,
                                 angle: edge_angle(inter.lower - self.current_position),
                                 winding: inter.winding,
+                                source: inter.source,
                             }
                         );
                     }
@@ -518,7 +1022,22 @@ impl FillTessellator {
                 self.current_position = position;
                 tess_log!(self, "\n\n -- current_position is now {:?}", position);
             } else {
-                return;
+                return true;
+            }
+
+            // The events at the new position haven't been consumed yet, so this
+            // is where the sweep can stop and resume later.
+            num_positions += 1;
+            let out_of_budget = match budget {
+                FillBudget::Events(max) => num_positions >= max,
+                // Checking the time is not free, so only do it once in a while.
+                #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+                FillBudget::Time(..) => num_positions % 16 == 0 && Instant::now() >= deadline.unwrap(),
+                FillBudget::Unlimited => false,
+            };
+            if out_of_budget {
+                self.sweep_cursor = (edge_idx, vertex_idx);
+                return false;
             }
         }
     }
@@ -582,6 +1101,7 @@ impl FillTessellator {
         &mut self,
         prev: &TessPoint,
         next: &TessPoint,
+        source: VertexSource,
         output: &mut dyn GeometryBuilder<Vertex>
     ) -> Result<VertexId, GeometryBuilderError> {
         let position = to_f32_point(self.current_position);
@@ -592,7 +1112,44 @@ impl FillTessellator {
             (next - position).normalize(),
         );
 
-        output.add_vertex(Vertex { position, normal })
+        output.add_vertex(Vertex { position, normal, source })
+    }
+
+    // Find out where the current vertex comes from by looking at the edges
+    // that end or start at the current position.
+    fn vertex_source(&self, first_edge_above: ActiveEdgeId) -> VertexSource {
+        let above = self.active_edges.iter().skip(first_edge_above.handle).filter(|edge| {
+            !edge.merge && !edge.source.is_clip() && edge.points.lower == self.current_position
+        }).map(|edge| (edge.source.lower_position(), edge.source.lower_is_cut));
+
+        let below = self.pending_edges.iter().filter(|edge| !edge.source.is_clip()).map(|edge| {
+            (edge.source.upper_position(), edge.source.upper_is_cut)
+        });
+
+        // Prefer positions that come directly from the path and fall back
+        // to intersections.
+        let mut cut = (None, None);
+        for (position, is_cut) in above.chain(below) {
+            if !is_cut {
+                return if position.t == 0.0 || position.t == 1.0 {
+                    VertexSource::Endpoint(position)
+                } else {
+                    VertexSource::Curve(position)
+                };
+            }
+
+            match cut {
+                (None, _) => { cut.0 = Some(position); }
+                (Some(first), None) if first != position => { cut.1 = Some(position); }
+                _ => {}
+            }
+        }
+
+        match cut {
+            (Some(a), Some(b)) => VertexSource::Intersection(a, b),
+            (Some(a), None) => VertexSource::Curve(a),
+            _ => VertexSource::Unknown,
+        }
     }
 
     fn process_vertex(
@@ -625,10 +1182,17 @@ impl FillTessellator {
         // The index of the next pending edge to be processed.
         let mut pending_edge_id = 0;
 
+        let source = self.vertex_source(first_edge_above);
+
         // Go through all pending edges, sort them and handle pairs of overlapping edges.
         // Doing this here avoids some potentially tricky cases with intersections
         // later.
-        prepare_pending_edges(&mut self.pending_edges, &mut self.intersections);
+        prepare_pending_edges(
+            &mut self.pending_edges,
+            &mut self.intersections,
+            self.current_position,
+            self.options.robust,
+        );
 
         self.log_sl(first_edge_above);
         tess_log!(self, "{:?}", point_type);
@@ -640,6 +1204,7 @@ impl FillTessellator {
                 Vertex {
                     position: vector_position,
                     normal: vector(0.0, 0.0),
+                    source,
                 }
             )?
         } else {
@@ -685,7 +1250,7 @@ impl FillTessellator {
                 if self.options.compute_normals {
                     let vertex_above = self.active_edges[above_idx].points.upper;
                     let edge_to = self.pending_edges[0].lower;
-                    vertex_id = self.add_vertex_with_normal(&edge_to, &vertex_above, output)?;
+                    vertex_id = self.add_vertex_with_normal(&edge_to, &vertex_above, source, output)?;
                 }
 
                 above_idx = self.resolve_merge_vertices_right(above_idx, vertex_id, output);
@@ -718,7 +1283,7 @@ impl FillTessellator {
             if self.options.compute_normals {
                 let left = self.active_edges[above_idx].points.upper;
                 let right = self.active_edges[above_idx+1].points.upper;
-                vertex_id = self.add_vertex_with_normal(&left, &right, output)?;
+                vertex_id = self.add_vertex_with_normal(&left, &right, source, output)?;
             }
 
             self.resolve_merge_vertices(above_idx, vertex_id, output);
@@ -740,7 +1305,7 @@ impl FillTessellator {
             if self.options.compute_normals {
                 let left = self.active_edges[first_edge_above].points.upper;
                 let right = self.active_edges[first_edge_above+1].points.upper;
-                vertex_id = self.add_vertex_with_normal(&right, &left, output)?;
+                vertex_id = self.add_vertex_with_normal(&right, &left, source, output)?;
             }
 
             self.merge_event(vertex_id, first_edge_above, output);
@@ -759,7 +1324,7 @@ impl FillTessellator {
             if self.options.compute_normals {
                 let vertex_above = self.active_edges[above_idx].points.upper;
                 let vertex_below = self.pending_edges[vertex_below_id].lower;
-                vertex_id = self.add_vertex_with_normal(&vertex_above, &vertex_below, output)?;
+                vertex_id = self.add_vertex_with_normal(&vertex_above, &vertex_below, source, output)?;
             }
             self.resolve_merge_vertices(above_idx, vertex_id, output);
             self.insert_edge(above_idx, vertex_below_id, vertex_id);
@@ -797,15 +1362,21 @@ impl FillTessellator {
                 if self.options.compute_normals {
                     let left_vertex = self.pending_edges[left_idx].lower;
                     let right_vertex = self.pending_edges[right_idx].lower;
-                    vertex_id = self.add_vertex_with_normal(&left_vertex, &right_vertex, output)?;
+                    vertex_id = self.add_vertex_with_normal(&left_vertex, &right_vertex, source, output)?;
                 }
 
+                let below_merge_vertex = self.active_edges[above_idx].merge;
                 self.split_event(above_idx, left_idx, right_idx, vertex_id, output);
 
                 num_pending_edges -= 2;
                 // split_event inserts two active edges at the current offset
-                // and we need skip them.
-                above_idx = above_idx + 2;
+                // and we need skip them. Below a merge vertex, they replace the
+                // two merge edges instead, and the remaining edges go between them.
+                if !below_merge_vertex {
+                    above_idx = above_idx + 2;
+                }
+                // The remaining pending edges are between the left and right ones.
+                pending_edge_id = left_idx + 1;
             }
 
             while num_pending_edges >= 2 {
@@ -821,7 +1392,7 @@ impl FillTessellator {
                 if self.options.compute_normals {
                     let left = self.pending_edges[pending_edge_id].lower;
                     let right = self.pending_edges[pending_edge_id + 1].lower;
-                    vertex_id = self.add_vertex_with_normal(&right, &left, output)?;
+                    vertex_id = self.add_vertex_with_normal(&right, &left, source, output)?;
                 }
 
                 self.start_event(above_idx, vertex_id, pending_edge_id);
@@ -873,6 +1444,7 @@ impl FillTessellator {
                 compare_edge_against_position(
                     &active_edge.points,
                     self.current_position,
+                    self.options.robust,
                     &mut on_edge,
                     &mut edge_after_point,
                 );
@@ -900,12 +1472,15 @@ impl FillTessellator {
                 // geometry that can lead to this scenario.
 
                 // Split the edge.
+                let (above, below) = active_edge.source.split(&active_edge.points, self.current_position);
                 self.pending_edges.push(PendingEdge {
                     lower: active_edge.points.lower,
                     angle: edge_angle(active_edge.points.lower - self.current_position),
                     winding: active_edge.winding,
+                    source: below,
                 });
                 active_edge.points.lower = self.current_position;
+                active_edge.source = above;
             }
 
             if edge_after_point {
@@ -1224,26 +1799,34 @@ impl FillTessellator {
             new_edge.lower = intersection;
         }
 
+        let (new_edge_above, new_edge_below) = new_edge.source.split(&original_edge, intersection);
         self.pending_edges[new_edge_idx].lower = new_edge.lower;
+        self.pending_edges[new_edge_idx].source = new_edge_above;
 
         let active_edge_lower;
         let active_edge_winding;
+        let active_edge_below;
         {
             let active_edge = &mut self.active_edges[edge_idx];
+            let (above, below) = active_edge.source.split(&active_edge.points, intersection);
             active_edge_lower = active_edge.points.lower;
             active_edge_winding = active_edge.winding;
+            active_edge_below = below;
             active_edge.points.lower = intersection;
+            active_edge.source = above;
         }
 
         self.intersections.push(OrientedEdge::with_winding(
             intersection,
             original_edge.lower,
-            new_edge.winding
+            new_edge.winding,
+            new_edge_below,
         ));
         self.intersections.push(OrientedEdge::with_winding(
             intersection,
             active_edge_lower,
-            active_edge_winding
+            active_edge_winding,
+            active_edge_below,
         ));
 
         #[cfg(feature="debugger")] {
@@ -1270,6 +1853,9 @@ impl FillTessellator {
             let tess = &mut self.monotone_tessellators[span_idx];
             tess.end(vector_position, id);
             tess.flush(output);
+            if self.record_polygons && tess.polygon(&mut self.polygons) {
+                self.polygon_ends.push(self.polygons.len());
+            }
         }
 
         self.active_edges.remove(edge_idx + 1);
@@ -1433,7 +2019,7 @@ fn span_for_edge(edge: ActiveEdgeId) -> SpanId {
     SpanId::new(edge.handle / 2)
 }
 
-fn compare_positions(a: TessPoint, b: TessPoint) -> Ordering {
+pub(crate) fn compare_positions(a: TessPoint, b: TessPoint) -> Ordering {
     if a.y > b.y {
         return Ordering::Greater;
     }
@@ -1455,10 +2041,13 @@ fn compare_positions(a: TessPoint, b: TessPoint) -> Ordering {
 fn compare_edge_against_position(
     edge: &Edge,
     position: TessPoint,
+    exact: bool,
     on_edge: &mut bool,
     edge_passed_point: &mut bool,
 ) {
-    let threshold = FixedPoint32::epsilon() * 50;
+    // In robust mode the positions are snapped to a grid that is coarser than the threshold,
+    // so we use exact predicates instead.
+    let threshold = if exact { FixedPoint32::zero() } else { FixedPoint32::epsilon() * 50 };
 
     // This early-out test gives a noticeable performance improvement.
     let (min, max) = edge.upper.x.min_max(edge.lower.x);
@@ -1482,6 +2071,13 @@ fn compare_edge_against_position(
         return;
     }
 
+    if exact {
+        let orientation = orientation(edge.upper, edge.lower, position);
+        *on_edge = orientation == 0;
+        *edge_passed_point = orientation > 0;
+        return;
+    }
+
     // Intersect the edge with the horizontal line passing at the current position.
     let dy = position.y - edge.upper.y;
     let x = edge.upper.x + dy.mul_div(v.x, v.y);
@@ -1494,7 +2090,22 @@ fn compare_edge_against_position(
 fn prepare_pending_edges(
     pending_edges: &mut Vec<PendingEdge>,
     intersections: &mut Vec<OrientedEdge>,
+    current_position: TessPoint,
+    exact: bool,
 ) {
+    if exact {
+        // In robust mode the edges that overlap were merged before the sweep, and the
+        // angles are not precise enough to sort edges that are almost collinear.
+        pending_edges.sort_by(|a, b| {
+            match orientation(current_position, a.lower, b.lower) {
+                0 => compare_positions(a.lower, b.lower),
+                o if o < 0 => Ordering::Less,
+                _ => Ordering::Greater,
+            }
+        });
+        return;
+    }
+
     pending_edges.sort_by(|a, b| a.angle.partial_cmp(&b.angle).unwrap_or(Ordering::Equal));
 
     if pending_edges.len() >= 2 {
@@ -1513,9 +2124,19 @@ fn prepare_pending_edges(
             if (edge_a.angle - edge_b.angle).abs() < threshold && doesnt_affect_winding {
                 to_remove.push(i);
                 if edge_a.lower != edge_b.lower {
-                    let furthest = if is_after(edge_a.lower, edge_b.lower) { i } else { i + 1 };
-                    let winding = pending_edges[furthest].winding;
-                    intersections.push(OrientedEdge::with_winding(edge_a.lower, edge_b.lower, winding));
+                    let (furthest, nearest) = if is_after(edge_a.lower, edge_b.lower) {
+                        (edge_a, edge_b)
+                    } else {
+                        (edge_b, edge_a)
+                    };
+                    let furthest_edge = Edge { upper: current_position, lower: furthest.lower };
+                    let (_, source) = furthest.source.split(&furthest_edge, nearest.lower);
+                    intersections.push(OrientedEdge::with_winding(
+                        nearest.lower,
+                        furthest.lower,
+                        furthest.winding,
+                        source,
+                    ));
                 }
                 i += 2;
             } else {
@@ -1536,6 +2157,7 @@ struct ActiveEdge {
     upper_id: VertexId,
     winding: i16,
     merge: bool,
+    source: EdgeSource,
 }
 
 impl ActiveEdge {
@@ -1578,8 +2200,8 @@ fn edge_angle(v: TessVector) -> f32 {
 
 /// A sequence of edges sorted from top to bottom, to be used as the tessellator's input.
 pub struct FillEvents {
-    edges: Vec<OrientedEdge>,
-    vertices: Vec<TessPoint>,
+    pub(crate) edges: Vec<OrientedEdge>,
+    pub(crate) vertices: Vec<TessPoint>,
 }
 
 impl FillEvents {
@@ -1603,6 +2225,26 @@ impl FillEvents {
     }
 
     pub fn set_path<Iter: Iterator<Item = PathEvent>>(&mut self, tolerance: f32, it: Iter) {
+        self.set_path_impl(tolerance, it, None);
+    }
+
+    // Same as `set_path` but only keeps the edges that overlap a vertical range, which
+    // avoids sorting the other ones.
+    pub(crate) fn set_path_in_y_range<Iter: Iterator<Item = PathEvent>>(
+        &mut self,
+        tolerance: f32,
+        it: Iter,
+        y_range: Range<f32>,
+    ) {
+        self.set_path_impl(tolerance, it, Some(y_range));
+    }
+
+    fn set_path_impl<Iter: Iterator<Item = PathEvent>>(
+        &mut self,
+        tolerance: f32,
+        it: Iter,
+        y_range: Option<Range<f32>>,
+    ) {
         self.clear();
         let mut tmp = FillEvents::new();
         swap(self, &mut tmp);
@@ -1611,7 +2253,10 @@ impl FillEvents {
         builder.recycle(tmp);
         builder.tolerance = tolerance;
 
-        for evt in it {
+        let mut count = 0;
+        for (idx, evt) in it.enumerate() {
+            builder.event = idx as u32;
+            count = idx as u32 + 1;
             match evt {
                 PathEvent::MoveTo(to) => {
                     builder.move_to(to);
@@ -1631,10 +2276,493 @@ impl FillEvents {
             }
         }
 
+        // The edge that implicitly closes the last sub-path.
+        builder.event = count;
+        if let Some(range) = y_range {
+            builder.close();
+            let (min, max) = (fixed(range.start), fixed(range.end));
+            builder.edges.retain(|edge| edge.lower.y >= min && edge.upper.y <= max);
+            builder.vertices.retain(|vertex| vertex.y >= min && vertex.y <= max);
+        }
         swap(self, &mut builder.build());
     }
 }
 
+impl FillEvents {
+    // Set the edges of `events` clipped to a rectangle.
+    //
+    // Edges are split where they cross the lines that support the sides of the rectangle.
+    // The parts that are outside of the rectangle are projected onto its closest side, which
+    // preserves the winding number of every point in the rectangle and keeps the outline
+    // closed. Parts that end up in a corner collapse into a point and are discarded.
+    // Projected edges are then merged so that their number only depends on the number
+    // of times the path crosses the sides of the rectangle.
+    fn set_clipped(&mut self, events: &FillEvents, rect: &Rect, fill_rule: FillRule) {
+        self.clear();
+
+        let min = to_internal(rect.min());
+        let max = to_internal(rect.max());
+        let clamp = |p: TessPoint| {
+            TessPoint::new(
+                if p.x < min.x { min.x } else if p.x > max.x { max.x } else { p.x },
+                if p.y < min.y { min.y } else if p.y > max.y { max.y } else { p.y },
+            )
+        };
+
+        let mut left = Vec::new();
+        let mut right = Vec::new();
+        let mut top = Vec::new();
+        let mut bottom = Vec::new();
+
+        for edge in &events.edges {
+            let mut cuts = [edge.lower; 4];
+            let mut num_cuts = 0;
+            for &y in &[min.y, max.y] {
+                if edge.upper.y < y && edge.lower.y > y {
+                    cuts[num_cuts] = TessPoint::new(x_at_y(edge, y), y);
+                    num_cuts += 1;
+                }
+            }
+            for &x in &[min.x, max.x] {
+                if (edge.upper.x < x && edge.lower.x > x) || (edge.upper.x > x && edge.lower.x < x) {
+                    cuts[num_cuts] = TessPoint::new(x, y_at_x(edge, x));
+                    num_cuts += 1;
+                }
+            }
+            // Sort the cuts along the edge.
+            let d = edge.lower - edge.upper;
+            let key = |p: &TessPoint| {
+                let v = *p - edge.upper;
+                v.x.raw() as i64 * d.x.raw() as i64 + v.y.raw() as i64 * d.y.raw() as i64
+            };
+            cuts[..num_cuts].sort_by_key(key);
+
+            let mut upper = edge.upper;
+            let mut source = edge.source;
+            for &lower in cuts[..num_cuts].iter().chain(Some(&edge.lower)) {
+                if lower == upper {
+                    continue;
+                }
+                let (above, below) = if lower == edge.lower {
+                    (source, source)
+                } else {
+                    source.split(&Edge { upper, lower: edge.lower }, lower)
+                };
+
+                let mid_x = (upper.x.raw() as i64 + lower.x.raw() as i64) / 2;
+                let mid_y = (upper.y.raw() as i64 + lower.y.raw() as i64) / 2;
+                let is_left = mid_x < min.x.raw() as i64;
+                let is_right = mid_x > max.x.raw() as i64;
+                let is_above = mid_y < min.y.raw() as i64;
+                let is_below = mid_y > max.y.raw() as i64;
+
+                let (a, b) = (clamp(upper), clamp(lower));
+                // The direction of the piece along the x axis, following the path.
+                let x_winding = if b.x > a.x { edge.winding } else { -edge.winding };
+                let vertical = (a.y, b.y, edge.winding);
+                let horizontal = (a.x.min(b.x), a.x.max(b.x), x_winding);
+
+                if a == b || ((is_left || is_right) && (is_above || is_below)) {
+                    // Collapsed into a point.
+                } else if is_above || (a.y == b.y && a.y == min.y) {
+                    top.push(horizontal);
+                } else if is_below || (a.y == b.y && a.y == max.y) {
+                    bottom.push(horizontal);
+                } else if is_left || (a.x == b.x && a.x == min.x) {
+                    left.push(vertical);
+                } else if is_right || (a.x == b.x && a.x == max.x) {
+                    right.push(vertical);
+                } else {
+                    self.edges.push(OrientedEdge {
+                        upper,
+                        lower,
+                        winding: edge.winding,
+                        source: above,
+                    });
+                }
+
+                upper = lower;
+                source = below;
+            }
+        }
+
+        self.add_clip_edges(&mut left, fill_rule, |y| TessPoint::new(min.x, y));
+        self.add_clip_edges(&mut right, fill_rule, |y| TessPoint::new(max.x, y));
+        self.add_clip_edges(&mut top, fill_rule, |x| TessPoint::new(x, min.y));
+        self.add_clip_edges(&mut bottom, fill_rule, |x| TessPoint::new(x, max.y));
+
+        // Edges that end at the sides of the rectangle may not be followed by another edge.
+        for edge in &self.edges {
+            self.vertices.push(edge.lower);
+        }
+
+        self.edges.sort_by(|a, b| compare_positions(a.upper, b.upper));
+        self.vertices.sort_by(|a, b| compare_positions(*a, *b));
+        self.vertices.dedup();
+    }
+
+    // Merge the edges that were projected on a side of the clip rectangle.
+    //
+    // The projected edges are given as intervals along the side, and `position` maps a
+    // coordinate along the side to a point.
+    fn add_clip_edges(
+        &mut self,
+        edges: &mut Vec<(FixedPoint32, FixedPoint32, i16)>,
+        fill_rule: FillRule,
+        position: impl Fn(FixedPoint32) -> TessPoint,
+    ) {
+        let mut changes = Vec::with_capacity(edges.len() * 2);
+        for &(start, end, winding) in edges.iter() {
+            if start != end {
+                changes.push((start, winding));
+                changes.push((end, -winding));
+            }
+        }
+        changes.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+
+        let mut winding = 0;
+        let mut start = FixedPoint32::min_val();
+        let mut i = 0;
+        while i < changes.len() {
+            let v = changes[i].0;
+            let mut new_winding = winding;
+            while i < changes.len() && changes[i].0 == v {
+                new_winding += changes[i].1;
+                i += 1;
+            }
+
+            if new_winding != winding {
+                let visible = match fill_rule {
+                    FillRule::EvenOdd => winding % 2 != 0,
+                    FillRule::NonZero => winding != 0,
+                };
+                if visible {
+                    self.edges.push(OrientedEdge::with_winding(
+                        position(start),
+                        position(v),
+                        winding,
+                        EdgeSource::CLIP,
+                    ));
+                }
+                start = v;
+                winding = new_winding;
+            }
+        }
+
+        edges.clear();
+    }
+}
+
+impl FillEvents {
+    // Find the edges of `events` that overlap a horizontal band.
+    //
+    // The bands must be visited from top to bottom. `next` is the first edge that wasn't
+    // considered by the previous bands and `active` the edges that overlapped them. When
+    // this returns, `active` contains the edges that overlap the band, sorted like the
+    // edges of `events`.
+    pub(crate) fn find_band_edges(&self, band: &Rect, next: &mut usize, active: &mut Vec<usize>) {
+        let min_y = to_internal(band.min()).y;
+        let max_y = to_internal(band.max()).y;
+        while *next < self.edges.len() && self.edges[*next].upper.y <= max_y {
+            active.push(*next);
+            *next += 1;
+        }
+        active.retain(|&idx| self.edges[idx].lower.y >= min_y);
+    }
+
+    // Set the parts of some edges of `events` that are in a horizontal band.
+    //
+    // `edges` must contain all of the edges that overlap the band, see `find_band_edges`.
+    // The parts of the path above the band go from one point of its top side to another,
+    // so each edge that crosses the top side is cut and its upper part is replaced with a
+    // horizontal edge from the left of the band to the cut. Paired up, these edges cover
+    // the same intervals of the side as the parts of the path they replace, which preserves
+    // the winding number of every point in the band. The same goes for the bottom side.
+    // The rest of the path doesn't need to be visited.
+    pub(crate) fn set_band(&mut self, events: &FillEvents, edges: &[usize], band: &Rect) {
+        self.clear();
+
+        let min = to_internal(band.min());
+        let max = to_internal(band.max());
+
+        for &idx in edges {
+            let edge = &events.edges[idx];
+            let mut upper = edge.upper;
+            let mut lower = edge.lower;
+            let mut source = edge.source;
+
+            if edge.upper.y < min.y {
+                let cut = if edge.lower.y == min.y {
+                    edge.lower
+                } else {
+                    TessPoint::new(x_at_y(edge, min.y), min.y)
+                };
+                self.push_side_edge(cut, min.x, edge.winding);
+                if cut != edge.lower {
+                    source = source.split(&edge.edge(), cut).1;
+                }
+                upper = cut;
+            }
+
+            if edge.lower.y > max.y {
+                let cut = if edge.upper.y == max.y {
+                    edge.upper
+                } else {
+                    TessPoint::new(x_at_y(edge, max.y), max.y)
+                };
+                self.push_side_edge(cut, min.x, -edge.winding);
+                if cut != upper {
+                    source = source.split(&Edge { upper, lower }, cut).0;
+                }
+                lower = cut;
+            }
+
+            if upper != lower {
+                self.edges.push(OrientedEdge { upper, lower, winding: edge.winding, source });
+            }
+        }
+
+        for edge in &self.edges {
+            self.vertices.push(edge.lower);
+        }
+
+        self.edges.sort_by(|a, b| compare_positions(a.upper, b.upper));
+        self.vertices.sort_by(|a, b| compare_positions(*a, *b));
+        self.vertices.dedup();
+    }
+
+    // Add a horizontal edge between a point and a given x coordinate, with the winding of
+    // an edge going from the left to the point.
+    fn push_side_edge(&mut self, to: TessPoint, x: FixedPoint32, winding: i16) {
+        let from = TessPoint::new(x, to.y);
+        if from == to {
+            return;
+        }
+        self.edges.push(if from.x < to.x {
+            OrientedEdge { upper: from, lower: to, winding, source: EdgeSource::CLIP }
+        } else {
+            OrientedEdge { upper: to, lower: from, winding: -winding, source: EdgeSource::CLIP }
+        });
+    }
+}
+
+impl FillEvents {
+    // Set the edges of `events` snapped to the grid of the robust mode.
+    //
+    // Edges are split where they intersect (see `split_intersecting_edges`) and the ones
+    // that end up on top of each other are merged.
+    fn set_robust(&mut self, events: &FillEvents, fill_rule: FillRule) {
+        self.clear();
+        for edge in &events.edges {
+            push_snapped_edge(&mut self.edges, snap_to_grid(edge.upper), snap_to_grid(edge.lower), edge.winding, edge.source);
+        }
+
+        let mut tags = vec![(); self.edges.len()];
+        split_intersecting_edges(&mut self.edges, &mut tags);
+
+        // Merge the edges that overlap.
+        let mut edges = Vec::new();
+        self.edges.sort_by(|a, b| {
+            compare_positions(a.upper, b.upper).then_with(|| compare_positions(a.lower, b.lower))
+        });
+        swap(&mut self.edges, &mut edges);
+        let mut i = 0;
+        while i < edges.len() {
+            let mut merged = edges[i];
+            let mut count = 1;
+            i += 1;
+            while i < edges.len() && edges[i].upper == merged.upper && edges[i].lower == merged.lower {
+                merged.winding += edges[i].winding;
+                count += 1;
+                i += 1;
+            }
+
+            let visible = match fill_rule {
+                FillRule::EvenOdd => count % 2 == 1,
+                FillRule::NonZero => merged.winding != 0,
+            };
+            if visible {
+                if merged.winding == 0 {
+                    merged.winding = 1;
+                }
+                self.edges.push(merged);
+            }
+        }
+
+        for edge in &self.edges {
+            self.vertices.push(edge.lower);
+        }
+        self.vertices.sort_by(|a, b| compare_positions(*a, *b));
+        self.vertices.dedup();
+    }
+}
+
+// Split edges that are snapped to the grid of the robust mode where they intersect, so
+// that they only touch at their endpoints.
+//
+// This uses snap rounding: the intersections are rounded to the grid, and each edge is
+// routed through the center of every grid cell that contains an endpoint or an
+// intersection and that the edge passes through. Moving the edges this way can't create
+// new intersections, so unlike splitting the edges at the rounded intersections only, a
+// single pass is enough.
+//
+// `tags` has a value per edge, which is given to the pieces of the edge.
+pub(crate) fn split_intersecting_edges<T: Copy>(edges: &mut Vec<OrientedEdge>, tags: &mut Vec<T>) {
+    debug_assert_eq!(edges.len(), tags.len());
+
+    let mut order: Vec<usize> = (0..edges.len()).collect();
+    order.sort_by(|&a, &b| compare_positions(edges[a].upper, edges[b].upper));
+    let sorted_edges: Vec<OrientedEdge> = order.iter().map(|&idx| edges[idx]).collect();
+    let sorted_tags: Vec<T> = order.iter().map(|&idx| tags[idx]).collect();
+
+    // The centers of the grid cells that the edges are routed through.
+    let mut hot_pixels = Vec::with_capacity(sorted_edges.len() * 2);
+
+    // Sweep the edges from top to bottom, keeping the ones that overlap the current position
+    // vertically.
+    let mut active = Vec::new();
+    for (i, edge) in sorted_edges.iter().enumerate() {
+        hot_pixels.push(edge.upper);
+        hot_pixels.push(edge.lower);
+
+        let e1 = edge.edge();
+        active.retain(|&j: &usize| sorted_edges[j].lower.y >= e1.upper.y);
+        for &j in &active {
+            if let Some(position) = exact_segment_intersection(&sorted_edges[j].edge(), &e1) {
+                hot_pixels.push(position);
+            }
+        }
+        active.push(i);
+    }
+
+    hot_pixels.sort_by(|a, b| compare_positions(*a, *b));
+    hot_pixels.dedup();
+
+    edges.clear();
+    tags.clear();
+    let mut crossed = Vec::new();
+    for (edge, &tag) in sorted_edges.iter().zip(sorted_tags.iter()) {
+        // The endpoints are on the grid, so the edge can only pass through the cells whose
+        // center is between its endpoints vertically.
+        let first = hot_pixels
+            .binary_search_by(|p| if p.y < edge.upper.y { Ordering::Less } else { Ordering::Greater })
+            .unwrap_err();
+        crossed.clear();
+        for &p in &hot_pixels[first..] {
+            if p.y > edge.lower.y {
+                break;
+            }
+            if p != edge.upper && p != edge.lower
+                && segment_crosses_grid_cell(edge.upper, edge.lower, p) {
+                crossed.push(p);
+            }
+        }
+
+        // Sort the cells along the edge.
+        let d = edge.lower - edge.upper;
+        crossed.sort_by_key(|&p: &TessPoint| {
+            let v = p - edge.upper;
+            v.x.raw() as i128 * d.x.raw() as i128 + v.y.raw() as i128 * d.y.raw() as i128
+        });
+
+        let mut upper = edge.upper;
+        let mut source = edge.source;
+        for &position in &crossed {
+            let (above, below) = source.split(&Edge { upper, lower: edge.lower }, position);
+            push_snapped_edge(edges, upper, position, edge.winding, above);
+            upper = position;
+            source = below;
+        }
+        push_snapped_edge(edges, upper, edge.lower, edge.winding, source);
+        tags.resize(edges.len(), tag);
+    }
+}
+
+pub(crate) fn push_snapped_edge(
+    edges: &mut Vec<OrientedEdge>,
+    a: TessPoint,
+    b: TessPoint,
+    winding: i16,
+    source: EdgeSource,
+) {
+    if a == b {
+        return;
+    }
+
+    // Snapping can change the order of the endpoints of almost horizontal edges.
+    edges.push(if is_after(a, b) {
+        OrientedEdge { upper: b, lower: a, winding: -winding, source: source.swapped() }
+    } else {
+        OrientedEdge { upper: a, lower: b, winding, source }
+    });
+}
+
+// The geometry produced in robust mode, before it is sent to the output.
+#[derive(Default)]
+struct RecordedGeometry {
+    vertices: Vec<Vertex>,
+    triangles: Vec<[VertexId; 3]>,
+}
+
+impl RecordedGeometry {
+    fn clear(&mut self) {
+        self.vertices.clear();
+        self.triangles.clear();
+    }
+
+    fn replay(&self, output: &mut dyn GeometryBuilder<Vertex>) -> Result<(), GeometryBuilderError> {
+        let mut ids = Vec::with_capacity(self.vertices.len());
+        for vertex in &self.vertices {
+            ids.push(output.add_vertex(*vertex)?);
+        }
+        for triangle in &self.triangles {
+            output.add_triangle(
+                ids[triangle[0].to_usize()],
+                ids[triangle[1].to_usize()],
+                ids[triangle[2].to_usize()],
+            );
+        }
+
+        Ok(())
+    }
+}
+
+impl GeometryBuilder<Vertex> for RecordedGeometry {
+    fn begin_geometry(&mut self) {}
+    fn end_geometry(&mut self) -> Count { Count { vertices: 0, indices: 0 } }
+    fn abort_geometry(&mut self) {}
+
+    fn add_vertex(&mut self, vertex: Vertex) -> Result<VertexId, GeometryBuilderError> {
+        self.vertices.push(vertex);
+        Ok(VertexId::from_usize(self.vertices.len() - 1))
+    }
+
+    fn add_triangle(&mut self, a: VertexId, b: VertexId, c: VertexId) {
+        self.triangles.push([a, b, c]);
+    }
+}
+
+impl BatchGeometryBuilder<Vertex> for RecordedGeometry {
+    fn set_path_id(&mut self, _id: PathId) {}
+}
+
+// The x coordinate of an edge at a given y coordinate between its endpoints.
+fn x_at_y(edge: &OrientedEdge, y: FixedPoint32) -> FixedPoint32 {
+    let (ux, uy) = (edge.upper.x.raw() as i64, edge.upper.y.raw() as i64);
+    let (lx, ly) = (edge.lower.x.raw() as i64, edge.lower.y.raw() as i64);
+    FixedPoint32::from_raw((ux + (lx - ux) * (y.raw() as i64 - uy) / (ly - uy)) as i32)
+}
+
+// The y coordinate of an edge at a given x coordinate between its endpoints.
+fn y_at_x(edge: &OrientedEdge, x: FixedPoint32) -> FixedPoint32 {
+    let (ux, uy) = (edge.upper.x.raw() as i64, edge.upper.y.raw() as i64);
+    let (lx, ly) = (edge.lower.x.raw() as i64, edge.lower.y.raw() as i64);
+    if lx == ux {
+        return edge.upper.y;
+    }
+    FixedPoint32::from_raw((uy + (ly - uy) * (x.raw() as i64 - ux) / (lx - ux)) as i32)
+}
+
 pub(crate) struct EventsBuilder {
     edges: Vec<OrientedEdge>,
     vertices: Vec<TessPoint>,
@@ -1645,6 +2773,11 @@ pub(crate) struct EventsBuilder {
     current: TessPoint,
     nth: u32,
     tolerance: f32,
+
+    // Used to track the origin of the edges.
+    sub_path: u32,
+    next_sub_path: u32,
+    event: u32,
 }
 
 impl EventsBuilder {
@@ -1659,6 +2792,10 @@ impl EventsBuilder {
             current: TessPoint::new(fixed(0.0), fixed(0.0)),
             nth: 0,
             tolerance: 0.1,
+
+            sub_path: 0,
+            next_sub_path: 0,
+            event: 0,
         }
     }
 
@@ -1667,9 +2804,17 @@ impl EventsBuilder {
         self.vertices = events.vertices;
     }
 
-    fn add_edge(&mut self, a: TessPoint, b: TessPoint) {
+    fn add_edge(&mut self, a: TessPoint, b: TessPoint, t_a: f32, t_b: f32) {
         if a != b {
-            self.edges.push(OrientedEdge::new(a, b));
+            let source = EdgeSource {
+                sub_path: self.sub_path,
+                event: self.event,
+                upper_t: t_a,
+                lower_t: t_b,
+                upper_is_cut: false,
+                lower_is_cut: false,
+            };
+            self.edges.push(OrientedEdge::new(a, b, source));
         }
     }
 
@@ -1703,8 +2848,11 @@ impl EventsBuilder {
         let mut prev = from;
         let mut first = None;
         let mut nth = self.nth;
-        segment.for_each_flattened(self.tolerance, &mut|to| {
+        // Parameters are expressed on the original (not swapped) segment.
+        let mut from_t = if needs_swap { 1.0 } else { 0.0 };
+        segment.for_each_flattened_with_t(self.tolerance, &mut|to, t| {
             let to = to_internal(to);
+            let t = if needs_swap { 1.0 - t } else { t };
             if first == None {
                 first = Some(to)
                 // We can't call vertex(prev, from, to) in the first iteration
@@ -1715,10 +2863,11 @@ impl EventsBuilder {
                 self.vertex(prev, from, to);
             }
 
-            self.add_edge(from, to);
+            self.add_edge(from, to, from_t, t);
 
             prev = from;
             from = to;
+            from_t = t;
             nth += 1;
         });
 
@@ -1757,18 +2906,22 @@ impl EventsBuilder {
         let mut prev = from;
         let mut first = None;
         let mut nth = self.nth;
-        segment.for_each_flattened(self.tolerance, &mut|to| {
+        // Parameters are expressed on the original (not swapped) segment.
+        let mut from_t = if needs_swap { 1.0 } else { 0.0 };
+        segment.for_each_flattened_with_t(self.tolerance, &mut|to, t| {
             let to = to_internal(to);
+            let t = if needs_swap { 1.0 - t } else { t };
             if first == None {
                 first = Some(to)
             } else {
                 self.vertex(prev, from, to);
             }
 
-            self.add_edge(from, to);
+            self.add_edge(from, to, from_t, t);
 
             prev = from;
             from = to;
+            from_t = t;
             nth += 1;
         });
 
@@ -1810,6 +2963,9 @@ impl Build for EventsBuilder {
         self.previous = TessPoint::new(fixed(0.0), fixed(0.0));
         self.current = TessPoint::new(fixed(0.0), fixed(0.0));
         self.nth = 0;
+        self.sub_path = 0;
+        self.next_sub_path = 0;
+        self.event = 0;
 
         self.edges.sort_by(|a, b| compare_positions(a.upper, b.upper));
         self.vertices.sort_by(|a, b| compare_positions(*a, *b));
@@ -1835,13 +2991,15 @@ impl FlatPathBuilder for EventsBuilder {
             let previous = self.previous;
             let first = self.first;
             let second = self.second;
-            self.add_edge(current, first);
+            self.add_edge(current, first, 0.0, 1.0);
             self.vertex(previous, current, first);
             self.vertex(current, first, second);
         }
         self.first = next;
         self.current = next;
         self.nth = 0;
+        self.sub_path = self.next_sub_path;
+        self.next_sub_path += 1;
     }
 
     fn line_to(&mut self, to: Point) {
@@ -1856,7 +3014,7 @@ impl FlatPathBuilder for EventsBuilder {
         }
         let current = self.current;
         let previous = self.previous;
-        self.add_edge(current, next);
+        self.add_edge(current, next, 0.0, 1.0);
         if self.nth > 0 {
             self.vertex(previous, current, next);
         }
@@ -1872,7 +3030,7 @@ impl FlatPathBuilder for EventsBuilder {
         let second = self.second;
         if self.current != self.first {
             if self.nth > 0 {
-                self.add_edge(current, first);
+                self.add_edge(current, first, 0.0, 1.0);
                 self.vertex(previous, current, first);
             }
             if self.nth > 1 {
@@ -1890,12 +3048,37 @@ impl FlatPathBuilder for EventsBuilder {
     }
 }
 
+// Lets the paths of a batch be tessellated one by one into the same geometry.
+struct BatchOutput<'l> {
+    output: &'l mut dyn BatchGeometryBuilder<Vertex>,
+}
+
+impl<'l> GeometryBuilder<Vertex> for BatchOutput<'l> {
+    // The batch itself begins, ends and aborts the geometry.
+    fn begin_geometry(&mut self) {}
+    fn end_geometry(&mut self) -> Count { Count { vertices: 0, indices: 0 } }
+    fn abort_geometry(&mut self) {}
+
+    fn add_vertex(&mut self, vertex: Vertex) -> Result<VertexId, GeometryBuilderError> {
+        self.output.add_vertex(vertex)
+    }
+
+    fn add_triangle(&mut self, a: VertexId, b: VertexId, c: VertexId) {
+        self.output.add_triangle(a, b, c);
+    }
+}
+
 /// Helper class that generates a triangulation from a sequence of vertices describing a monotone
 /// polygon (used internally by the `FillTessellator`).
 struct MonotoneTessellator {
     stack: Vec<MonotoneVertex>,
     previous: MonotoneVertex,
     triangles: Vec<(VertexId, VertexId, VertexId)>,
+    // The boundary of the monotone polygon.
+    top: VertexId,
+    bottom: VertexId,
+    left_chain: Vec<VertexId>,
+    right_chain: Vec<VertexId>,
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -1910,6 +3093,10 @@ impl MonotoneTessellator {
         MonotoneTessellator {
             stack: Vec::with_capacity(16),
             triangles: Vec::with_capacity(128),
+            top: VertexId(0),
+            bottom: VertexId(0),
+            left_chain: Vec::with_capacity(16),
+            right_chain: Vec::with_capacity(16),
             // Some placeholder value that will be replaced right away.
             previous: MonotoneVertex {
                 pos: Point::new(0.0, 0.0),
@@ -1930,6 +3117,10 @@ impl MonotoneTessellator {
         self.triangles.clear();
         self.stack.clear();
         self.stack.push(first);
+        self.top = id;
+        self.bottom = id;
+        self.left_chain.clear();
+        self.right_chain.clear();
 
         self
     }
@@ -1937,6 +3128,11 @@ impl MonotoneTessellator {
     pub fn vertex(&mut self, pos: Point, id: VertexId, side: Side) {
         let current = MonotoneVertex { pos, id, side };
 
+        match side {
+            Side::Left => self.left_chain.push(id),
+            Side::Right => self.right_chain.push(id),
+        }
+
         // cf. test_fixed_to_f32_precision
         // TODO: investigate whether we could do the conversion without this
         // precision issue. Otherwise we could also make MonotoneTessellator
@@ -1995,6 +3191,39 @@ impl MonotoneTessellator {
         let side = self.previous.side.opposite();
         self.vertex(pos, id, side);
         self.stack.clear();
+
+        match side {
+            Side::Left => self.left_chain.pop(),
+            Side::Right => self.right_chain.pop(),
+        };
+        self.bottom = id;
+    }
+
+    // Append the boundary of the monotone polygon to the output, with the same
+    // winding order as the triangles. Returns false if the polygon is degenerate.
+    fn polygon(&self, output: &mut Vec<VertexId>) -> bool {
+        let start = output.len();
+        let chain = Some(self.top).into_iter()
+            .chain(self.left_chain.iter().cloned())
+            .chain(Some(self.bottom))
+            .chain(self.right_chain.iter().rev().cloned());
+
+        for id in chain {
+            if output.len() > start && output.last() == Some(&id) {
+                continue;
+            }
+            output.push(id);
+        }
+        if output.len() > start + 1 && output.last() == Some(&output[start]) {
+            output.pop();
+        }
+
+        if output.len() - start < 3 {
+            output.truncate(start);
+            return false;
+        }
+
+        true
     }
 
     fn push_triangle(&mut self, a: &MonotoneVertex, b: &MonotoneVertex, c: &MonotoneVertex) {
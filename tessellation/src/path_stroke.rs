@@ -3,12 +3,17 @@ use crate::geom::math::*;
 use crate::geom::{QuadraticBezierSegment, CubicBezierSegment, LineSegment, Arc};
 use crate::geom::utils::{normalized_tangent, directed_angle};
 use crate::geom::euclid::Trig;
-use crate::geometry_builder::{VertexId, GeometryBuilder, GeometryBuilderError};
+use crate::geometry_builder::{VertexId, GeometryBuilder, GeometryBuilderError, VertexBuffers, BuffersBuilder};
+use crate::edge_subdivision::EdgeSubdivider;
+use crate::clipping::TriangleClipper;
+use crate::attributes::AttributeInterpolator;
 use crate::basic_shapes::circle_flattening_step;
 use crate::path::builder::{Build, FlatPathBuilder, PathBuilder};
-use crate::path::PathEvent;
+use crate::path::{PathEvent, PathSlice};
 use crate::StrokeVertex as Vertex;
 use crate::{Side, Order, LineCap, LineJoin, StrokeOptions, TessellationError, TessellationResult};
+use crate::{FillTessellator, FillOptions, FillVertex};
+use crate::{VertexSource, EventPosition};
 
 use std::f32::consts::PI;
 const EPSILON: f32 = 1e-4;
@@ -84,10 +89,17 @@ const EPSILON: f32 = 1e-4;
 /// # }
 /// ```
 #[derive(Default)]
-pub struct StrokeTessellator {}
+pub struct StrokeTessellator {
+    custom_cap: Option<CapShape>,
+}
 
 impl StrokeTessellator {
-    pub fn new() -> Self { StrokeTessellator {} }
+    pub fn new() -> Self { StrokeTessellator { custom_cap: None } }
+
+    /// Set the shape of the caps that use `LineCap::Custom`.
+    pub fn set_custom_cap(&mut self, cap: Option<CapShape>) {
+        self.custom_cap = cap;
+    }
 
     /// Compute the tessellation from a path iterator.
     pub fn tessellate_path<Input>(
@@ -99,24 +111,157 @@ impl StrokeTessellator {
     where
         Input: IntoIterator<Item = PathEvent>,
     {
-        builder.begin_geometry();
-        {
-            let mut stroker = StrokeBuilder::new(options, builder);
-
-            for evt in input {
-                stroker.path_event(evt);
-                if let Some(error) = stroker.error {
-                    stroker.output.abort_geometry();
-                    return Err(error)
-                }
-            }
+        let custom_cap = self.custom_cap.as_ref();
+        stroke_with_options(options, builder, |output| stroke_path(input, options, custom_cap, output))
+    }
+
+    /// Compute the tessellation of a path and interpolate its custom attributes on the
+    /// generated vertices.
+    ///
+    /// The attributes are written in `attributes`, `path.num_attributes()` values per
+    /// vertex, at the offset given by the `VertexId` that the output returns for each vertex.
+    ///
+    /// The `max_edge_length` and `clip_rect` options are not supported with this output.
+    pub fn tessellate_path_with_attributes(
+        &mut self,
+        path: PathSlice,
+        options: &StrokeOptions,
+        output: &mut dyn GeometryBuilder<Vertex>,
+        attributes: &mut Vec<f32>,
+    ) -> TessellationResult {
+        if options.max_edge_length.is_some() || options.clip_rect.is_some() {
+            return Err(TessellationError::UnsupportedParamater);
+        }
+
+        let mut interpolator = AttributeInterpolator::new(path, output, attributes);
+
+        self.tessellate_path(path.iter(), options, &mut interpolator)
+    }
+}
+
+/// A user-defined shape for the caps that use `LineCap::Custom`.
+///
+/// The shape is expressed in a coordinate space where the end of the sub-path is at the
+/// origin, the x axis follows the tangent at the end of the sub-path (pointing outward) and
+/// the y axis points towards the left side of the stroke. Distances are expressed in multiples
+/// of the line width, so the sides of the stroke are at `y = -0.5` and `y = 0.5`. The same
+/// shape is used at both ends of the sub-paths.
+#[derive(Clone, Debug, Default)]
+pub struct CapShape {
+    vertices: Vec<Point>,
+    indices: Vec<u32>,
+}
+
+impl CapShape {
+    /// Creates a cap shape from the fill of a path.
+    pub fn new<Iter>(path: Iter, tolerance: f32) -> Result<Self, TessellationError>
+    where
+        Iter: IntoIterator<Item = PathEvent>,
+    {
+        let mut buffers: VertexBuffers<Point, u32> = VertexBuffers::new();
+        FillTessellator::new().tessellate_path(
+            path,
+            &FillOptions::tolerance(tolerance),
+            &mut BuffersBuilder::new(&mut buffers, |vertex: FillVertex| vertex.position),
+        )?;
+
+        Ok(CapShape {
+            vertices: buffers.vertices,
+            indices: buffers.indices,
+        })
+    }
+
+    /// Creates a cap shape from a convex polygon.
+    pub fn convex_polygon(points: &[Point]) -> Self {
+        let mut indices = Vec::new();
+        for i in 2..points.len() {
+            indices.extend_from_slice(&[0, i as u32 - 1, i as u32]);
+        }
+
+        CapShape {
+            vertices: points.to_vec(),
+            indices,
+        }
+    }
+}
 
-            stroker.build()?;
+// Runs `stroke` between the begin and end of the geometry, with the output wrapped to
+// implement the `max_edge_length` and `clip_rect` options.
+pub(crate) fn stroke_with_options<F>(
+    options: &StrokeOptions,
+    builder: &mut dyn GeometryBuilder<Vertex>,
+    stroke: F,
+) -> TessellationResult
+where
+    F: FnOnce(&mut dyn GeometryBuilder<Vertex>) -> Result<(), TessellationError>,
+{
+    builder.begin_geometry();
+
+    let result = if let Some(max_edge_length) = options.max_edge_length {
+        let mut subdivider = EdgeSubdivider::new(max_edge_length, builder);
+        let result = clip_and_stroke(options, &mut subdivider, stroke);
+        match subdivider.error() {
+            Some(error) => Err(error.into()),
+            None => result,
         }
-        Ok(builder.end_geometry())
+    } else {
+        clip_and_stroke(options, builder, stroke)
+    };
+
+    if let Err(error) = result {
+        builder.abort_geometry();
+        return Err(error);
+    }
+
+    Ok(builder.end_geometry())
+}
+
+fn clip_and_stroke<F>(
+    options: &StrokeOptions,
+    output: &mut dyn GeometryBuilder<Vertex>,
+    stroke: F,
+) -> Result<(), TessellationError>
+where
+    F: FnOnce(&mut dyn GeometryBuilder<Vertex>) -> Result<(), TessellationError>,
+{
+    if let Some(rect) = options.clip_rect {
+        let mut clipper = TriangleClipper::new(rect, output);
+        let result = stroke(&mut clipper);
+        match clipper.error() {
+            Some(error) => Err(error.into()),
+            None => result,
+        }
+    } else {
+        stroke(output)
     }
 }
 
+fn stroke_path<Input>(
+    input: Input,
+    options: &StrokeOptions,
+    custom_cap: Option<&CapShape>,
+    output: &mut dyn GeometryBuilder<Vertex>,
+) -> Result<(), TessellationError>
+where
+    Input: IntoIterator<Item = PathEvent>,
+{
+    let mut stroker = StrokeBuilder::new(options, output);
+    if let Some(cap) = custom_cap {
+        stroker.set_custom_cap(cap);
+    }
+
+    for evt in input {
+        stroker.path_event(evt);
+        if let Some(error) = stroker.error {
+            return Err(error);
+        }
+    }
+
+    stroker.build()?;
+
+    Ok(())
+}
+
 macro_rules! add_vertex {
     ($builder: expr, $vertex: expr) => {{
         let mut v = $vertex;
@@ -154,6 +299,22 @@ pub struct StrokeBuilder<'l> {
     previous_command_was_move: bool,
     error: Option<TessellationError>,
     output: &'l mut dyn GeometryBuilder<Vertex>,
+    custom_cap: Option<&'l CapShape>,
+
+    // Signed curvatures at the end of the previous event, at the start of the
+    // event being added and at the start of the sub-path, for the arcs joins.
+    previous_curvature: f32,
+    next_curvature: f32,
+    first_curvature: f32,
+
+    // Where the first, second, previous and current points come from.
+    first_source: VertexSource,
+    second_source: VertexSource,
+    previous_source: VertexSource,
+    current_source: VertexSource,
+    sub_path: u32,
+    next_sub_path: u32,
+    event: u32,
 }
 
 impl<'l> Build for StrokeBuilder<'l> {
@@ -174,6 +335,16 @@ impl<'l> Build for StrokeBuilder<'l> {
         self.length = 0.0;
         self.sub_path_start_length = 0.0;
         self.previous_command_was_move = false;
+        self.previous_curvature = 0.0;
+        self.next_curvature = 0.0;
+        self.first_curvature = 0.0;
+        self.first_source = VertexSource::Unknown;
+        self.second_source = VertexSource::Unknown;
+        self.previous_source = VertexSource::Unknown;
+        self.current_source = VertexSource::Unknown;
+        self.sub_path = 0;
+        self.next_sub_path = 0;
+        self.event = 0;
         Ok(())
     }
 }
@@ -182,16 +353,32 @@ impl<'l> FlatPathBuilder for StrokeBuilder<'l> {
     fn move_to(&mut self, to: Point) {
         self.finish();
 
+        self.sub_path = self.next_sub_path;
+        self.next_sub_path += 1;
+        // The first point is attributed to the start of the event that follows.
+        let source = VertexSource::Endpoint(EventPosition {
+            sub_path: self.sub_path,
+            event: self.event + 1,
+            t: 0.0,
+        });
+
         self.first = to;
         self.current = to;
+        self.first_source = source;
+        self.current_source = source;
         self.nth = 0;
         self.sub_path_start_length = self.length;
         self.previous_command_was_move = true;
+        self.event += 1;
     }
 
     fn line_to(&mut self, to: Point) {
         self.previous_command_was_move = false;
-        self.edge_to(to, true);
+        let source = self.source(1.0);
+        self.next_curvature = 0.0;
+        self.edge_to(to, source, true);
+        self.previous_curvature = 0.0;
+        self.event += 1;
     }
 
     fn close(&mut self) {
@@ -202,12 +389,17 @@ impl<'l> FlatPathBuilder for StrokeBuilder<'l> {
         let threshold = 0.001;
         if (self.first - self.current).square_length() > threshold {
             let first = self.first;
-            self.edge_to(first, true);
+            let source = self.source(1.0);
+            self.next_curvature = 0.0;
+            self.edge_to(first, source, true);
+            self.previous_curvature = 0.0;
         }
 
         if self.nth > 1 {
             let second = self.second;
-            self.edge_to(second, true);
+            let second_source = self.second_source;
+            self.next_curvature = self.first_curvature;
+            self.edge_to(second, second_source, true);
 
             let first_left_id = add_vertex!(
                 self,
@@ -216,6 +408,7 @@ impl<'l> FlatPathBuilder for StrokeBuilder<'l> {
                     normal: self.prev_normal,
                     advancement: self.sub_path_start_length,
                     side: Side::Left,
+                    source: self.previous_source,
                 }
             );
             let first_right_id = add_vertex!(
@@ -225,6 +418,7 @@ impl<'l> FlatPathBuilder for StrokeBuilder<'l> {
                     normal: -self.prev_normal,
                     advancement: self.sub_path_start_length,
                     side: Side::Right,
+                    source: self.previous_source,
                 }
             );
 
@@ -233,8 +427,10 @@ impl<'l> FlatPathBuilder for StrokeBuilder<'l> {
         }
         self.nth = 0;
         self.current = self.first;
+        self.current_source = self.first_source;
         self.sub_path_start_length = self.length;
         self.previous_command_was_move = false;
+        self.event += 1;
     }
 
     fn current_position(&self) -> Point { self.current }
@@ -244,34 +440,51 @@ impl<'l> PathBuilder for StrokeBuilder<'l> {
     fn quadratic_bezier_to(&mut self, ctrl: Point, to: Point) {
         self.previous_command_was_move = false;
         let mut first = true;
+        let from = self.current;
+        let d2 = ((from - ctrl) + (to - ctrl)) * 2.0;
+        self.next_curvature = curvature((ctrl - from) * 2.0, d2);
         QuadraticBezierSegment {
-            from: self.current,
+            from,
             ctrl,
             to,
-        }.for_each_flattened(
+        }.for_each_flattened_with_t(
             self.options.tolerance,
-            &mut |point| {
-                self.edge_to(point, first);
+            &mut |point, t| {
+                let source = self.source(t);
+                self.edge_to(point, source, first);
                 first = false;
             }
         );
+        self.previous_curvature = curvature((to - ctrl) * 2.0, d2);
+        self.event += 1;
     }
 
     fn cubic_bezier_to(&mut self, ctrl1: Point, ctrl2: Point, to: Point) {
         self.previous_command_was_move = false;
         let mut first = true;
+        let from = self.current;
+        self.next_curvature = curvature(
+            (ctrl1 - from) * 3.0,
+            ((from - ctrl1) + (ctrl2 - ctrl1)) * 6.0,
+        );
         CubicBezierSegment {
-            from: self.current,
+            from,
             ctrl1,
             ctrl2,
             to,
-        }.for_each_flattened(
+        }.for_each_flattened_with_t(
             self.options.tolerance,
-            &mut |point| {
-                self.edge_to(point, first);
+            &mut |point, t| {
+                let source = self.source(t);
+                self.edge_to(point, source, first);
                 first = false;
             }
         );
+        self.previous_curvature = curvature(
+            (to - ctrl2) * 3.0,
+            ((ctrl1 - ctrl2) + (to - ctrl2)) * 6.0,
+        );
+        self.event += 1;
     }
 
     fn arc(
@@ -283,19 +496,24 @@ impl<'l> PathBuilder for StrokeBuilder<'l> {
     ) {
         let start_angle = (self.current - center).angle_from_x_axis() - x_rotation;
         let mut first = true;
-        Arc {
+        let arc = Arc {
             center,
             radii,
             start_angle,
             sweep_angle,
             x_rotation,
-        }.for_each_flattened(
+        };
+        self.next_curvature = arc_curvature(&arc, start_angle);
+        arc.for_each_flattened_with_t(
             self.options.tolerance,
-            &mut |point| {
-                self.edge_to(point, first);
+            &mut |point, t| {
+                let source = self.source(t);
+                self.edge_to(point, source, first);
                 first = false;
             }
         );
+        self.previous_curvature = arc_curvature(&arc, start_angle + sweep_angle);
+        self.event += 1;
     }
 }
 
@@ -323,11 +541,37 @@ impl<'l> StrokeBuilder<'l> {
             previous_command_was_move: false,
             error: None,
             output: builder,
+            custom_cap: None,
+
+            previous_curvature: 0.0,
+            next_curvature: 0.0,
+            first_curvature: 0.0,
+
+            first_source: VertexSource::Unknown,
+            second_source: VertexSource::Unknown,
+            previous_source: VertexSource::Unknown,
+            current_source: VertexSource::Unknown,
+            sub_path: 0,
+            next_sub_path: 0,
+            event: 0,
         }
     }
 
     pub fn set_options(&mut self, options: &StrokeOptions) { self.options = *options; }
 
+    /// Set the shape of the caps that use `LineCap::Custom`.
+    pub fn set_custom_cap(&mut self, cap: &'l CapShape) { self.custom_cap = Some(cap); }
+
+    // The source of a point at a given parameter on the current event.
+    fn source(&self, t: f32) -> VertexSource {
+        let position = EventPosition { sub_path: self.sub_path, event: self.event, t };
+        if t == 1.0 {
+            VertexSource::Endpoint(position)
+        } else {
+            VertexSource::Curve(position)
+        }
+    }
+
     #[cold]
     fn builder_error(&mut self, e: GeometryBuilderError) {
         if self.error.is_none() {
@@ -343,6 +587,7 @@ impl<'l> StrokeBuilder<'l> {
                 normal: vector(1.0, 1.0),
                 advancement: 0.0,
                 side: Side::Right,
+                source: self.current_source,
             }
         );
         let b = add_vertex!(
@@ -352,6 +597,7 @@ impl<'l> StrokeBuilder<'l> {
                 normal: vector(1.0, -1.0),
                 advancement: 0.0,
                 side: Side::Left,
+                source: self.current_source,
             }
         );
         let c = add_vertex!(
@@ -361,6 +607,7 @@ impl<'l> StrokeBuilder<'l> {
                 normal: vector(-1.0, -1.0),
                 advancement: 0.0,
                 side: Side::Left,
+                source: self.current_source,
             }
         );
         let d = add_vertex!(
@@ -370,6 +617,7 @@ impl<'l> StrokeBuilder<'l> {
                 normal: vector(-1.0, 1.0),
                 advancement: 0.0,
                 side: Side::Right,
+                source: self.current_source,
             }
         );
         self.output.add_triangle(a, b, c);
@@ -385,6 +633,7 @@ impl<'l> StrokeBuilder<'l> {
                 normal: vector(-1.0, 0.0),
                 advancement: 0.0,
                 side: Side::Left,
+                source: self.current_source,
             }
         );
         let right_id = add_vertex!(
@@ -394,10 +643,12 @@ impl<'l> StrokeBuilder<'l> {
                 normal: vector(1.0, 0.0),
                 advancement: 0.0,
                 side: Side::Right,
+                source: self.current_source,
             }
         );
-        self.tessellate_round_cap(center, vector(0.0, -1.0), left_id, right_id, true);
-        self.tessellate_round_cap(center, vector(0.0, 1.0), left_id, right_id, false);
+        let source = self.current_source;
+        self.tessellate_round_cap(center, vector(0.0, -1.0), left_id, right_id, true, source);
+        self.tessellate_round_cap(center, vector(0.0, 1.0), left_id, right_id, false, source);
     }
 
     fn finish(&mut self) {
@@ -412,6 +663,11 @@ impl<'l> StrokeBuilder<'l> {
                     // Same thing for round caps.
                     self.tessellate_empty_round_cap();
                 }
+                LineCap::Circle { .. } | LineCap::Diamond { .. } => {
+                    // These are centered on the point, so a single one is enough.
+                    let (cap, position, source) = (self.options.start_cap, self.current, self.current_source);
+                    self.tessellate_shape_cap(cap, position, vector(1.0, 0.0), false, 0.0, source);
+                }
                 _ => {}
             }
         }
@@ -419,22 +675,28 @@ impl<'l> StrokeBuilder<'l> {
         // last edge
         if self.nth > 0 {
             let current = self.current;
+            let current_source = self.current_source;
             let d = self.current - self.previous;
             if self.options.end_cap == LineCap::Square {
                 // The easiest way to implement square caps is to lie about the current position
                 // and move it slightly to accommodate for the width/2 extra length.
                 self.current += d.normalize();
             }
+            // Arrowheads are implemented the same way, moving the position backward.
+            self.current -= d.normalize() * self.cap_inset(self.options.end_cap, d);
             let p = self.current + d;
-            self.edge_to(p, true);
+            self.edge_to(p, current_source, true);
             // Restore the real current position.
             self.current = current;
 
             if self.options.end_cap == LineCap::Round {
                 let left_id = self.previous_left_id;
                 let right_id = self.previous_right_id;
-                self.tessellate_round_cap(current, d, left_id, right_id, false);
+                self.tessellate_round_cap(current, d, left_id, right_id, false, current_source);
             }
+
+            let (cap, length) = (self.options.end_cap, self.length);
+            self.tessellate_shape_cap(cap, current, d.normalize(), false, length, current_source);
         }
         // first edge
         if self.nth > 1 {
@@ -444,6 +706,7 @@ impl<'l> StrokeBuilder<'l> {
             if self.options.start_cap == LineCap::Square {
                 first += d.normalize();
             }
+            first -= d.normalize() * self.cap_inset(self.options.start_cap, d);
 
             let n2 = normalized_tangent(d);
             let n1 = -n2;
@@ -455,6 +718,7 @@ impl<'l> StrokeBuilder<'l> {
                     normal: n1,
                     advancement: self.sub_path_start_length,
                     side: Side::Left,
+                    source: self.first_source,
                 }
             );
             let first_right_id = add_vertex!(
@@ -464,19 +728,155 @@ impl<'l> StrokeBuilder<'l> {
                     normal: n2,
                     advancement: self.sub_path_start_length,
                     side: Side::Right,
+                    source: self.first_source,
                 }
             );
 
             if self.options.start_cap == LineCap::Round {
-                self.tessellate_round_cap(first, d, first_left_id, first_right_id, true);
+                let source = self.first_source;
+                self.tessellate_round_cap(first, d, first_left_id, first_right_id, true, source);
             }
 
+            let (cap, position, length, source) = (
+                self.options.start_cap,
+                self.first,
+                self.sub_path_start_length,
+                self.first_source,
+            );
+            self.tessellate_shape_cap(cap, position, d.normalize(), true, length, source);
+
             self.output.add_triangle(first_right_id, first_left_id, self.second_right_id);
             self.output.add_triangle(first_left_id, self.second_left_id, self.second_right_id);
         }
     }
 
-    fn edge_to(&mut self, to: Point, with_join: bool) {
+    // How much the stroke must be shortened at an end, given the edge leading to it.
+    fn cap_inset(&self, cap: LineCap, edge: Vector) -> f32 {
+        let inset = match cap {
+            LineCap::Arrow { length, .. } => length * self.options.line_width,
+            _ => { return 0.0; }
+        };
+
+        // Don't move past the other end of the edge.
+        if inset < edge.length() { inset } else { 0.0 }
+    }
+
+    // Caps that are shapes added at the end of the sub-path, independently from the
+    // rest of the stroke.
+    //
+    // The shapes are expressed in a coordinate space where the x axis is the outward
+    // tangent at the end of the sub-path and the y axis is the normal of the left side,
+    // in multiples of the line width.
+    fn tessellate_shape_cap(
+        &mut self,
+        cap: LineCap,
+        center: Point,
+        outward: Vector,
+        is_start: bool,
+        advancement: f32,
+        source: VertexSource,
+    ) {
+        let left = if is_start { -normalized_tangent(outward) } else { normalized_tangent(outward) };
+
+        let polygon = match cap {
+            LineCap::Triangle => vec![point(0.0, -0.5), point(0.5, 0.0), point(0.0, 0.5)],
+            LineCap::Arrow { length, width } => vec![
+                point(-length, -width * 0.5),
+                point(0.0, 0.0),
+                point(-length, width * 0.5),
+            ],
+            LineCap::Diamond { size } => vec![
+                point(size, 0.0),
+                point(0.0, size),
+                point(-size, 0.0),
+                point(0.0, -size),
+            ],
+            LineCap::Circle { radius } => {
+                let angle = compute_max_radius_segment_angle(
+                    radius * self.options.line_width,
+                    self.options.tolerance,
+                );
+                let n = if angle.is_finite() && angle > 0.0 {
+                    ((2.0 * PI / angle).ceil() as usize).max(8).min(1024)
+                } else {
+                    8
+                };
+                (0..n).map(|i| {
+                    let a = 2.0 * PI * i as f32 / n as f32;
+                    point(a.cos() * radius, a.sin() * radius)
+                }).collect()
+            }
+            LineCap::Custom => {
+                if let Some(shape) = self.custom_cap {
+                    let ids: Vec<VertexId> = shape.vertices.iter().map(|p| {
+                        self.add_cap_vertex(*p, center, outward, left, advancement, source)
+                    }).collect();
+                    for tri in shape.indices.chunks(3) {
+                        let (a, b, c) = (tri[0] as usize, tri[1] as usize, tri[2] as usize);
+                        let positions = [shape.vertices[a], shape.vertices[b], shape.vertices[c]];
+                        self.add_cap_triangle(positions, [ids[a], ids[b], ids[c]], is_start);
+                    }
+                }
+                return;
+            }
+            LineCap::Butt | LineCap::Square | LineCap::Round => { return; }
+        };
+
+        // The built-in shapes are convex.
+        let ids: Vec<VertexId> = polygon.iter().map(|p| {
+            self.add_cap_vertex(*p, center, outward, left, advancement, source)
+        }).collect();
+        for i in 2..polygon.len() {
+            let positions = [polygon[0], polygon[i - 1], polygon[i]];
+            self.add_cap_triangle(positions, [ids[0], ids[i - 1], ids[i]], is_start);
+        }
+    }
+
+    // Adds a triangle of a cap shape with the same winding as the rest of the stroke.
+    // The cap space is mirrored at the start of the sub-paths.
+    fn add_cap_triangle(&mut self, positions: [Point; 3], ids: [VertexId; 3], is_start: bool) {
+        let [a, b, c] = positions;
+        let cross = (a - b).cross(c - b);
+        if (cross > 0.0) != is_start {
+            self.output.add_triangle(ids[0], ids[1], ids[2]);
+        } else {
+            self.output.add_triangle(ids[0], ids[2], ids[1]);
+        }
+    }
+
+    fn add_cap_vertex(
+        &mut self,
+        p: Point,
+        center: Point,
+        outward: Vector,
+        left: Vector,
+        advancement: f32,
+        source: VertexSource,
+    ) -> VertexId {
+        // Extruding by half of the line width moves the vertex to its position in the shape.
+        let mut position = center;
+        let mut normal = (outward * p.x + left * p.y) * 2.0;
+        if normal.square_length() < EPSILON * EPSILON {
+            // Vertices at the end of the sub-path (like the tip of an arrow) would have a
+            // null normal. Use the outward direction instead and move the vertex back so that
+            // it still ends up at the right position once extruded.
+            position += (normal - outward) * self.options.line_width * 0.5;
+            normal = outward;
+        }
+
+        add_vertex!(
+            self,
+            Vertex {
+                position,
+                normal,
+                advancement,
+                side: if p.y >= 0.0 { Side::Left } else { Side::Right },
+                source,
+            }
+        )
+    }
+
+    fn edge_to(&mut self, to: Point, source: VertexSource, with_join: bool) {
         if to == self.current {
             return;
         }
@@ -484,8 +884,11 @@ impl<'l> StrokeBuilder<'l> {
         if self.nth == 0 {
             // We don't have enough information to compute the previous
             // vertices (and thus the current join) yet.
+            self.first_curvature = self.next_curvature;
             self.previous = self.first;
+            self.previous_source = self.first_source;
             self.current = to;
+            self.current_source = source;
             self.nth += 1;
             return;
         }
@@ -522,12 +925,15 @@ impl<'l> StrokeBuilder<'l> {
 
         self.previous_front_side = front_side;
         self.previous = self.current;
+        self.previous_source = self.current_source;
         self.previous_left_id = end_left_id;
         self.previous_right_id = end_right_id;
         self.current = to;
+        self.current_source = source;
 
         if self.nth == 1 {
             self.second = self.previous;
+            self.second_source = self.previous_source;
             self.second_left_id = start_left_id;
             self.second_right_id = start_right_id;
         }
@@ -542,6 +948,7 @@ impl<'l> StrokeBuilder<'l> {
         left: VertexId,
         right: VertexId,
         is_start: bool,
+        source: VertexSource,
     ) {
         let radius = self.options.line_width.abs();
         if radius < 1e-4 {
@@ -568,6 +975,7 @@ impl<'l> StrokeBuilder<'l> {
                 normal: dir,
                 advancement,
                 side: Side::Left,
+                source,
             }
         );
 
@@ -594,6 +1002,7 @@ impl<'l> StrokeBuilder<'l> {
             Side::Left,
             apply_width,
             !is_start,
+            source,
             self.output
         ) {
             self.builder_error(e);
@@ -608,6 +1017,7 @@ impl<'l> StrokeBuilder<'l> {
             Side::Right,
             apply_width,
             !is_start,
+            source,
             self.output
         ) {
             self.builder_error(e);
@@ -649,6 +1059,7 @@ impl<'l> StrokeBuilder<'l> {
                     normal: back_start_vertex_normal,
                     advancement: self.length,
                     side: front_side.opposite(),
+                    source: self.current_source,
                 }
             );
             let back_end_vertex = add_vertex!(
@@ -658,6 +1069,7 @@ impl<'l> StrokeBuilder<'l> {
                     normal: back_end_vertex_normal,
                     advancement: self.length,
                     side: front_side.opposite(),
+                    source: self.current_source,
                 }
             );
             // return
@@ -675,6 +1087,7 @@ impl<'l> StrokeBuilder<'l> {
                 normal: -front_normal,
                 advancement: self.length,
                 side: front_side.opposite(),
+                source: self.current_source,
             }
         );
         let back_end_vertex = back_start_vertex;
@@ -720,7 +1133,16 @@ impl<'l> StrokeBuilder<'l> {
             // Per SVG spec: If the stroke-miterlimit is exceeded, the line join
             // falls back to bevel.
             join_type = LineJoin::Bevel;
-        } else if join_type == LineJoin::MiterClip && !self.miter_limit_is_exceeded(normal) {
+        }
+
+        let mut arcs_outline = Vec::new();
+        if join_type == LineJoin::Arcs
+            && !self.arcs_join_outline(prev_tangent, next_tangent, front_side, normal, &mut arcs_outline) {
+            // Per SVG spec: If the arcs don't intersect, the line join falls back to miter-clip.
+            join_type = LineJoin::MiterClip;
+        }
+
+        if join_type == LineJoin::MiterClip && !self.miter_limit_is_exceeded(normal) {
             join_type = LineJoin::Miter;
         }
 
@@ -759,6 +1181,14 @@ impl<'l> StrokeBuilder<'l> {
                     normal
                 )
             }
+            LineJoin::Arcs => {
+                self.tessellate_arcs_join(
+                    &arcs_outline,
+                    next_tangent,
+                    front_side,
+                    back_join_vertex,
+                )
+            }
             // Fallback to Miter for unimplemented line joins
             _ => {
                 let end_vertex = add_vertex!(
@@ -768,6 +1198,7 @@ impl<'l> StrokeBuilder<'l> {
                         normal: front_normal,
                         advancement: self.length,
                         side: front_side,
+                        source: self.current_source,
                     }
                 );
                 self.prev_normal = normal;
@@ -789,6 +1220,7 @@ impl<'l> StrokeBuilder<'l> {
                             normal: n1,
                             advancement: self.length,
                             side: front_side,
+                            source: self.current_source,
                         }
                     );
                      self.output.add_triangle(start_vertex, end_vertex, back_join_vertex);
@@ -842,6 +1274,7 @@ impl<'l> StrokeBuilder<'l> {
                 normal: prev_normal * neg_if_right,
                 advancement: self.length,
                 side: front_side,
+                source: self.current_source,
             }
         );
         let last_vertex = add_vertex!(
@@ -851,6 +1284,7 @@ impl<'l> StrokeBuilder<'l> {
                 normal: next_normal * neg_if_right,
                 advancement: self.length,
                 side: front_side,
+                source: self.current_source,
             }
         );
         self.prev_normal = next_normal;
@@ -892,6 +1326,7 @@ impl<'l> StrokeBuilder<'l> {
                 normal: initial_normal,
                 advancement: self.length,
                 side: front_side,
+                source: self.current_source,
             }
         );
         let start_vertex = last_vertex;
@@ -919,6 +1354,7 @@ impl<'l> StrokeBuilder<'l> {
                     normal: n,
                     advancement: self.length,
                     side: front_side,
+                    source: self.current_source,
                 }
             );
 
@@ -958,6 +1394,7 @@ impl<'l> StrokeBuilder<'l> {
                 normal: v1 * neg_if_right,
                 advancement: self.length,
                 side: front_side,
+                source: self.current_source,
             }
         );
 
@@ -968,6 +1405,7 @@ impl<'l> StrokeBuilder<'l> {
                 normal: v2 * neg_if_right,
                 advancement: self.length,
                 side: front_side,
+                source: self.current_source,
             }
         );
 
@@ -983,6 +1421,132 @@ impl<'l> StrokeBuilder<'l> {
         (start_vertex, last_vertex)
     }
 
+    // Computes the outline of the front side of an arcs join, expressed as normals
+    // going from the end of the previous edge to the start of the next one.
+    //
+    // Returns false if the join should fall back to miter-clip.
+    fn arcs_join_outline(
+        &self,
+        prev_tangent: Vector,
+        next_tangent: Vector,
+        front_side: Side,
+        normal: Vector,
+        outline: &mut Vec<Vector>,
+    ) -> bool {
+        let neg_if_right = if front_side.is_left() { 1.0 } else { -1.0 };
+        let half_width = self.options.line_width * 0.5;
+
+        // Curvature of the outer edges, in the normal space where the half line width is 1.
+        let offset_curvature = |k: f32| {
+            let k = k * half_width;
+            let d = 1.0 - k * neg_if_right;
+            // The outer edge has a cusp.
+            if d <= EPSILON { None } else { Some(k / d) }
+        };
+
+        let (k1, k2) = match (
+            offset_curvature(self.previous_curvature),
+            offset_curvature(self.next_curvature),
+        ) {
+            (Some(k1), Some(k2)) => (k1, k2),
+            _ => { return false; }
+        };
+
+        if k1.abs() < EPSILON && k2.abs() < EPSILON {
+            // With straight edges the arcs join is a miter-clip join.
+            return false;
+        }
+
+        let from = (normalized_tangent(prev_tangent) * neg_if_right).to_point();
+        let to = (normalized_tangent(next_tangent) * neg_if_right).to_point();
+        let front = normal.normalize() * neg_if_right;
+
+        let ext1 = JoinExtension::new(from, prev_tangent, k1);
+        let ext2 = JoinExtension::new(to, next_tangent, k2);
+
+        let mut intersection: Option<Point> = None;
+        let mut min_distance = std::f32::MAX;
+        for p in ext1.intersections(&ext2) {
+            // Only consider intersections ahead of the previous edge, behind the next edge
+            // and on the front of the join.
+            if (p - from).dot(prev_tangent) < 0.0
+                || (p - to).dot(next_tangent) > 0.0
+                || p.to_vector().dot(front) <= 0.0 {
+                continue;
+            }
+            let d = p.to_vector().square_length();
+            if d < min_distance {
+                min_distance = d;
+                intersection = Some(p);
+            }
+        }
+
+        let intersection = match intersection {
+            Some(p) => p,
+            None => { return false; }
+        };
+
+        let tolerance = self.options.tolerance / half_width;
+        let mut points = vec![from];
+        ext1.flatten_to(from, intersection, tolerance, &mut points);
+        ext2.flatten_to(intersection, to, tolerance, &mut points);
+
+        // Clip the extensions at the miter limit, the same way miter-clip joins are.
+        let limit = self.options.miter_limit;
+        let inside = |p: Point| p.to_vector().dot(front) <= limit;
+        for i in 0..points.len() {
+            let p = points[i];
+            if inside(p) {
+                outline.push(p.to_vector());
+            }
+            if let Some(&next) = points.get(i + 1) {
+                if inside(p) != inside(next) {
+                    let dp = p.to_vector().dot(front);
+                    let dn = next.to_vector().dot(front);
+                    let t = (limit - dp) / (dn - dp);
+                    outline.push(p.lerp(next, t).to_vector());
+                }
+            }
+        }
+
+        true
+    }
+
+    fn tessellate_arcs_join(
+        &mut self,
+        outline: &[Vector],
+        next_tangent: Vector,
+        front_side: Side,
+        back_vertex: VertexId,
+    ) -> (VertexId, VertexId) {
+        let mut ids = Vec::with_capacity(outline.len());
+        for normal in outline {
+            ids.push(add_vertex!(
+                self,
+                Vertex {
+                    position: self.current,
+                    normal: *normal,
+                    advancement: self.length,
+                    side: front_side,
+                    source: self.current_source,
+                }
+            ));
+        }
+
+        for i in 1..ids.len() {
+            let (v1, v2, v3) = if front_side.is_left() {
+                (back_vertex, ids[i - 1], ids[i])
+            } else {
+                (back_vertex, ids[i], ids[i - 1])
+            };
+            self.output.add_triangle(v1, v2, v3);
+        }
+
+        self.prev_normal = normalized_tangent(next_tangent);
+
+        (ids[0], ids[ids.len() - 1])
+    }
+
     fn miter_limit_is_exceeded(&self, normal: Vector ) -> bool {
         normal.square_length() > self.options.miter_limit * self.options.miter_limit
     }
@@ -1010,6 +1574,111 @@ impl<'l> StrokeBuilder<'l> {
     }
 }
 
+// The extension of the outer edge of the stroke at an arcs join: the circle that is
+// tangent to the edge at the join with the same curvature, or a line if the curvature
+// is null.
+enum JoinExtension {
+    Line { point: Point, direction: Vector },
+    Circle { center: Point, radius: f32, ccw: bool },
+}
+
+impl JoinExtension {
+    fn new(point: Point, tangent: Vector, curvature: f32) -> Self {
+        if curvature.abs() < EPSILON {
+            return JoinExtension::Line { point, direction: tangent };
+        }
+
+        JoinExtension::Circle {
+            center: point + normalized_tangent(tangent) / curvature,
+            radius: 1.0 / curvature.abs(),
+            ccw: curvature > 0.0,
+        }
+    }
+
+    fn intersections(&self, other: &JoinExtension) -> Vec<Point> {
+        use self::JoinExtension::*;
+        match (self, other) {
+            (&Line { point: p1, direction: d1 }, &Line { point: p2, direction: d2 }) => {
+                let denom = d1.cross(d2);
+                if denom.abs() < EPSILON {
+                    return Vec::new();
+                }
+                vec![p1 + d1 * ((p2 - p1).cross(d2) / denom)]
+            }
+            (&Line { point, direction }, &Circle { center, radius, .. })
+            | (&Circle { center, radius, .. }, &Line { point, direction }) => {
+                let d = direction.normalize();
+                let t = (center - point).dot(d);
+                let closest = point + d * t;
+                let h2 = radius * radius - (center - closest).square_length();
+                if h2 < 0.0 {
+                    return Vec::new();
+                }
+                let h = h2.sqrt();
+                vec![closest - d * h, closest + d * h]
+            }
+            (&Circle { center: c1, radius: r1, .. }, &Circle { center: c2, radius: r2, .. }) => {
+                let v = c2 - c1;
+                let dist = v.length();
+                if dist < EPSILON || dist > r1 + r2 || dist < (r1 - r2).abs() {
+                    return Vec::new();
+                }
+                let a = (r1 * r1 - r2 * r2 + dist * dist) / (2.0 * dist);
+                let h = (r1 * r1 - a * a).max(0.0).sqrt();
+                let mid = c1 + v * (a / dist);
+                let n = normalized_tangent(v) * h;
+                vec![mid + n, mid - n]
+            }
+        }
+    }
+
+    // Adds the points of the extension between two of its points, excluding `from`.
+    fn flatten_to(&self, from: Point, to: Point, tolerance: f32, output: &mut Vec<Point>) {
+        if let JoinExtension::Circle { center, radius, ccw } = *self {
+            let start = (from - center).angle_from_x_axis().radians;
+            let end = (to - center).angle_from_x_axis().radians;
+            let mut sweep = end - start;
+            if ccw && sweep < 0.0 {
+                sweep += 2.0 * PI;
+            } else if !ccw && sweep > 0.0 {
+                sweep -= 2.0 * PI;
+            }
+
+            let step = compute_max_radius_segment_angle(radius, tolerance);
+            let n = if step.is_finite() && step > 0.0 {
+                ((sweep.abs() / step).ceil() as u32).max(1).min(1024)
+            } else {
+                1
+            };
+            for i in 1..n {
+                let angle = start + sweep * i as f32 / n as f32;
+                output.push(center + vector(angle.cos(), angle.sin()) * radius);
+            }
+        }
+
+        output.push(to);
+    }
+}
+
+// Signed curvature from the first and second derivatives of a curve, positive when
+// the curve turns towards its left side.
+fn curvature(d1: Vector, d2: Vector) -> f32 {
+    let l = d1.length();
+    if l < EPSILON {
+        return 0.0;
+    }
+
+    d1.cross(d2) / (l * l * l)
+}
+
+// Signed curvature of an elliptic arc at a given angle.
+fn arc_curvature(arc: &Arc<f32>, angle: Angle) -> f32 {
+    let (sin, cos) = angle.radians.sin_cos();
+    let (a, b) = (arc.radii.x, arc.radii.y);
+    let sign = if arc.sweep_angle.radians >= 0.0 { 1.0 } else { -1.0 };
+    curvature(vector(-a * sin, b * cos) * sign, vector(-a * cos, -b * sin))
+}
+
 // Computes the max angle of a radius segment for a given tolerance
 fn compute_max_radius_segment_angle(radius: f32, tolerance: f32) -> f32 {
     let t = radius - tolerance;
@@ -1040,6 +1709,7 @@ fn tess_round_cap(
     side: Side,
     line_width: f32,
     invert_winding: bool,
+    source: VertexSource,
     output: &mut dyn GeometryBuilder<Vertex>
 ) -> Result<(), GeometryBuilderError> {
     if num_recursions == 0 {
@@ -1055,6 +1725,7 @@ fn tess_round_cap(
         normal,
         advancement,
         side,
+        source,
     })?;
 
     let (v1, v2, v3) = if invert_winding {
@@ -1075,6 +1746,7 @@ fn tess_round_cap(
         side,
         line_width,
         invert_winding,
+        source,
         output
     )?;
     tess_round_cap(
@@ -1088,14 +1760,15 @@ fn tess_round_cap(
         side,
         line_width,
         invert_winding,
+        source,
         output
     )
 }
 
 #[cfg(test)]
-use crate::path::{Path, PathSlice};
+use crate::path::Path;
 #[cfg(test)]
-use crate::geometry_builder::{SimpleBuffersBuilder, simple_builder, VertexBuffers, Count};
+use crate::geometry_builder::{SimpleBuffersBuilder, simple_builder, Count};
 
 #[cfg(test)]
 fn test_path(
@@ -1103,6 +1776,16 @@ fn test_path(
     options: &StrokeOptions,
     expected_triangle_count: Option<u32>
 ) {
+    test_path_with_custom_cap(path, options, None, expected_triangle_count);
+}
+
+#[cfg(test)]
+fn test_path_with_custom_cap(
+    path: PathSlice,
+    options: &StrokeOptions,
+    custom_cap: Option<CapShape>,
+    expected_triangle_count: Option<u32>
+) {
 
     struct TestBuilder<'l> {
         builder: SimpleBuffersBuilder<'l, Vertex>,
@@ -1143,6 +1826,7 @@ fn test_path(
     let mut buffers: VertexBuffers<Vertex, u16> = VertexBuffers::new();
 
     let mut tess = StrokeTessellator::new();
+    tess.set_custom_cap(custom_cap);
     let count = tess.tessellate_path(
         path,
         &options,
@@ -1211,6 +1895,18 @@ fn test_square() {
         Some(12),
     );
 
+    // Arcs joins between straight edges are miter-clip joins.
+    test_path(
+        path1.as_slice(),
+        &StrokeOptions::default().with_line_join(LineJoin::Arcs).with_miter_limit(1.0),
+        Some(12),
+    );
+    test_path(
+        path2.as_slice(),
+        &StrokeOptions::default().with_line_join(LineJoin::Arcs).with_miter_limit(1.0),
+        Some(12),
+    );
+
     test_path(
         path1.as_slice(),
         &StrokeOptions::tolerance(0.001).with_line_join(LineJoin::Round),
@@ -1260,6 +1956,35 @@ fn test_empty_caps() {
     );
 }
 
+#[test]
+fn test_max_edge_length() {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(10.0, 0.0));
+    builder.quadratic_bezier_to(point(20.0, 0.0), point(20.0, 10.0));
+    builder.line_to(point(0.0, 10.0));
+    builder.close();
+    let path = builder.build();
+
+    for &max_edge_length in &[0.4, 2.0] {
+        let mut buffers: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+        StrokeTessellator::new().tessellate_path(
+            &path,
+            &StrokeOptions::default().with_max_edge_length(max_edge_length),
+            &mut simple_builder(&mut buffers),
+        ).unwrap();
+
+        assert!(!buffers.indices.is_empty());
+        for tri in buffers.indices.chunks(3) {
+            for i in 0..3 {
+                let a = buffers.vertices[tri[i] as usize].position;
+                let b = buffers.vertices[tri[(i + 1) % 3] as usize].position;
+                assert!((b - a).length() <= max_edge_length);
+            }
+        }
+    }
+}
+
 #[test]
 fn test_too_many_vertices() {
     /// This test checks that the tessellator returns the proper error when
@@ -1304,3 +2029,321 @@ fn test_too_many_vertices() {
         Err(TessellationError::TooManyVertices),
     );
 }
+
+#[test]
+fn test_attributes() {
+    let mut builder = Path::builder_with_attributes(2);
+    builder.move_to_with_attributes(point(0.0, 0.0), &[0.0, 0.0]);
+    builder.line_to_with_attributes(point(10.0, 0.0), &[10.0, 0.0]);
+    builder.quadratic_bezier_to_with_attributes(point(15.0, 5.0), point(20.0, 10.0), &[20.0, 10.0]);
+    builder.line_to_with_attributes(point(0.0, 10.0), &[0.0, 10.0]);
+    builder.close();
+    builder.move_to_with_attributes(point(30.0, 0.0), &[30.0, 0.0]);
+    builder.line_to_with_attributes(point(40.0, 0.0), &[40.0, 0.0]);
+    let path = builder.build();
+
+    let options = StrokeOptions::default()
+        .with_line_join(LineJoin::Round)
+        .dont_apply_line_width();
+
+    let mut buffers: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+    let mut attributes = Vec::new();
+    StrokeTessellator::new().tessellate_path_with_attributes(
+        path.as_slice(),
+        &options,
+        &mut simple_builder(&mut buffers),
+        &mut attributes,
+    ).unwrap();
+
+    // The attributes are the coordinates of the endpoints and the curve has
+    // its control point in the middle, so they should match the position of
+    // the vertices after interpolation.
+    assert_eq!(attributes.len(), buffers.vertices.len() * 2);
+    for (i, vertex) in buffers.vertices.iter().enumerate() {
+        let interpolated = point(attributes[i * 2], attributes[i * 2 + 1]);
+        assert!((interpolated - vertex.position).length() < 0.01);
+    }
+
+    for options in &[options.with_max_edge_length(1.0), options.with_clip_rect(rect(0.0, 0.0, 5.0, 5.0))] {
+        assert_eq!(
+            StrokeTessellator::new().tessellate_path_with_attributes(
+                path.as_slice(),
+                options,
+                &mut simple_builder(&mut buffers),
+                &mut attributes,
+            ),
+            Err(TessellationError::UnsupportedParamater),
+        );
+    }
+}
+
+#[test]
+fn test_arc_sources() {
+    let mut buffers: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+    {
+        let options = StrokeOptions::tolerance(0.01);
+        let mut output = simple_builder(&mut buffers);
+        let mut builder = StrokeBuilder::new(&options, &mut output);
+        builder.move_to(point(10.0, 0.0));
+        builder.arc(point(0.0, 0.0), vector(10.0, 10.0), Angle::radians(PI), Angle::radians(0.0));
+        builder.build().unwrap();
+    }
+
+    // All vertices of the arc know where they come from on the arc.
+    assert!(buffers.vertices.len() > 4);
+    for vertex in &buffers.vertices {
+        match vertex.source {
+            VertexSource::Endpoint(pos) => { assert!(pos.t == 0.0 || pos.t == 1.0); }
+            VertexSource::Curve(pos) => { assert!(pos.t > 0.0 && pos.t < 1.0); }
+            _ => { panic!("{:?}", vertex.source); }
+        }
+    }
+}
+
+#[test]
+fn test_clip_rect() {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(20.0, 5.0));
+    builder.line_to(point(10.0, 20.0));
+    let path = builder.build();
+
+    let options = StrokeOptions::default()
+        .with_line_width(2.0)
+        .with_line_join(LineJoin::Round);
+
+    fn area(buffers: &VertexBuffers<Vertex, u16>) -> f32 {
+        let mut area = 0.0;
+        for tri in buffers.indices.chunks(3) {
+            let a = buffers.vertices[tri[0] as usize].position;
+            let b = buffers.vertices[tri[1] as usize].position;
+            let c = buffers.vertices[tri[2] as usize].position;
+            area += ((b - a).cross(c - a) * 0.5).abs();
+        }
+        area
+    }
+
+    let mut tess = StrokeTessellator::new();
+    let mut buffers: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+    tess.tessellate_path(&path, &options, &mut simple_builder(&mut buffers)).unwrap();
+    let expected_area = area(&buffers);
+
+    // Split the stroke over two adjacent rectangles that cover it.
+    let tiles: [Rect; 2] = [rect(-5.0, -5.0, 12.0, 30.0), rect(7.0, -5.0, 20.0, 30.0)];
+    let mut clipped_area = 0.0;
+    for tile in &tiles {
+        let mut buffers: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+        tess.tessellate_path(
+            &path,
+            &options.with_clip_rect(*tile),
+            &mut simple_builder(&mut buffers),
+        ).unwrap();
+
+        for vertex in &buffers.vertices {
+            assert!(vertex.position.x >= tile.min_x() && vertex.position.x <= tile.max_x());
+            assert!(vertex.position.y >= tile.min_y() && vertex.position.y <= tile.max_y());
+        }
+        clipped_area += area(&buffers);
+    }
+
+    assert!((clipped_area - expected_area).abs() < 0.01);
+}
+
+#[test]
+fn test_shape_caps() {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(10.0, 0.0));
+    builder.line_to(point(10.0, 10.0));
+    builder.move_to(point(20.0, 0.0));
+    builder.close();
+    let path = builder.build();
+
+    let caps = [
+        LineCap::Triangle,
+        LineCap::Arrow { length: 2.0, width: 3.0 },
+        LineCap::Circle { radius: 1.0 },
+        LineCap::Diamond { size: 1.5 },
+    ];
+
+    for cap in &caps {
+        test_path(path.as_slice(), &StrokeOptions::default().with_line_cap(*cap), None);
+    }
+
+    // A fish tail, which isn't convex.
+    let mut tail = Path::builder();
+    tail.move_to(point(0.0, -0.5));
+    tail.line_to(point(1.0, -1.0));
+    tail.line_to(point(0.6, 0.0));
+    tail.line_to(point(1.0, 1.0));
+    tail.line_to(point(0.0, 0.5));
+    tail.close();
+    let shapes = [
+        CapShape::new(&tail.build(), 0.01).unwrap(),
+        CapShape::convex_polygon(&[point(0.0, -0.5), point(1.0, 0.0), point(0.0, 0.5)]),
+    ];
+
+    for shape in &shapes {
+        test_path_with_custom_cap(
+            path.as_slice(),
+            &StrokeOptions::default().with_line_cap(LineCap::Custom),
+            Some(shape.clone()),
+            None,
+        );
+    }
+}
+
+#[test]
+fn test_arrow_cap() {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(10.0, 0.0));
+    let path = builder.build();
+
+    let options = StrokeOptions::default()
+        .with_line_width(2.0)
+        .with_start_cap(LineCap::Butt)
+        .with_end_cap(LineCap::Arrow { length: 2.0, width: 3.0 });
+
+    let mut buffers: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+    StrokeTessellator::new().tessellate_path(
+        &path,
+        &options,
+        &mut simple_builder(&mut buffers),
+    ).unwrap();
+
+    // The tip of the arrow is at the end of the path and the body of the stroke
+    // stops at its base.
+    let mut max = point(std::f32::MIN, std::f32::MIN);
+    for vertex in &buffers.vertices {
+        max = max.max(vertex.position);
+        if vertex.position.y.abs() == 1.0 {
+            assert!(vertex.position.x <= 6.0 + EPSILON);
+        }
+    }
+    assert!((max.x - 10.0).abs() < EPSILON);
+    assert!((max.y - 3.0).abs() < EPSILON);
+}
+
+#[test]
+fn test_custom_cap() {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(10.0, 0.0));
+    let path = builder.build();
+
+    let options = StrokeOptions::default()
+        .with_line_width(2.0)
+        .with_line_cap(LineCap::Custom);
+
+    let mut tess = StrokeTessellator::new();
+    let mut butt: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+    tess.tessellate_path(&path, &options, &mut simple_builder(&mut butt)).unwrap();
+
+    // A square that extends the stroke by one line width at each end.
+    let mut square = Path::builder();
+    square.move_to(point(0.0, -0.5));
+    square.line_to(point(1.0, -0.5));
+    square.line_to(point(1.0, 0.5));
+    square.line_to(point(0.0, 0.5));
+    square.close();
+    let shapes = [
+        CapShape::new(&square.build(), 0.01).unwrap(),
+        CapShape::convex_polygon(&[
+            point(0.0, -0.5), point(1.0, -0.5), point(1.0, 0.5), point(0.0, 0.5)
+        ]),
+    ];
+
+    for shape in &shapes {
+        tess.set_custom_cap(Some(shape.clone()));
+        let mut buffers: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+        tess.tessellate_path(&path, &options, &mut simple_builder(&mut buffers)).unwrap();
+
+        assert_eq!(buffers.indices.len(), butt.indices.len() + 12);
+        let mut min_x = std::f32::MAX;
+        let mut max_x = std::f32::MIN;
+        for vertex in &buffers.vertices {
+            min_x = min_x.min(vertex.position.x);
+            max_x = max_x.max(vertex.position.x);
+        }
+        assert!((min_x + 2.0).abs() < EPSILON);
+        assert!((max_x - 12.0).abs() < EPSILON);
+    }
+}
+
+#[test]
+fn test_arcs_join() {
+    // A line followed by an arc of radius 10 that turns left and then curves to the right,
+    // and the same path mirrored.
+    for &sign in &[1.0, -1.0] {
+        let mut builder = Path::builder();
+        builder.move_to(point(-10.0, 0.0));
+        builder.line_to(point(0.0, 0.0));
+        builder.arc(
+            point(10.0, 0.0),
+            vector(10.0, 10.0),
+            Angle::radians(-PI * 0.5 * sign),
+            Angle::radians(0.0),
+        );
+        let path = builder.build();
+
+        let options = StrokeOptions::tolerance(0.0001).with_line_width(2.0);
+
+        // How far the outer edge of the line (at y = -sign) is extended by the join.
+        let join_length = |options: &StrokeOptions| {
+            let mut buffers: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+            StrokeTessellator::new().tessellate_path(
+                &path,
+                options,
+                &mut simple_builder(&mut buffers),
+            ).unwrap();
+
+            buffers.vertices.iter()
+                .filter(|v| (v.position.y + sign).abs() < 0.001 && v.position.x < 5.0)
+                .fold(0.0f32, |x, v| x.max(v.position.x))
+        };
+
+        // The outer edge of the line is extended along y = -sign and the arc's along its circle
+        // of radius 9, so they intersect a bit further than the miter. The curvature of the
+        // flattened arc is only approximately the one of the circle.
+        let arcs_tip = 10.0 - 80.0f32.sqrt();
+        let arcs = join_length(&options.with_line_join(LineJoin::Arcs));
+        assert!(arcs > 1.02 && arcs < arcs_tip + 0.001, "{}", arcs);
+        assert!(join_length(&options.with_line_join(LineJoin::Miter)) < 1.001);
+
+        // Clipped at the miter limit.
+        let options = options.with_line_join(LineJoin::Arcs).with_miter_limit(1.02);
+        let clip = 1.02 * 2.0f32.sqrt() - 1.0;
+        assert!((join_length(&options) - clip).abs() < 0.001);
+
+        test_path(path.as_slice(), &options.with_tolerance(0.01).with_line_width(0.5), None);
+    }
+}
+
+#[test]
+fn test_arcs_join_logo() {
+    use crate::extra::rust_logo::build_logo_path;
+
+    let mut builder = Path::builder().with_svg();
+    build_logo_path(&mut builder);
+    let path = builder.build();
+
+    for &(line_width, miter_limit) in &[(1.0, 4.0), (3.0, 1.5), (10.0, 10.0)] {
+        let options = StrokeOptions::default()
+            .with_line_join(LineJoin::Arcs)
+            .with_line_width(line_width)
+            .with_miter_limit(miter_limit);
+
+        let mut buffers: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+        StrokeTessellator::new().tessellate_path(
+            &path,
+            &options,
+            &mut simple_builder(&mut buffers),
+        ).unwrap();
+
+        for vertex in &buffers.vertices {
+            assert!(vertex.position.x.is_finite());
+            assert!(vertex.position.y.is_finite());
+        }
+    }
+}
@@ -0,0 +1,257 @@
+//! Tessellation of overlapping paths into a planar map.
+//!
+//! The paths are stacked in z-order and the parts of each path that are hidden by the
+//! paths above it are removed, so that the output does not contain overlapping triangles.
+//!
+//! The edges of all paths are snapped to the grid of the robust mode and split where they
+//! intersect, like in robust mode, so that they only touch at their endpoints. Like the
+//! sweep line of the fill tessellator, we then go through the edges from top to bottom,
+//! keeping the ones that cross the sweep line sorted along the x axis. When edges are
+//! inserted, the winding number of each path on the left of the insertion point tells us
+//! which paths cover the regions on each side of the new edges. The topmost of these paths
+//! owns the region.
+//!
+//! The edges that separate two regions with different owners form the outline of the
+//! visible part of each of the owners, which is then tessellated with the fill tessellator.
+
+use crate::math_utils::orientation;
+use crate::path::PathEvent;
+use crate::path_fill::{OrientedEdge, EdgeSource, split_intersecting_edges, push_snapped_edge, compare_positions};
+use crate::math_utils::snap_to_grid;
+use crate::{FillEvents, FillRule, PathId};
+
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+
+/// An edge of the map, which can be shared by several paths.
+struct MapEdge {
+    edge: OrientedEdge,
+    // The range of the edge's paths in `PlanarMap::contributions`.
+    contributions: (usize, usize),
+}
+
+impl MapEdge {
+    fn contributions<'l>(&self, contributions: &'l [Contribution]) -> &'l [Contribution] {
+        &contributions[self.contributions.0..self.contributions.1]
+    }
+}
+
+/// A path that contributes to an edge of the map, with its winding and source.
+#[derive(Copy, Clone)]
+struct Contribution {
+    path: usize,
+    winding: i16,
+    source: EdgeSource,
+}
+
+/// The winding number of each path at a position of the sweep line.
+struct Windings {
+    fill_rule: FillRule,
+    windings: Vec<i32>,
+    // The paths that cover the position.
+    covering: BTreeSet<usize>,
+}
+
+impl Windings {
+    fn cross(&mut self, contributions: &[Contribution]) {
+        for c in contributions {
+            let winding = &mut self.windings[c.path];
+            *winding += c.winding as i32;
+            let inside = match self.fill_rule {
+                FillRule::EvenOdd => *winding % 2 != 0,
+                FillRule::NonZero => *winding != 0,
+            };
+            if inside {
+                self.covering.insert(c.path);
+            } else {
+                self.covering.remove(&c.path);
+            }
+        }
+    }
+
+    // The topmost path that covers the position.
+    fn owner(&self) -> Option<usize> {
+        self.covering.iter().next_back().cloned()
+    }
+
+    fn reset(&mut self, contributions: &[Contribution]) {
+        for c in contributions {
+            self.windings[c.path] = 0;
+        }
+        self.covering.clear();
+    }
+}
+
+pub(crate) struct PlanarMap {
+    ids: Vec<PathId>,
+    edges: Vec<OrientedEdge>,
+    // The path of each edge.
+    paths: Vec<usize>,
+    events: FillEvents,
+}
+
+impl PlanarMap {
+    pub fn new() -> Self {
+        PlanarMap {
+            ids: Vec::new(),
+            edges: Vec::new(),
+            paths: Vec::new(),
+            events: FillEvents::new(),
+        }
+    }
+
+    /// Add a path on top of the previous ones.
+    pub fn add_path<Iter>(&mut self, id: PathId, it: Iter, tolerance: f32)
+    where
+        Iter: Iterator<Item = PathEvent>,
+    {
+        let path = self.ids.len();
+        self.ids.push(id);
+
+        self.events.set_path(tolerance, it);
+        for edge in &self.events.edges {
+            push_snapped_edge(&mut self.edges, snap_to_grid(edge.upper), snap_to_grid(edge.lower), edge.winding, edge.source);
+        }
+        self.paths.resize(self.edges.len(), path);
+    }
+
+    /// Compute the outline of the visible part of each path.
+    ///
+    /// The outlines are given with the id of their path, for the paths that are visible.
+    pub fn build(&mut self, fill_rule: FillRule) -> Vec<(PathId, FillEvents)> {
+        split_intersecting_edges(&mut self.edges, &mut self.paths);
+
+        // Merge the edges that overlap, and sort them by their upper endpoint and from
+        // left to right below it.
+        let mut order: Vec<usize> = (0..self.edges.len()).collect();
+        {
+            let edges = &self.edges;
+            order.sort_by(|&a, &b| {
+                let (a, b) = (&edges[a], &edges[b]);
+                compare_positions(a.upper, b.upper).then_with(|| {
+                    match orientation(a.upper, a.lower, b.lower) {
+                        0 => compare_positions(a.lower, b.lower),
+                        o if o < 0 => Ordering::Less,
+                        _ => Ordering::Greater,
+                    }
+                })
+            });
+        }
+
+        let mut map_edges: Vec<MapEdge> = Vec::with_capacity(order.len());
+        let mut contributions = Vec::with_capacity(order.len());
+        for &idx in &order {
+            let edge = self.edges[idx];
+            let merge = match map_edges.last() {
+                Some(prev) => prev.edge.upper == edge.upper && prev.edge.lower == edge.lower,
+                None => false,
+            };
+            if !merge {
+                map_edges.push(MapEdge {
+                    edge,
+                    contributions: (contributions.len(), contributions.len()),
+                });
+            }
+            contributions.push(Contribution {
+                path: self.paths[idx],
+                winding: edge.winding,
+                source: edge.source,
+            });
+            map_edges.last_mut().unwrap().contributions.1 = contributions.len();
+        }
+
+        // The owners of the regions on the left and right sides of each edge.
+        let mut sides = vec![(None, None); map_edges.len()];
+
+        let mut state = Windings {
+            fill_rule,
+            windings: vec![0; self.ids.len()],
+            covering: BTreeSet::new(),
+        };
+
+        // The edges that cross the sweep line, from left to right.
+        let mut active: Vec<usize> = Vec::new();
+        let mut i = 0;
+        while i < map_edges.len() {
+            let position = map_edges[i].edge.upper;
+            let mut end = i + 1;
+            while end < map_edges.len() && map_edges[end].edge.upper == position {
+                end += 1;
+            }
+
+            active.retain(|&e| compare_positions(map_edges[e].edge.lower, position) == Ordering::Greater);
+
+            // The edges that remain don't touch the current position, so it is either on
+            // their left or on their right.
+            let mut insert_idx = 0;
+            for &e in &active {
+                let edge = &map_edges[e].edge;
+                if orientation(edge.upper, edge.lower, position) > 0 {
+                    break;
+                }
+                state.cross(map_edges[e].contributions(&contributions));
+                insert_idx += 1;
+            }
+
+            for (map_edge, side) in map_edges[i..end].iter().zip(&mut sides[i..end]) {
+                let left = state.owner();
+                state.cross(map_edge.contributions(&contributions));
+                *side = (left, state.owner());
+            }
+
+            // Paths that aren't closed properly may leave some state behind.
+            for &e in &active[..insert_idx] {
+                state.reset(map_edges[e].contributions(&contributions));
+            }
+            for map_edge in &map_edges[i..end] {
+                state.reset(map_edge.contributions(&contributions));
+            }
+
+            active.splice(insert_idx..insert_idx, i..end);
+            i = end;
+        }
+
+        // The edges that separate two regions with different owners are part of the
+        // outline of both owners.
+        let mut outlines: Vec<FillEvents> = (0..self.ids.len()).map(|_| FillEvents::new()).collect();
+        for (map_edge, &(left, right)) in map_edges.iter().zip(&sides) {
+            if left == right {
+                continue;
+            }
+            for &(owner, winding) in &[(left, -1), (right, 1)] {
+                let owner = match owner {
+                    Some(owner) => owner,
+                    None => { continue; }
+                };
+
+                // The vertices that are only on the edges of other paths have no source.
+                let source = match map_edge.contributions(&contributions).iter().find(|c| c.path == owner) {
+                    Some(c) => c.source,
+                    None => EdgeSource::CLIP,
+                };
+
+                outlines[owner].edges.push(OrientedEdge {
+                    upper: map_edge.edge.upper,
+                    lower: map_edge.edge.lower,
+                    winding,
+                    source,
+                });
+            }
+        }
+
+        let mut result = Vec::new();
+        for (path, mut outline) in outlines.into_iter().enumerate() {
+            if outline.edges.is_empty() {
+                continue;
+            }
+            for edge in &outline.edges {
+                outline.vertices.push(edge.lower);
+            }
+            outline.vertices.sort_by(|a, b| compare_positions(*a, *b));
+            outline.vertices.dedup();
+            result.push((self.ids[path], outline));
+        }
+
+        result
+    }
+}
@@ -0,0 +1,200 @@
+//! Support for the polygon output of the fill tessellator.
+
+use crate::geom::math::*;
+use crate::geometry_builder::{GeometryBuilder, PolygonGeometryBuilder, GeometryBuilderError, VertexId, Count};
+use crate::FillVertex;
+
+use std::collections::HashMap;
+
+/// Lets the fill tessellator write into a `PolygonGeometryBuilder`.
+///
+/// Vertices are forwarded to the output while triangles are only recorded
+/// (if needed) in order to be merged into convex polygons at the end.
+pub(crate) struct PolygonCollector<'l> {
+    pub output: &'l mut dyn PolygonGeometryBuilder<FillVertex>,
+    record_triangles: bool,
+    positions: HashMap<VertexId, Point>,
+    triangles: Vec<[VertexId; 3]>,
+}
+
+impl<'l> PolygonCollector<'l> {
+    pub fn new(output: &'l mut dyn PolygonGeometryBuilder<FillVertex>, record_triangles: bool) -> Self {
+        PolygonCollector {
+            output,
+            record_triangles,
+            positions: HashMap::new(),
+            triangles: Vec::new(),
+        }
+    }
+
+    /// Merge the recorded triangles into convex polygons and send them to the output.
+    ///
+    /// This is a greedy variant of the Hertel-Mehlhorn algorithm: each diagonal
+    /// of the triangulation is removed if the resulting polygon remains convex.
+    pub fn flush_convex_polygons(&mut self) {
+        let mut polygons: Vec<Vec<VertexId>> = self.triangles.iter().map(|tri| tri.to_vec()).collect();
+        let mut owners = HashMap::with_capacity(self.triangles.len() * 3);
+        for (idx, tri) in self.triangles.iter().enumerate() {
+            for i in 0..3 {
+                owners.insert((tri[i], tri[(i + 1) % 3]), idx);
+            }
+        }
+
+        for tri in &self.triangles {
+            for i in 0..3 {
+                let (a, b) = (tri[i], tri[(i + 1) % 3]);
+                // Only visit each diagonal once.
+                if a.0 > b.0 {
+                    continue;
+                }
+
+                let (p, q) = match (owners.get(&(a, b)), owners.get(&(b, a))) {
+                    (Some(&p), Some(&q)) if p != q => (p, q),
+                    _ => { continue; }
+                };
+
+                let merged = merge_polygons(&polygons[p], &polygons[q], a);
+                if !self.is_convex_at(&merged, 0) || !self.is_convex_at(&merged, polygons[p].len() - 1) {
+                    continue;
+                }
+
+                owners.remove(&(a, b));
+                owners.remove(&(b, a));
+                let removed = &polygons[q];
+                for i in 0..removed.len() {
+                    let edge = (removed[i], removed[(i + 1) % removed.len()]);
+                    if let Some(owner) = owners.get_mut(&edge) {
+                        *owner = p;
+                    }
+                }
+                polygons[q].clear();
+                polygons[p] = merged;
+            }
+        }
+
+        for polygon in &polygons {
+            if polygon.len() >= 3 {
+                self.output.add_polygon(polygon);
+            }
+        }
+
+        self.triangles.clear();
+        self.positions.clear();
+    }
+
+    fn is_convex_at(&self, polygon: &[VertexId], idx: usize) -> bool {
+        let n = polygon.len();
+        let prev = self.positions[&polygon[(idx + n - 1) % n]];
+        let current = self.positions[&polygon[idx]];
+        let next = self.positions[&polygon[(idx + 1) % n]];
+
+        let a = prev - current;
+        let b = next - current;
+        let cross = a.cross(b);
+
+        cross > 0.0 || (cross == 0.0 && a.dot(b) <= 0.0)
+    }
+}
+
+// Merge two polygons that share the edge `a -> b` (in `p`), `b -> a` (in `q`).
+//
+// The returned polygon starts at `b` and `a` is at index `p.len() - 1`.
+fn merge_polygons(p: &[VertexId], q: &[VertexId], a: VertexId) -> Vec<VertexId> {
+    let mut merged = Vec::with_capacity(p.len() + q.len() - 2);
+
+    let ia = p.iter().position(|&id| id == a).unwrap();
+    for i in 1..=p.len() {
+        merged.push(p[(ia + i) % p.len()]);
+    }
+
+    let ia = q.iter().position(|&id| id == a).unwrap();
+    for i in 1..(q.len() - 1) {
+        merged.push(q[(ia + i) % q.len()]);
+    }
+
+    merged
+}
+
+impl<'l> GeometryBuilder<FillVertex> for PolygonCollector<'l> {
+    fn begin_geometry(&mut self) {
+        self.triangles.clear();
+        self.positions.clear();
+        self.output.begin_geometry();
+    }
+
+    fn end_geometry(&mut self) -> Count {
+        self.output.end_geometry()
+    }
+
+    fn abort_geometry(&mut self) {
+        self.triangles.clear();
+        self.positions.clear();
+        self.output.abort_geometry();
+    }
+
+    fn add_vertex(&mut self, vertex: FillVertex) -> Result<VertexId, GeometryBuilderError> {
+        let id = self.output.add_vertex(vertex)?;
+        if self.record_triangles {
+            self.positions.insert(id, vertex.position);
+        }
+
+        Ok(id)
+    }
+
+    fn add_triangle(&mut self, a: VertexId, b: VertexId, c: VertexId) {
+        if self.record_triangles {
+            self.triangles.push([a, b, c]);
+        }
+    }
+}
+
+#[test]
+fn test_merge_square() {
+    use crate::geometry_builder::NoOutput;
+
+    struct Polygons {
+        polygons: Vec<Vec<VertexId>>,
+        output: NoOutput,
+    }
+
+    impl GeometryBuilder<FillVertex> for Polygons {
+        fn begin_geometry(&mut self) { GeometryBuilder::<FillVertex>::begin_geometry(&mut self.output) }
+        fn end_geometry(&mut self) -> Count { GeometryBuilder::<FillVertex>::end_geometry(&mut self.output) }
+        fn abort_geometry(&mut self) {}
+        fn add_vertex(&mut self, v: FillVertex) -> Result<VertexId, GeometryBuilderError> {
+            self.output.add_vertex(v)
+        }
+        fn add_triangle(&mut self, _: VertexId, _: VertexId, _: VertexId) {
+            panic!();
+        }
+    }
+
+    impl PolygonGeometryBuilder<FillVertex> for Polygons {
+        fn add_polygon(&mut self, vertices: &[VertexId]) {
+            self.polygons.push(vertices.to_vec());
+        }
+    }
+
+    let mut output = Polygons { polygons: Vec::new(), output: NoOutput::new() };
+    {
+        let mut collector = PolygonCollector::new(&mut output, true);
+        collector.begin_geometry();
+        let mut ids = Vec::new();
+        // A square with an extra point in the middle of its right side.
+        for &(x, y) in &[(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (1.0, 0.5), (1.0, 0.0)] {
+            ids.push(collector.add_vertex(FillVertex {
+                position: point(x, y),
+                normal: vector(0.0, 0.0),
+                source: crate::VertexSource::Unknown,
+            }).unwrap());
+        }
+        collector.add_triangle(ids[0], ids[1], ids[2]);
+        collector.add_triangle(ids[0], ids[2], ids[3]);
+        collector.add_triangle(ids[0], ids[3], ids[4]);
+        collector.flush_convex_polygons();
+        collector.end_geometry();
+    }
+
+    assert_eq!(output.polygons.len(), 1);
+    assert_eq!(output.polygons[0].len(), 5);
+}
@@ -0,0 +1,789 @@
+//! Post-processing of the generated geometry.
+//!
+//! The tessellators favor speed over producing the most compact meshes. For example the
+//! stroke tessellator can emit the same position several times around joins, and the
+//! order of the triangles follows the sweep line rather than what works best for the
+//! GPU's post-transform vertex cache. The functions in this module work on any
+//! `VertexBuffers` and can be used to improve the geometry once it is generated:
+//!
+//! * `weld_vertices` merges vertices that are within a given distance of each other.
+//! * `remove_degenerate_triangles` removes triangles that don't cover any area.
+//! * `remove_unused_vertices` removes the vertices that aren't referred to by any triangle.
+//! * `optimize_vertex_cache` reorders the triangles to improve the cache hit rate, using
+//!   Tom Forsyth's linear-speed vertex cache optimization algorithm.
+//! * `triangle_strips` and `triangle_fans` convert the triangles into strips or fans separated
+//!   by a primitive restart index, and `triangle_list` converts them back.
+//!
+//! The vertices are only ever copied or removed, so custom vertex types are preserved.
+//! Since the functions don't know the layout of the vertices, they take a callback that
+//! returns the position of a vertex.
+//!
+//! ## Example
+//!
+//! ```
+//! # extern crate lyon_tessellation as tess;
+//! # use tess::path::Path;
+//! # use tess::math::point;
+//! # use tess::{StrokeTessellator, StrokeOptions, StrokeVertex, VertexBuffers, BuffersBuilder};
+//! # use tess::geometry_builder::Identity;
+//! # use tess::post_processing::*;
+//! # fn main() {
+//! # let mut builder = Path::builder();
+//! # builder.polygon(&[point(0.0, 0.0), point(10.0, 0.0), point(10.0, 10.0)]);
+//! # let path = builder.build();
+//! let mut buffers: VertexBuffers<StrokeVertex, u16> = VertexBuffers::new();
+//! StrokeTessellator::new().tessellate_path(
+//!     path.iter(),
+//!     &StrokeOptions::default(),
+//!     &mut BuffersBuilder::new(&mut buffers, Identity),
+//! ).unwrap();
+//!
+//! weld_vertices(&mut buffers, 0.001, |v| v.position);
+//! remove_degenerate_triangles(&mut buffers, |v| v.position);
+//! remove_unused_vertices(&mut buffers);
+//! optimize_vertex_cache(&mut buffers.indices, buffers.vertices.len(), 32);
+//! # }
+//! ```
+
+use crate::geometry_builder::{VertexBuffers, VertexId};
+use crate::math::Point;
+
+use std::collections::HashMap;
+use std::collections::VecDeque;
+
+/// Merges the vertices that are closer than `epsilon` to each other.
+///
+/// The first of the merged vertices is kept, with its attributes. Triangles that become
+/// degenerate are not removed (see `remove_degenerate_triangles`).
+pub fn weld_vertices<V, I, P>(buffers: &mut VertexBuffers<V, I>, epsilon: f32, position: P)
+where
+    V: Clone,
+    I: Copy + From<VertexId>,
+    VertexId: From<I>,
+    P: Fn(&V) -> Point,
+{
+    weld_vertices_with(buffers, epsilon, position, |_, _| true);
+}
+
+/// Merges the vertices that are closer than `epsilon` to each other and for which
+/// `can_merge` returns true.
+///
+/// This is useful to avoid merging vertices that have the same position but different
+/// attributes, for example the vertices on each side of a sharp corner.
+pub fn weld_vertices_with<V, I, P, M>(
+    buffers: &mut VertexBuffers<V, I>,
+    epsilon: f32,
+    position: P,
+    can_merge: M,
+)
+where
+    V: Clone,
+    I: Copy + From<VertexId>,
+    VertexId: From<I>,
+    P: Fn(&V) -> Point,
+    M: Fn(&V, &V) -> bool,
+{
+    // Vertices are binned in a grid with cells of size epsilon so that only the
+    // neighboring cells need to be looked at.
+    let cell_size = if epsilon > 0.0 { epsilon } else { 1.0 };
+    let cell = |p: Point| ((p.x / cell_size).floor() as i64, (p.y / cell_size).floor() as i64);
+
+    let mut grid: HashMap<(i64, i64), Vec<u32>> = HashMap::new();
+    let mut remap = Vec::with_capacity(buffers.vertices.len());
+    let mut vertices = Vec::with_capacity(buffers.vertices.len());
+
+    for vertex in &buffers.vertices {
+        let p = position(vertex);
+        let (cx, cy) = cell(p);
+
+        let mut found = None;
+        'search: for x in (cx - 1)..=(cx + 1) {
+            for y in (cy - 1)..=(cy + 1) {
+                if let Some(candidates) = grid.get(&(x, y)) {
+                    for &idx in candidates {
+                        let other = &vertices[idx as usize];
+                        if (position(other) - p).length() <= epsilon && can_merge(other, vertex) {
+                            found = Some(idx);
+                            break 'search;
+                        }
+                    }
+                }
+            }
+        }
+
+        let idx = match found {
+            Some(idx) => idx,
+            None => {
+                let idx = vertices.len() as u32;
+                vertices.push(vertex.clone());
+                grid.entry((cx, cy)).or_default().push(idx);
+                idx
+            }
+        };
+
+        remap.push(idx);
+    }
+
+    for index in &mut buffers.indices {
+        let old = VertexId::from(*index).to_usize();
+        *index = I::from(VertexId(remap[old]));
+    }
+
+    buffers.vertices = vertices;
+}
+
+/// Removes the triangles that have the same vertex more than once or that have
+/// a null area.
+pub fn remove_degenerate_triangles<V, I, P>(buffers: &mut VertexBuffers<V, I>, position: P)
+where
+    I: Copy + From<VertexId>,
+    VertexId: From<I>,
+    P: Fn(&V) -> Point,
+{
+    let vertices = &buffers.vertices;
+    let mut dst = 0;
+    for src in 0..(buffers.indices.len() / 3) {
+        let a = VertexId::from(buffers.indices[src * 3]);
+        let b = VertexId::from(buffers.indices[src * 3 + 1]);
+        let c = VertexId::from(buffers.indices[src * 3 + 2]);
+
+        if a == b || b == c || a == c {
+            continue;
+        }
+
+        let pa = position(&vertices[a.to_usize()]);
+        let pb = position(&vertices[b.to_usize()]);
+        let pc = position(&vertices[c.to_usize()]);
+        if (pb - pa).cross(pc - pa) == 0.0 {
+            continue;
+        }
+
+        for i in 0..3 {
+            buffers.indices[dst * 3 + i] = buffers.indices[src * 3 + i];
+        }
+        dst += 1;
+    }
+
+    buffers.indices.truncate(dst * 3);
+}
+
+/// Removes the vertices that are not used by any triangle.
+///
+/// The order of the remaining vertices is preserved.
+pub fn remove_unused_vertices<V, I>(buffers: &mut VertexBuffers<V, I>)
+where
+    I: Copy + From<VertexId>,
+    VertexId: From<I>,
+{
+    let mut used = vec![false; buffers.vertices.len()];
+    for &index in &buffers.indices {
+        used[VertexId::from(index).to_usize()] = true;
+    }
+
+    let mut remap = vec![0; buffers.vertices.len()];
+    let mut dst = 0;
+    for src in 0..buffers.vertices.len() {
+        if used[src] {
+            remap[src] = dst as u32;
+            buffers.vertices.swap(dst, src);
+            dst += 1;
+        }
+    }
+    buffers.vertices.truncate(dst);
+
+    for index in &mut buffers.indices {
+        let old = VertexId::from(*index).to_usize();
+        *index = I::from(VertexId(remap[old]));
+    }
+}
+
+// Parameters of the scoring function, from Tom Forsyth's article.
+const LAST_TRIANGLE_SCORE: f32 = 0.75;
+const CACHE_DECAY_POWER: f32 = 1.5;
+const VALENCE_BOOST_SCALE: f32 = 2.0;
+const VALENCE_BOOST_POWER: f32 = 0.5;
+
+fn vertex_score(cache_position: Option<usize>, remaining_triangles: u32, cache_size: usize) -> f32 {
+    if remaining_triangles == 0 {
+        return -1.0;
+    }
+
+    let mut score = match cache_position {
+        // The vertices of the last triangle get a fixed score, to avoid
+        // favoring the triangle that was just added.
+        Some(pos) if pos < 3 => LAST_TRIANGLE_SCORE,
+        Some(pos) => {
+            let scale = 1.0 / (cache_size - 3) as f32;
+            (1.0 - (pos - 3) as f32 * scale).powf(CACHE_DECAY_POWER)
+        }
+        None => 0.0,
+    };
+
+    // Boost the vertices that have few triangles left, to get rid of them first.
+    score += VALENCE_BOOST_SCALE * (remaining_triangles as f32).powf(-VALENCE_BOOST_POWER);
+
+    score
+}
+
+/// Reorders the triangles to improve the hit rate of the GPU's post-transform vertex cache.
+///
+/// `cache_size` is the size of the modeled LRU cache (32 is a good default). The vertices
+/// are not modified, and the winding of the triangles is preserved.
+///
+/// This implements Tom Forsyth's
+/// [linear-speed vertex cache optimization](https://tomforsyth1000.github.io/papers/fast_vert_cache_opt.html).
+pub fn optimize_vertex_cache<I>(indices: &mut [I], num_vertices: usize, cache_size: usize)
+where
+    I: Copy + From<VertexId>,
+    VertexId: From<I>,
+{
+    assert!(cache_size > 3);
+
+    let num_triangles = indices.len() / 3;
+    if num_triangles == 0 {
+        return;
+    }
+
+    let vertex = |tri: usize, i: usize| VertexId::from(indices[tri * 3 + i]).to_usize();
+
+    // The triangles of each vertex, in a single array.
+    let mut remaining = vec![0u32; num_vertices];
+    for tri in 0..num_triangles {
+        for i in 0..3 {
+            remaining[vertex(tri, i)] += 1;
+        }
+    }
+    let mut offsets = Vec::with_capacity(num_vertices + 1);
+    let mut offset = 0;
+    for &count in &remaining {
+        offsets.push(offset);
+        offset += count as usize;
+    }
+    offsets.push(offset);
+    let mut vertex_triangles = vec![0u32; offset];
+    let mut fill = offsets.clone();
+    for tri in 0..num_triangles {
+        for i in 0..3 {
+            let v = vertex(tri, i);
+            vertex_triangles[fill[v]] = tri as u32;
+            fill[v] += 1;
+        }
+    }
+
+    let mut cache_position: Vec<Option<usize>> = vec![None; num_vertices];
+    let mut vertex_scores: Vec<f32> = (0..num_vertices).map(|v| {
+        vertex_score(None, remaining[v], cache_size)
+    }).collect();
+    let mut triangle_scores: Vec<f32> = (0..num_triangles).map(|tri| {
+        (0..3).map(|i| vertex_scores[vertex(tri, i)]).sum()
+    }).collect();
+    let mut added = vec![false; num_triangles];
+
+    let mut cache: VecDeque<usize> = VecDeque::with_capacity(cache_size + 3);
+    let mut output: Vec<I> = Vec::with_capacity(indices.len());
+    // All triangles before this one have been added.
+    let mut search_start = 0;
+
+    let mut best = None;
+    for _ in 0..num_triangles {
+        let tri = match best {
+            Some(tri) => tri,
+            None => {
+                // Linear search, only happens when the cache doesn't help.
+                while added[search_start] {
+                    search_start += 1;
+                }
+                let mut best_tri = None;
+                let mut best_score = -1.0;
+                for tri in search_start..num_triangles {
+                    if !added[tri] && triangle_scores[tri] > best_score {
+                        best_score = triangle_scores[tri];
+                        best_tri = Some(tri);
+                    }
+                }
+                best_tri.unwrap()
+            }
+        };
+
+        added[tri] = true;
+        for i in 0..3 {
+            output.push(indices[tri * 3 + i]);
+        }
+
+        // Move the vertices of the triangle to the front of the cache and remove the
+        // triangle from their lists.
+        for i in (0..3).rev() {
+            let v = vertex(tri, i);
+            if let Some(pos) = cache.iter().position(|&cached| cached == v) {
+                cache.remove(pos);
+            }
+            cache.push_front(v);
+
+            remaining[v] -= 1;
+            let start = offsets[v];
+            let end = start + remaining[v] as usize + 1;
+            let list = &mut vertex_triangles[start..end];
+            let pos = list.iter().position(|&t| t as usize == tri).unwrap();
+            list.swap(pos, end - start - 1);
+        }
+
+        // Update the scores of the vertices in the cache and of the ones that were
+        // just evicted, along with the scores of their triangles.
+        let mut evicted = Vec::new();
+        while cache.len() > cache_size {
+            evicted.push(cache.pop_back().unwrap());
+        }
+        for &v in &evicted {
+            cache_position[v] = None;
+        }
+        for (pos, &v) in cache.iter().enumerate() {
+            cache_position[v] = Some(pos);
+        }
+
+        best = None;
+        let mut best_score = -1.0;
+        for &v in cache.iter().chain(evicted.iter()) {
+            let score = vertex_score(cache_position[v], remaining[v], cache_size);
+            let delta = score - vertex_scores[v];
+            vertex_scores[v] = score;
+
+            let start = offsets[v];
+            for &t in &vertex_triangles[start..start + remaining[v] as usize] {
+                let t = t as usize;
+                triangle_scores[t] += delta;
+                if cache_position[v].is_some() && triangle_scores[t] > best_score {
+                    best_score = triangle_scores[t];
+                    best = Some(t);
+                }
+            }
+        }
+
+        // The scores changed after the triangles were compared, so look again at
+        // the triangles of the cached vertices with their final scores.
+        if best.is_some() {
+            best_score = -1.0;
+            for &v in cache.iter() {
+                let start = offsets[v];
+                for &t in &vertex_triangles[start..start + remaining[v] as usize] {
+                    let t = t as usize;
+                    if triangle_scores[t] > best_score {
+                        best_score = triangle_scores[t];
+                        best = Some(t);
+                    }
+                }
+            }
+        }
+    }
+
+    indices.copy_from_slice(&output);
+}
+
+/// Computes the average number of vertex cache misses per triangle (ACMR) of a list of
+/// indices, with a FIFO cache of a given size.
+///
+/// This is useful to measure the effect of `optimize_vertex_cache`. The result is
+/// between 0.5 (for large regular meshes) and 3.0 (every vertex is a miss).
+pub fn average_cache_miss_ratio<I>(indices: &[I], cache_size: usize) -> f32
+where
+    I: Copy,
+    VertexId: From<I>,
+{
+    if indices.len() < 3 {
+        return 0.0;
+    }
+
+    let mut cache: VecDeque<VertexId> = VecDeque::with_capacity(cache_size + 1);
+    let mut misses = 0;
+    for &index in indices {
+        let v = VertexId::from(index);
+        if !cache.contains(&v) {
+            misses += 1;
+            cache.push_back(v);
+            if cache.len() > cache_size {
+                cache.pop_front();
+            }
+        }
+    }
+
+    misses as f32 / (indices.len() / 3) as f32
+}
+
+/// The kind of primitive generated by `triangle_strips` and `triangle_fans`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Topology {
+    Strip,
+    Fan,
+}
+
+/// Converts a list of triangles into triangle strips separated by a primitive restart index.
+///
+/// The strips are built greedily by following the edges shared between adjacent triangles.
+/// The winding of the triangles is preserved, following the usual convention that every
+/// other triangle of a strip has its first two vertices swapped. Triangles that share an
+/// edge but have opposite winding end up in separate strips.
+///
+/// `restart` is the index that separates the strips (typically `u16::MAX` or `u32::MAX`,
+/// depending on the index type). It must not be used by any vertex.
+pub fn triangle_strips<I>(indices: &[I], restart: I) -> Vec<I>
+where
+    I: Copy + From<VertexId>,
+    VertexId: From<I>,
+{
+    build_primitives(indices, restart, Topology::Strip)
+}
+
+/// Converts a list of triangles into triangle fans separated by a primitive restart index.
+///
+/// The triangles around a shared vertex are grouped in fans. The fill tessellator's output
+/// contains a lot of those, whereas stroke output is better suited to `triangle_strips`.
+/// The winding of the triangles is preserved.
+///
+/// `restart` is the index that separates the fans. It must not be used by any vertex.
+pub fn triangle_fans<I>(indices: &[I], restart: I) -> Vec<I>
+where
+    I: Copy + From<VertexId>,
+    VertexId: From<I>,
+{
+    build_primitives(indices, restart, Topology::Fan)
+}
+
+/// Converts restart-separated triangle strips or fans back into a list of triangles.
+///
+/// Degenerate triangles (that use the same vertex more than once) are skipped.
+pub fn triangle_list<I>(primitives: &[I], restart: I, topology: Topology) -> Vec<I>
+where
+    I: Copy,
+    VertexId: From<I>,
+{
+    let restart = VertexId::from(restart);
+    let mut output = Vec::new();
+    for primitive in primitives.split(|&index| VertexId::from(index) == restart) {
+        for i in 2..primitive.len() {
+            let tri = match topology {
+                Topology::Strip if i % 2 == 1 => [primitive[i - 1], primitive[i - 2], primitive[i]],
+                Topology::Strip => [primitive[i - 2], primitive[i - 1], primitive[i]],
+                Topology::Fan => [primitive[0], primitive[i - 1], primitive[i]],
+            };
+            let (a, b, c) = (VertexId::from(tri[0]), VertexId::from(tri[1]), VertexId::from(tri[2]));
+            if a == b || b == c || a == c {
+                continue;
+            }
+            output.extend_from_slice(&tri);
+        }
+    }
+
+    output
+}
+
+fn build_primitives<I>(indices: &[I], restart: I, topology: Topology) -> Vec<I>
+where
+    I: Copy + From<VertexId>,
+    VertexId: From<I>,
+{
+    let num_triangles = indices.len() / 3;
+    let vertex = |tri: usize, i: usize| VertexId::from(indices[tri * 3 + i]);
+
+    // Adjacent triangles with the same winding share an edge in opposite directions,
+    // so the triangles are looked up by their directed edges.
+    let mut edges: HashMap<(VertexId, VertexId), Vec<u32>> = HashMap::new();
+    for tri in 0..num_triangles {
+        for i in 0..3 {
+            edges.entry((vertex(tri, i), vertex(tri, (i + 1) % 3))).or_default().push(tri as u32);
+        }
+    }
+
+    let mut added = vec![false; num_triangles];
+    let find = |added: &[bool], from: VertexId, to: VertexId| -> Option<(usize, VertexId)> {
+        for &tri in edges.get(&(from, to))? {
+            let tri = tri as usize;
+            if added[tri] {
+                continue;
+            }
+            for i in 0..3 {
+                if vertex(tri, i) == from {
+                    return Some((tri, vertex(tri, (i + 2) % 3)));
+                }
+            }
+        }
+
+        None
+    };
+
+    // Starting the primitives at the triangles that have the fewest neighbors avoids
+    // cutting chains of triangles in the middle.
+    let mut start_order: Vec<usize> = (0..num_triangles).collect();
+    start_order.sort_by_key(|&tri| {
+        (0..3).filter(|&i| edges.contains_key(&(vertex(tri, (i + 1) % 3), vertex(tri, i)))).count()
+    });
+
+    let mut output = Vec::with_capacity(indices.len() + indices.len() / 3);
+    let mut primitive: Vec<VertexId> = Vec::new();
+    for first in start_order {
+        if added[first] {
+            continue;
+        }
+        added[first] = true;
+
+        // Start with the rotation of the first triangle that can be continued, if any.
+        let mut rotation = 0;
+        for r in 0..3 {
+            let (a, b, c) = (vertex(first, r), vertex(first, (r + 1) % 3), vertex(first, (r + 2) % 3));
+            let next = match topology {
+                Topology::Strip => find(&added, c, b),
+                Topology::Fan => find(&added, a, c),
+            };
+            if next.is_some() {
+                rotation = r;
+                break;
+            }
+        }
+
+        primitive.clear();
+        for i in 0..3 {
+            primitive.push(vertex(first, (rotation + i) % 3));
+        }
+
+        loop {
+            let n = primitive.len();
+            let last = primitive[n - 1];
+            let (from, to) = match topology {
+                // The next triangle of the strip is (n-1, n-2, next) if it is at an
+                // odd position and (n-2, n-1, next) otherwise.
+                Topology::Strip if n % 2 == 1 => (last, primitive[n - 2]),
+                Topology::Strip => (primitive[n - 2], last),
+                Topology::Fan => (primitive[0], last),
+            };
+
+            match find(&added, from, to) {
+                Some((tri, next)) => {
+                    added[tri] = true;
+                    primitive.push(next);
+                }
+                None => { break; }
+            }
+        }
+
+        if !output.is_empty() {
+            output.push(restart);
+        }
+        output.extend(primitive.iter().map(|&id| I::from(id)));
+    }
+
+    output
+}
+
+#[cfg(test)]
+use crate::math::point;
+#[cfg(test)]
+use crate::geometry_builder::{BuffersBuilder, Identity};
+#[cfg(test)]
+use crate::{FillTessellator, FillOptions, FillVertex, StrokeTessellator, StrokeOptions, StrokeVertex};
+#[cfg(test)]
+use crate::path::Path;
+#[cfg(test)]
+use crate::path::builder::Build;
+#[cfg(test)]
+use crate::extra::rust_logo::build_logo_path;
+
+#[cfg(test)]
+fn area<V>(buffers: &VertexBuffers<V, u16>, position: impl Fn(&V) -> Point) -> f32 {
+    buffers.indices.chunks(3).map(|tri| {
+        let a = position(&buffers.vertices[tri[0] as usize]);
+        let b = position(&buffers.vertices[tri[1] as usize]);
+        let c = position(&buffers.vertices[tri[2] as usize]);
+        (b - a).cross(c - a) * 0.5
+    }).sum()
+}
+
+#[test]
+fn weld_and_remove_degenerate() {
+    let mut buffers: VertexBuffers<(Point, u32), u16> = VertexBuffers::new();
+    buffers.vertices = vec![
+        (point(0.0, 0.0), 0),
+        (point(1.0, 0.0), 1),
+        (point(1.0, 1.0), 2),
+        (point(1.0, 0.0001), 3),
+        (point(0.0, 1.0), 4),
+        (point(2.0, 2.0), 5),
+        (point(0.5, 0.5), 6),
+    ];
+    buffers.indices = vec![
+        0, 1, 2,
+        0, 2, 4,
+        // Becomes degenerate after welding 1 and 3.
+        1, 3, 2,
+        // Zero area.
+        0, 6, 2,
+        3, 5, 2,
+    ];
+
+    weld_vertices(&mut buffers, 0.001, |v| v.0);
+    assert_eq!(buffers.vertices.len(), 6);
+    assert_eq!(buffers.indices, vec![0, 1, 2, 0, 2, 3, 1, 1, 2, 0, 5, 2, 1, 4, 2]);
+
+    remove_degenerate_triangles(&mut buffers, |v| v.0);
+    assert_eq!(buffers.indices, vec![0, 1, 2, 0, 2, 3, 1, 4, 2]);
+
+    remove_unused_vertices(&mut buffers);
+    // Custom data is preserved.
+    let ids: Vec<u32> = buffers.vertices.iter().map(|v| v.1).collect();
+    assert_eq!(ids, vec![0, 1, 2, 4, 5]);
+    assert_eq!(buffers.indices, vec![0, 1, 2, 0, 2, 3, 1, 4, 2]);
+}
+
+#[test]
+fn weld_with_predicate() {
+    let mut buffers: VertexBuffers<(Point, u32), u16> = VertexBuffers::new();
+    buffers.vertices = vec![
+        (point(0.0, 0.0), 0),
+        (point(0.0, 0.0), 1),
+        (point(0.0, 0.0), 0),
+    ];
+    buffers.indices = vec![0, 1, 2];
+
+    weld_vertices_with(&mut buffers, 0.0, |v| v.0, |a, b| a.1 == b.1);
+    assert_eq!(buffers.vertices.len(), 2);
+    assert_eq!(buffers.indices, vec![0, 1, 0]);
+}
+
+#[test]
+fn post_process_stroke() {
+    let mut path = Path::builder().with_svg();
+    build_logo_path(&mut path);
+    let path = path.build();
+
+    let mut buffers: VertexBuffers<StrokeVertex, u16> = VertexBuffers::new();
+    StrokeTessellator::new().tessellate_path(
+        path.iter(),
+        &StrokeOptions::tolerance(0.05),
+        &mut BuffersBuilder::new(&mut buffers, Identity),
+    ).unwrap();
+
+    let num_vertices = buffers.vertices.len();
+    let original_area = area(&buffers, |v| v.position);
+
+    weld_vertices(&mut buffers, 0.0001, |v| v.position);
+    remove_degenerate_triangles(&mut buffers, |v| v.position);
+    remove_unused_vertices(&mut buffers);
+
+    assert!(buffers.vertices.len() < num_vertices);
+    assert!((area(&buffers, |v| v.position) - original_area).abs() < 0.01);
+}
+
+#[test]
+fn optimize_fill_vertex_cache() {
+    // A fine grid of small squares makes a mesh with a lot of shared vertices.
+    let mut builder = Path::builder();
+    for i in 0..30 {
+        for j in 0..30 {
+            let x = i as f32 * 10.0;
+            let y = j as f32 * 10.0;
+            builder.polygon(&[
+                point(x, y), point(x + 10.0, y), point(x + 10.0, y + 10.0), point(x, y + 10.0),
+                point(x + 5.0, y + 12.0),
+            ]);
+        }
+    }
+    let path = builder.build();
+
+    let mut buffers: VertexBuffers<FillVertex, u16> = VertexBuffers::new();
+    FillTessellator::new().tessellate_path(
+        path.iter(),
+        &FillOptions::default(),
+        &mut BuffersBuilder::new(&mut buffers, Identity),
+    ).unwrap();
+
+    let mut sorted_before: Vec<[u16; 3]> = buffers.indices.chunks(3).map(|t| [t[0], t[1], t[2]]).collect();
+    let acmr_before = average_cache_miss_ratio(&buffers.indices, 16);
+
+    optimize_vertex_cache(&mut buffers.indices, buffers.vertices.len(), 32);
+
+    let acmr_after = average_cache_miss_ratio(&buffers.indices, 16);
+    assert!(acmr_after < acmr_before, "{} > {}", acmr_after, acmr_before);
+
+    // Same triangles with the same winding, in a different order.
+    let mut sorted_after: Vec<[u16; 3]> = buffers.indices.chunks(3).map(|t| [t[0], t[1], t[2]]).collect();
+    sorted_before.sort();
+    sorted_after.sort();
+    assert_eq!(sorted_before, sorted_after);
+}
+
+#[cfg(test)]
+fn canonical_triangles(indices: &[u16]) -> Vec<[u16; 3]> {
+    // Rotate the triangles so that the smallest index comes first, which preserves the winding.
+    let mut triangles: Vec<[u16; 3]> = indices.chunks(3).map(|t| {
+        let r = (0..3).min_by_key(|&i| t[i]).unwrap();
+        [t[r], t[(r + 1) % 3], t[(r + 2) % 3]]
+    }).collect();
+    triangles.sort();
+
+    triangles
+}
+
+#[test]
+fn strips_and_fans() {
+    // A quad strip: 0 2 4 6
+    //               1 3 5 7
+    let indices: Vec<u16> = vec![
+        0, 1, 2,  2, 1, 3,
+        2, 3, 4,  4, 3, 5,
+        4, 5, 6,  6, 5, 7,
+    ];
+    let strips = triangle_strips(&indices, std::u16::MAX);
+    assert_eq!(strips, vec![0, 1, 2, 3, 4, 5, 6, 7]);
+    assert_eq!(triangle_list(&strips, std::u16::MAX, Topology::Strip), indices);
+
+    // A triangle with the opposite winding can't be part of the strip.
+    let mut indices = indices;
+    indices.extend_from_slice(&[6, 8, 7]);
+    let strips = triangle_strips(&indices, std::u16::MAX);
+    assert_eq!(strips, vec![6, 8, 7, std::u16::MAX, 0, 1, 2, 3, 4, 5, 6, 7]);
+
+    // A fan around vertex 0.
+    let indices: Vec<u16> = vec![0, 1, 2,  0, 2, 3,  0, 3, 4,  5, 6, 7];
+    let fans = triangle_fans(&indices, std::u16::MAX);
+    assert_eq!(fans, vec![5, 6, 7, std::u16::MAX, 0, 1, 2, 3, 4]);
+    assert_eq!(
+        canonical_triangles(&triangle_list(&fans, std::u16::MAX, Topology::Fan)),
+        canonical_triangles(&indices),
+    );
+}
+
+#[test]
+fn stroke_and_fill_strips() {
+    let mut path = Path::builder().with_svg();
+    build_logo_path(&mut path);
+    let path = path.build();
+
+    let mut stroke: VertexBuffers<StrokeVertex, u16> = VertexBuffers::new();
+    StrokeTessellator::new().tessellate_path(
+        path.iter(),
+        &StrokeOptions::tolerance(0.05),
+        &mut BuffersBuilder::new(&mut stroke, Identity),
+    ).unwrap();
+
+    let strips = triangle_strips(&stroke.indices, std::u16::MAX);
+    assert!(strips.len() * 3 < stroke.indices.len() * 2);
+    assert_eq!(
+        canonical_triangles(&triangle_list(&strips, std::u16::MAX, Topology::Strip)),
+        canonical_triangles(&stroke.indices),
+    );
+
+    let mut fill: VertexBuffers<FillVertex, u16> = VertexBuffers::new();
+    FillTessellator::new().tessellate_path(
+        path.iter(),
+        &FillOptions::tolerance(0.05),
+        &mut BuffersBuilder::new(&mut fill, Identity),
+    ).unwrap();
+
+    for &topology in &[Topology::Strip, Topology::Fan] {
+        let primitives = match topology {
+            Topology::Strip => triangle_strips(&fill.indices, std::u16::MAX),
+            Topology::Fan => triangle_fans(&fill.indices, std::u16::MAX),
+        };
+        assert_eq!(
+            canonical_triangles(&triangle_list(&primitives, std::u16::MAX, topology)),
+            canonical_triangles(&fill.indices),
+        );
+    }
+}
@@ -0,0 +1,345 @@
+//! Geometry for the "stencil, then cover" rendering technique.
+//!
+//! Instead of triangulating the interior of a path, the path is rendered in two passes
+//! on GPUs that have a stencil buffer:
+//!
+//! - The stencil geometry is a triangle fan from the first point of each flattened
+//!   sub-path. Rendering it with the stencil operation set to increment for front
+//!   facing triangles and decrement for back facing triangles (without writing to the
+//!   color buffer) leaves the winding number of each pixel in the stencil buffer.
+//! - The cover geometry is a rectangle that contains the path. Rendering it with a stencil
+//!   test that depends on the fill rule (`!= 0` for `NonZero`, odd values for `EvenOdd`)
+//!   fills the pixels that are inside of the path. The cover pass usually resets the stencil
+//!   buffer as well.
+//!
+//! Generating this geometry is much cheaper than running the sweep line algorithm of the
+//! `FillTessellator`, which makes it a good fit for content that changes every frame.
+//! Since the fill rule is only applied when rendering, any fill rule is supported.
+//!
+//! ## Example
+//!
+//! ```
+//! # extern crate lyon_tessellation as tess;
+//! # use tess::path::Path;
+//! # use tess::math::point;
+//! # use tess::{StencilCoverTessellator, FillOptions, FillVertex, VertexBuffers, BuffersBuilder};
+//! # use tess::geometry_builder::Identity;
+//! # fn main() {
+//! let mut builder = Path::builder();
+//! builder.polygon(&[point(0.0, 0.0), point(10.0, 0.0), point(10.0, 10.0), point(0.0, 10.0)]);
+//! let path = builder.build();
+//!
+//! let mut stencil: VertexBuffers<FillVertex, u16> = VertexBuffers::new();
+//! let mut cover: VertexBuffers<FillVertex, u16> = VertexBuffers::new();
+//!
+//! StencilCoverTessellator::new().tessellate_path(
+//!     path.iter(),
+//!     &FillOptions::default(),
+//!     &mut BuffersBuilder::new(&mut stencil, Identity),
+//!     &mut BuffersBuilder::new(&mut cover, Identity),
+//! ).unwrap();
+//!
+//! assert_eq!(stencil.indices.len(), 6);
+//! assert_eq!(cover.indices.len(), 6);
+//! # }
+//! ```
+
+use crate::geometry_builder::{GeometryBuilder, GeometryBuilderError, Count, VertexId};
+use crate::basic_shapes::fill_rectangle;
+use crate::path::PathEvent;
+use crate::math::{Point, Rect, point, vector};
+use crate::{FillOptions, FillVertex, VertexSource, EventPosition, TessellationError};
+
+/// The number of vertices and indices generated for each pass.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct StencilCoverCount {
+    pub stencil: Count,
+    pub cover: Count,
+}
+
+/// Generates the geometry of the "stencil, then cover" rendering technique.
+///
+/// See the [module documentation](index.html).
+pub struct StencilCoverTessellator {
+    // The first vertex of the current sub-path's fan and the previous vertex.
+    pivot: Option<VertexId>,
+    previous: Option<VertexId>,
+    pivot_position: Point,
+    previous_position: Point,
+    min: Point,
+    max: Point,
+    sub_path: u32,
+    event: u32,
+}
+
+impl StencilCoverTessellator {
+    /// Constructor.
+    pub fn new() -> Self {
+        StencilCoverTessellator {
+            pivot: None,
+            previous: None,
+            pivot_position: point(0.0, 0.0),
+            previous_position: point(0.0, 0.0),
+            min: point(0.0, 0.0),
+            max: point(0.0, 0.0),
+            sub_path: 0,
+            event: 0,
+        }
+    }
+
+    /// Generate the stencil and cover geometry of a path.
+    ///
+    /// The vertices of the stencil geometry are on the flattened path and their normals are
+    /// not computed (they are set to `vector(0.0, 0.0)`). The winding of the triangles
+    /// depends on the direction of the edges, which is what the stencil operations rely on,
+    /// so back-face culling must be disabled when rendering it.
+    ///
+    /// The cover geometry is the bounding rectangle of the flattened path, or nothing if
+    /// the path is empty.
+    ///
+    /// Only the `tolerance` option is used. The fill rule is applied by the stencil test
+    /// of the cover pass. The `clip_rect` and `max_edge_length` options are not supported.
+    pub fn tessellate_path<Iter>(
+        &mut self,
+        it: Iter,
+        options: &FillOptions,
+        stencil_output: &mut dyn GeometryBuilder<FillVertex>,
+        cover_output: &mut dyn GeometryBuilder<FillVertex>,
+    ) -> Result<StencilCoverCount, TessellationError>
+    where
+        Iter: IntoIterator<Item = PathEvent>,
+    {
+        if options.clip_rect.is_some() || options.max_edge_length.is_some() {
+            return Err(TessellationError::UnsupportedParamater);
+        }
+
+        stencil_output.begin_geometry();
+        if let Err(e) = self.stencil(it, options.tolerance, stencil_output) {
+            stencil_output.abort_geometry();
+            return Err(e.into());
+        }
+        let stencil = stencil_output.end_geometry();
+
+        let cover = if self.min.x <= self.max.x {
+            let rect = Rect::new(self.min, (self.max - self.min).to_size());
+            fill_rectangle(&rect, options, cover_output)?
+        } else {
+            cover_output.begin_geometry();
+            cover_output.end_geometry()
+        };
+
+        Ok(StencilCoverCount { stencil, cover })
+    }
+
+    fn stencil<Iter>(
+        &mut self,
+        it: Iter,
+        tolerance: f32,
+        output: &mut dyn GeometryBuilder<FillVertex>,
+    ) -> Result<(), GeometryBuilderError>
+    where
+        Iter: IntoIterator<Item = PathEvent>,
+    {
+        self.pivot = None;
+        self.previous = None;
+        self.min = point(std::f32::MAX, std::f32::MAX);
+        self.max = point(std::f32::MIN, std::f32::MIN);
+        self.sub_path = 0;
+
+        let mut first_sub_path = true;
+        for (idx, evt) in it.into_iter().enumerate() {
+            self.event = idx as u32;
+            match evt {
+                PathEvent::MoveTo(to) => {
+                    if !first_sub_path {
+                        self.sub_path += 1;
+                    }
+                    first_sub_path = false;
+                    self.pivot = None;
+                    self.previous = None;
+                    self.add_point(to, VertexSource::Endpoint(self.position(0.0)), output)?;
+                }
+                PathEvent::Line(segment) => {
+                    self.add_point(segment.to, VertexSource::Endpoint(self.position(1.0)), output)?;
+                }
+                PathEvent::Quadratic(segment) => {
+                    let mut result = Ok(());
+                    segment.for_each_flattened_with_t(tolerance, &mut |p, t| {
+                        if result.is_ok() {
+                            result = self.add_curve_point(p, t, output);
+                        }
+                    });
+                    result?;
+                }
+                PathEvent::Cubic(segment) => {
+                    let mut result = Ok(());
+                    segment.for_each_flattened_with_t(tolerance, &mut |p, t| {
+                        if result.is_ok() {
+                            result = self.add_curve_point(p, t, output);
+                        }
+                    });
+                    result?;
+                }
+                PathEvent::Close(..) => {
+                    // The closing edge ends at the pivot, so its triangle is empty.
+                    self.pivot = None;
+                    self.previous = None;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn position(&self, t: f32) -> EventPosition {
+        EventPosition { sub_path: self.sub_path, event: self.event, t }
+    }
+
+    fn add_curve_point(
+        &mut self,
+        p: Point,
+        t: f32,
+        output: &mut dyn GeometryBuilder<FillVertex>,
+    ) -> Result<(), GeometryBuilderError> {
+        let source = if t == 1.0 {
+            VertexSource::Endpoint(self.position(t))
+        } else {
+            VertexSource::Curve(self.position(t))
+        };
+
+        self.add_point(p, source, output)
+    }
+
+    fn add_point(
+        &mut self,
+        position: Point,
+        source: VertexSource,
+        output: &mut dyn GeometryBuilder<FillVertex>,
+    ) -> Result<(), GeometryBuilderError> {
+        self.min = self.min.min(position);
+        self.max = self.max.max(position);
+
+        if self.previous.is_some() && position == self.previous_position {
+            return Ok(());
+        }
+
+        // Edges that touch the pivot don't contribute any triangle.
+        if let Some(pivot) = self.pivot {
+            if position == self.pivot_position {
+                self.previous = Some(pivot);
+                self.previous_position = position;
+                return Ok(());
+            }
+        }
+
+        let id = output.add_vertex(FillVertex {
+            position,
+            normal: vector(0.0, 0.0),
+            source,
+        })?;
+
+        match (self.pivot, self.previous) {
+            (Some(pivot), Some(previous)) if previous != pivot => {
+                output.add_triangle(pivot, previous, id);
+            }
+            (None, _) => {
+                self.pivot = Some(id);
+                self.pivot_position = position;
+            }
+            _ => {}
+        }
+        self.previous = Some(id);
+        self.previous_position = position;
+
+        Ok(())
+    }
+}
+
+impl Default for StencilCoverTessellator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+use crate::geometry_builder::{VertexBuffers, BuffersBuilder, Identity};
+#[cfg(test)]
+use crate::path::Path;
+
+// Computes the winding number of a point by summing the orientations of the
+// triangles that contain it, the way the stencil buffer does.
+#[cfg(test)]
+fn winding_number(buffers: &VertexBuffers<FillVertex, u16>, p: Point) -> i32 {
+    let mut winding = 0;
+    for tri in buffers.indices.chunks(3) {
+        let a = buffers.vertices[tri[0] as usize].position;
+        let b = buffers.vertices[tri[1] as usize].position;
+        let c = buffers.vertices[tri[2] as usize].position;
+        let area = (b - a).cross(c - a);
+        let sign = if area > 0.0 { 1 } else { -1 };
+        let inside = (b - a).cross(p - a) * sign as f32 > 0.0
+            && (c - b).cross(p - b) * sign as f32 > 0.0
+            && (a - c).cross(p - c) * sign as f32 > 0.0;
+        if inside {
+            winding += sign;
+        }
+    }
+
+    winding
+}
+
+#[test]
+fn stencil_cover_winding() {
+    // Two overlapping squares with the same orientation and a hole with the
+    // opposite orientation.
+    let mut builder = Path::builder();
+    builder.polygon(&[point(0.0, 0.0), point(10.0, 0.0), point(10.0, 10.0), point(0.0, 10.0)]);
+    builder.polygon(&[point(5.0, 5.0), point(15.0, 5.0), point(15.0, 15.0), point(5.0, 15.0)]);
+    builder.polygon(&[point(1.0, 1.0), point(1.0, 3.0), point(3.0, 3.0), point(3.0, 1.0)]);
+    builder.move_to(point(20.0, 0.0));
+    builder.quadratic_bezier_to(point(30.0, 0.0), point(30.0, 10.0));
+    builder.close();
+    let path = builder.build();
+
+    let mut stencil: VertexBuffers<FillVertex, u16> = VertexBuffers::new();
+    let mut cover: VertexBuffers<FillVertex, u16> = VertexBuffers::new();
+    let count = StencilCoverTessellator::new().tessellate_path(
+        path.iter(),
+        &FillOptions::non_zero().with_tolerance(0.01),
+        &mut BuffersBuilder::new(&mut stencil, Identity),
+        &mut BuffersBuilder::new(&mut cover, Identity),
+    ).unwrap();
+
+    assert_eq!(count.stencil.indices as usize, stencil.indices.len());
+    assert_eq!(count.cover, Count { vertices: 4, indices: 6 });
+
+    let orientation = winding_number(&stencil, point(2.0, 8.0));
+    assert!(orientation == 1 || orientation == -1);
+    assert_eq!(winding_number(&stencil, point(7.0, 6.0)), 2 * orientation);
+    assert_eq!(winding_number(&stencil, point(12.0, 11.0)), orientation);
+    assert_eq!(winding_number(&stencil, point(2.5, 1.5)), 0);
+    assert_eq!(winding_number(&stencil, point(17.0, 2.0)), 0);
+    assert_eq!(winding_number(&stencil, point(24.0, 2.0)), orientation);
+    assert_eq!(winding_number(&stencil, point(21.0, 9.0)), 0);
+
+    let min = cover.vertices.iter().fold(point(100.0, 100.0), |m, v| m.min(v.position));
+    let max = cover.vertices.iter().fold(point(-100.0, -100.0), |m, v| m.max(v.position));
+    assert_eq!((min, max), (point(0.0, 0.0), point(30.0, 15.0)));
+}
+
+#[test]
+fn stencil_cover_empty() {
+    let path = Path::builder().build();
+
+    let mut stencil: VertexBuffers<FillVertex, u16> = VertexBuffers::new();
+    let mut cover: VertexBuffers<FillVertex, u16> = VertexBuffers::new();
+    let count = StencilCoverTessellator::new().tessellate_path(
+        path.iter(),
+        &FillOptions::default(),
+        &mut BuffersBuilder::new(&mut stencil, Identity),
+        &mut BuffersBuilder::new(&mut cover, Identity),
+    ).unwrap();
+
+    assert_eq!(count.stencil, Count { vertices: 0, indices: 0 });
+    assert_eq!(count.cover, Count { vertices: 0, indices: 0 });
+}
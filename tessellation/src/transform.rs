@@ -0,0 +1,353 @@
+//! Tessellation of transformed paths.
+//!
+//! When a path is rendered with a transform, the tolerance that matters is the one in the
+//! transformed (typically screen) space. These functions tessellate the path in its own
+//! coordinate space with a tolerance that is adjusted for the transform, and can apply the
+//! transform to the generated vertices.
+//!
+//! Strokes that have a constant width in the transformed space (non-scaling strokes) are
+//! computed the other way around: the path is transformed and stroked in the transformed
+//! space, and the inverse transform can be applied to the generated vertices.
+
+use crate::{FillTessellator, StrokeTessellator, FillOptions, StrokeOptions, TessellationResult, TessellationError};
+use crate::{FillVertex, StrokeVertex};
+use crate::geometry_builder::{GeometryBuilder, GeometryBuilderError, Count, VertexId};
+use crate::math::Transform2D;
+use crate::path::PathEvent;
+use crate::path::iterator::Transformed;
+
+/// The coordinate space of the vertices generated by `tessellate_path_with_transform`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub enum OutputSpace {
+    /// The vertices are in the coordinate space of the path.
+    Local,
+    /// The transform is applied to the positions and normals of the vertices.
+    Transformed,
+}
+
+impl FillTessellator {
+    /// Compute the tessellation of a path that is rendered with a transform.
+    ///
+    /// The tolerance is measured after the transform is applied. With non-uniform scales,
+    /// it is adjusted for the direction that is scaled the most. The other options are
+    /// expressed in the coordinate space of the path.
+    ///
+    /// With `OutputSpace::Transformed`, the positions and normals of the vertices are
+    /// transformed. Note that transforms that flip the orientation also flip the winding
+    /// of the triangles.
+    pub fn tessellate_path_with_transform<Iter>(
+        &mut self,
+        it: Iter,
+        transform: &Transform2D,
+        output_space: OutputSpace,
+        options: &FillOptions,
+        output: &mut dyn GeometryBuilder<FillVertex>,
+    ) -> TessellationResult
+    where
+        Iter: IntoIterator<Item = PathEvent>,
+    {
+        let mut options = *options;
+        options.tolerance = local_tolerance(options.tolerance, transform);
+
+        match output_space {
+            OutputSpace::Local => self.tessellate_path(it, &options, output),
+            OutputSpace::Transformed => self.tessellate_path(
+                it,
+                &options,
+                &mut TransformedOutput { output, transform },
+            ),
+        }
+    }
+}
+
+impl StrokeTessellator {
+    /// Compute the tessellation of a path that is rendered with a transform.
+    ///
+    /// The tolerance is measured after the transform is applied. With non-uniform scales,
+    /// it is adjusted for the direction that is scaled the most. The line width and the
+    /// other options are expressed in the coordinate space of the path, which is how the
+    /// stroke looks when it is transformed.
+    ///
+    /// With `OutputSpace::Transformed`, the positions and normals of the vertices are
+    /// transformed. The advancement is still measured in the coordinate space of the path.
+    pub fn tessellate_path_with_transform<Iter>(
+        &mut self,
+        it: Iter,
+        transform: &Transform2D,
+        output_space: OutputSpace,
+        options: &StrokeOptions,
+        output: &mut dyn GeometryBuilder<StrokeVertex>,
+    ) -> TessellationResult
+    where
+        Iter: IntoIterator<Item = PathEvent>,
+    {
+        let mut options = *options;
+        options.tolerance = local_tolerance(options.tolerance, transform);
+
+        match output_space {
+            OutputSpace::Local => self.tessellate_path(it, &options, output),
+            OutputSpace::Transformed => self.tessellate_path(
+                it,
+                &options,
+                &mut TransformedOutput { output, transform },
+            ),
+        }
+    }
+}
+
+impl StrokeTessellator {
+    /// Compute the tessellation of a stroke that has a constant width after the transform
+    /// is applied, like SVG's `vector-effect: non-scaling-stroke`.
+    ///
+    /// The path is transformed before it is stroked, so that the line width, the miter
+    /// limit, the joins and the caps are evaluated in the transformed space, even with
+    /// non-uniform scales and skews. All of the options are expressed in the transformed
+    /// space.
+    ///
+    /// With `OutputSpace::Local`, the inverse of the transform is applied to the positions
+    /// and normals of the vertices, so that they produce the expected stroke when they are
+    /// rendered with the transform. This fails with `TessellationError::UnsupportedParamater`
+    /// if the transform isn't invertible. The advancement is measured in the transformed
+    /// space in both cases.
+    pub fn tessellate_path_non_scaling<Iter>(
+        &mut self,
+        it: Iter,
+        transform: &Transform2D,
+        output_space: OutputSpace,
+        options: &StrokeOptions,
+        output: &mut dyn GeometryBuilder<StrokeVertex>,
+    ) -> TessellationResult
+    where
+        Iter: IntoIterator<Item = PathEvent>,
+    {
+        let transformed = Transformed::new(transform, it.into_iter());
+
+        match output_space {
+            OutputSpace::Transformed => self.tessellate_path(transformed, options, output),
+            OutputSpace::Local => {
+                let inverse = transform.inverse().ok_or(TessellationError::UnsupportedParamater)?;
+                self.tessellate_path(
+                    transformed,
+                    options,
+                    &mut TransformedOutput { output, transform: &inverse },
+                )
+            }
+        }
+    }
+}
+
+// The largest factor by which the transform scales distances, that is the largest
+// singular value of its linear part.
+fn max_scale(transform: &Transform2D) -> f32 {
+    let (a, b, c, d) = (transform.m11, transform.m12, transform.m21, transform.m22);
+    let sum = a * a + b * b + c * c + d * d;
+    let det = a * d - b * c;
+
+    ((sum + (sum * sum - 4.0 * det * det).max(0.0).sqrt()) * 0.5).sqrt()
+}
+
+// A distance in the coordinate space of the path that is at most `tolerance` after
+// the transform is applied.
+fn local_tolerance(tolerance: f32, transform: &Transform2D) -> f32 {
+    let scale = max_scale(transform);
+    if scale > 0.0 && scale.is_finite() {
+        tolerance / scale
+    } else {
+        tolerance
+    }
+}
+
+trait TransformVertex {
+    fn transform(&mut self, transform: &Transform2D);
+}
+
+impl TransformVertex for FillVertex {
+    fn transform(&mut self, transform: &Transform2D) {
+        self.position = transform.transform_point(self.position);
+        self.normal = transform.transform_vector(self.normal);
+    }
+}
+
+impl TransformVertex for StrokeVertex {
+    fn transform(&mut self, transform: &Transform2D) {
+        self.position = transform.transform_point(self.position);
+        self.normal = transform.transform_vector(self.normal);
+    }
+}
+
+struct TransformedOutput<'l, Vertex> {
+    output: &'l mut dyn GeometryBuilder<Vertex>,
+    transform: &'l Transform2D,
+}
+
+impl<'l, Vertex: TransformVertex> GeometryBuilder<Vertex> for TransformedOutput<'l, Vertex> {
+    fn begin_geometry(&mut self) { self.output.begin_geometry(); }
+
+    fn end_geometry(&mut self) -> Count { self.output.end_geometry() }
+
+    fn add_vertex(&mut self, mut vertex: Vertex) -> Result<VertexId, GeometryBuilderError> {
+        vertex.transform(self.transform);
+        self.output.add_vertex(vertex)
+    }
+
+    fn add_triangle(&mut self, a: VertexId, b: VertexId, c: VertexId) {
+        self.output.add_triangle(a, b, c);
+    }
+
+    fn abort_geometry(&mut self) { self.output.abort_geometry(); }
+}
+
+#[cfg(test)]
+use crate::geometry_builder::{VertexBuffers, BuffersBuilder, Identity};
+#[cfg(test)]
+use crate::math::point;
+#[cfg(test)]
+use crate::path::Path;
+#[cfg(test)]
+use crate::path::iterator::PathIterator;
+#[cfg(test)]
+use crate::LineJoin;
+
+#[cfg(test)]
+fn circle() -> Path {
+    let mut builder = Path::builder();
+    builder.move_to(point(1.0, 0.0));
+    builder.cubic_bezier_to(point(1.0, 0.55), point(0.55, 1.0), point(0.0, 1.0));
+    builder.cubic_bezier_to(point(-0.55, 1.0), point(-1.0, 0.55), point(-1.0, 0.0));
+    builder.cubic_bezier_to(point(-1.0, -0.55), point(-0.55, -1.0), point(0.0, -1.0));
+    builder.cubic_bezier_to(point(0.55, -1.0), point(1.0, -0.55), point(1.0, 0.0));
+    builder.close();
+
+    builder.build()
+}
+
+#[test]
+fn max_scale_of_transforms() {
+    assert_eq!(max_scale(&Transform2D::identity()), 1.0);
+    assert_eq!(max_scale(&Transform2D::create_scale(2.0, 30.0)), 30.0);
+    assert!((max_scale(&Transform2D::create_rotation(crate::math::Angle::radians(1.0)).post_scale(3.0, 3.0)) - 3.0).abs() < 1e-5);
+    assert_eq!(max_scale(&Transform2D::create_scale(0.0, 0.0)), 0.0);
+}
+
+#[test]
+fn fill_with_transform() {
+    let path = circle();
+    let transform = Transform2D::create_scale(100.0, 10.0).post_translate(crate::math::vector(5.0, 5.0));
+
+    let mut transformed: VertexBuffers<FillVertex, u16> = VertexBuffers::new();
+    FillTessellator::new().tessellate_path_with_transform(
+        path.iter(),
+        &transform,
+        OutputSpace::Transformed,
+        &FillOptions::tolerance(0.1),
+        &mut BuffersBuilder::new(&mut transformed, Identity),
+    ).unwrap();
+
+    let mut local: VertexBuffers<FillVertex, u16> = VertexBuffers::new();
+    FillTessellator::new().tessellate_path_with_transform(
+        path.iter(),
+        &transform,
+        OutputSpace::Local,
+        &FillOptions::tolerance(0.1),
+        &mut BuffersBuilder::new(&mut local, Identity),
+    ).unwrap();
+
+    assert_eq!(transformed.indices, local.indices);
+    for (a, b) in transformed.vertices.iter().zip(local.vertices.iter()) {
+        assert!((a.position - transform.transform_point(b.position)).length() < 1e-3);
+    }
+
+    // At least as precise as tessellating the transformed path.
+    let mut reference: VertexBuffers<FillVertex, u16> = VertexBuffers::new();
+    FillTessellator::new().tessellate_path(
+        path.iter().transformed(&transform),
+        &FillOptions::tolerance(0.1),
+        &mut BuffersBuilder::new(&mut reference, Identity),
+    ).unwrap();
+
+    assert!(transformed.vertices.len() >= reference.vertices.len());
+}
+
+#[test]
+fn stroke_with_transform() {
+    let path = circle();
+    let transform = Transform2D::create_scale(1.0, 50.0);
+
+    let mut transformed: VertexBuffers<StrokeVertex, u16> = VertexBuffers::new();
+    StrokeTessellator::new().tessellate_path_with_transform(
+        path.iter(),
+        &transform,
+        OutputSpace::Transformed,
+        &StrokeOptions::tolerance(0.1).with_line_width(0.1),
+        &mut BuffersBuilder::new(&mut transformed, Identity),
+    ).unwrap();
+
+    let mut untransformed: VertexBuffers<StrokeVertex, u16> = VertexBuffers::new();
+    StrokeTessellator::new().tessellate_path(
+        path.iter(),
+        &StrokeOptions::tolerance(0.1).with_line_width(0.1),
+        &mut BuffersBuilder::new(&mut untransformed, Identity),
+    ).unwrap();
+
+    assert!(transformed.vertices.len() > untransformed.vertices.len());
+    for v in &transformed.vertices {
+        // The line width is scaled along with the path.
+        assert!(v.position.y.abs() <= 50.0 * 1.051);
+    }
+}
+
+#[test]
+fn non_scaling_stroke() {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(1.0, 0.0));
+    builder.line_to(point(1.0, 1.0));
+    let path = builder.build();
+
+    // A skew and a non-uniform scale.
+    let transform = Transform2D::row_major(10.0, 0.0, 5.0, 100.0, 0.0, 0.0);
+    let options = StrokeOptions::default().with_line_width(2.0).with_line_join(LineJoin::Round);
+
+    let mut transformed: VertexBuffers<StrokeVertex, u16> = VertexBuffers::new();
+    StrokeTessellator::new().tessellate_path_non_scaling(
+        path.iter(),
+        &transform,
+        OutputSpace::Transformed,
+        &options,
+        &mut BuffersBuilder::new(&mut transformed, Identity),
+    ).unwrap();
+
+    // The first edge goes from (0, 0) to (10, 0) in the transformed space and
+    // the stroke is 2 units wide there.
+    for v in &transformed.vertices {
+        if v.position.x < 5.0 {
+            assert!((v.position.y.abs() - 1.0).abs() < 1e-4);
+        }
+    }
+
+    let mut local: VertexBuffers<StrokeVertex, u16> = VertexBuffers::new();
+    StrokeTessellator::new().tessellate_path_non_scaling(
+        path.iter(),
+        &transform,
+        OutputSpace::Local,
+        &options,
+        &mut BuffersBuilder::new(&mut local, Identity),
+    ).unwrap();
+
+    assert_eq!(local.indices, transformed.indices);
+    for (a, b) in transformed.vertices.iter().zip(local.vertices.iter()) {
+        assert!((a.position - transform.transform_point(b.position)).length() < 1e-3);
+        assert!((a.normal - transform.transform_vector(b.normal)).length() < 1e-3);
+    }
+
+    let singular = Transform2D::create_scale(1.0, 0.0);
+    let result = StrokeTessellator::new().tessellate_path_non_scaling(
+        path.iter(),
+        &singular,
+        OutputSpace::Local,
+        &options,
+        &mut BuffersBuilder::new(&mut local, Identity),
+    );
+    assert_eq!(result, Err(TessellationError::UnsupportedParamater));
+}